@@ -80,6 +80,62 @@ impl Key {
             Key::Sub(_, _, opt) => *opt,
         }
     }
+
+    /// The name of the key in the config file, which is what serde's
+    /// kebab-case renaming gives
+    pub fn config_name(&self) -> Option<String> {
+        match self {
+            Key::Field(_, _, _, Deserialize::Skip, _) => None,
+            _ => Some(self.name().to_string().replace('_', "-")),
+        }
+    }
+}
+
+/// Generate `find_unknown_keys` for a config struct, which reports every key
+/// in a table that isn't one of its fields and checks subtables as well
+fn gen_unknown_keys(name: &Ident, keys: &SubKeys) -> TokenStream2 {
+    let known = keys
+        .keys
+        .iter()
+        .filter_map(|k| k.config_name())
+        .collect::<Vec<_>>();
+    let mut subs = TokenStream2::new();
+    for key in &keys.keys {
+        if let (Key::Sub(..), Some(key_name), Some(type_)) =
+            (key, key.config_name(), key.type_name())
+        {
+            subs.extend(quote! {
+                (#key_name, toml::Value::Table(sub)) => #type_::find_unknown_keys(sub, &key_path, unknown),
+            });
+        }
+    }
+    quote! {
+        #[allow(dead_code)]
+        impl #name {
+            /// Call `unknown` with the path and name of every key in a table
+            /// that isn't a field of this config, along with the keys that
+            /// are
+            pub fn find_unknown_keys(
+                table: &toml::Table,
+                path: &str,
+                unknown: &mut dyn FnMut(&str, &str, &[&str]),
+            ) {
+                let known: &[&str] = &[#(#known),*];
+                for (key, value) in table {
+                    let key_path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{path}.{key}")
+                    };
+                    match (key.as_str(), value) {
+                        #subs
+                        (key, _) if known.contains(&key) => {}
+                        (key, _) => unknown(&key_path, key, known),
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl Gen for Key {
@@ -200,6 +256,8 @@ impl Gen for Key {
                     None
                 };
 
+                let unknown_keys = gen_unknown_keys(name.as_ref().unwrap(), sub);
+
                 Ok(quote! {
                     #pregen
 
@@ -210,6 +268,8 @@ impl Gen for Key {
                     }
 
                     #default
+
+                    #unknown_keys
                 })
             },
         }
@@ -304,6 +364,7 @@ impl Gen for ConfigDecl {
         let gen = self.keys.gen()?;
 
         let name = &self.name;
+        let unknown_keys = gen_unknown_keys(name, &self.keys);
         Ok(quote! {
             #pregen
 
@@ -312,6 +373,8 @@ impl Gen for ConfigDecl {
             pub struct #name {
                 #gen
             }
+
+            #unknown_keys
         })
     }
 
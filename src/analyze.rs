@@ -1,25 +1,62 @@
-use crate::{builder::builder::Builder, cmake, config::Config};
+use crate::{builder::builder::Builder, cmake, config::Config, examples};
+use clang::diagnostic::Severity;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::{fs, path::PathBuf, process::Command, sync::Arc, time::Duration};
+use log::{debug, error, info, warn};
+use std::{
+    fs,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::Arc,
+    time::Duration,
+};
 
-fn run_command(cmd: &String) -> Result<(), String> {
-    let args =
-        shlex::split(cmd).unwrap_or_else(|| panic!("Unable to parse prebuild command `{cmd}`"));
-    let exit = Command::new(&args[0])
+/// Environment variables describing the build, passed to hook commands
+pub fn hook_env(config: Arc<Config>) -> Vec<(&'static str, String)> {
+    vec![
+        (
+            "FLASH_INPUT_DIR",
+            config.input_dir.to_string_lossy().to_string(),
+        ),
+        (
+            "FLASH_OUTPUT_DIR",
+            config.output_dir.to_string_lossy().to_string(),
+        ),
+        (
+            "FLASH_OUTPUT_URL",
+            config
+                .output_url
+                .as_ref()
+                .map(|u| u.to_string())
+                .unwrap_or_default(),
+        ),
+        ("FLASH_PROJECT_NAME", config.project.name.clone()),
+        ("FLASH_PROJECT_VERSION", config.project.version.clone()),
+    ]
+}
+
+pub async fn run_command(kind: &str, cmd: &String, env: &[(&str, String)]) -> Result<(), String> {
+    let args = shlex::split(cmd)
+        .filter(|args| !args.is_empty())
+        .ok_or_else(|| format!("Unable to parse {kind} command `{cmd}`"))?;
+    let exit = tokio::process::Command::new(&args[0])
         .args(&args[1..])
-        .spawn()
-        .map_err(|e| format!("Unable to execute prebuild command `{cmd}`: {e}"))?
-        .wait()
-        .unwrap();
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .status()
+        .await
+        .map_err(|e| format!("Unable to execute {kind} command `{cmd}`: {e}"))?;
     if exit.success() {
         Ok(())
     } else {
-        Err(format!("Prebuild command `{cmd}` failed"))
+        Err(format!("The {kind} command `{cmd}` failed"))
     }
 }
 
 fn create_analyzable_file(config: Arc<Config>) -> Result<PathBuf, String> {
-    let out_path = config.output_dir.join("_analyze.cpp");
+    let out_path = config.output_dir.join(if config.is_c() {
+        "_analyze.c"
+    } else {
+        "_analyze.cpp"
+    });
 
     let mut data = String::from(
         "// File generated by Flash for including all headers in order to\n\
@@ -34,7 +71,168 @@ fn create_analyzable_file(config: Arc<Config>) -> Result<PathBuf, String> {
     Ok(out_path)
 }
 
+/// Ask the system's compiler for the directories it searches for system
+/// headers, from the output of `clang++ -E -v`
+fn detect_system_includes(config: Arc<Config>) -> Vec<PathBuf> {
+    let (compiler, language) = if config.is_c() {
+        (std::env::var("CC").unwrap_or(String::from("clang")), "c")
+    } else {
+        (
+            std::env::var("CXX").unwrap_or(String::from("clang++")),
+            "c++",
+        )
+    };
+    let output = match Command::new(&compiler)
+        .args(["-E", "-x", language, "-", "-v"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => {
+            warn!("Unable to detect system include directories with {compiler}: {e}");
+            return Vec::new();
+        }
+    };
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .skip_while(|line| !line.starts_with("#include <...> search starts here:"))
+        .skip(1)
+        .take_while(|line| !line.starts_with("End of search list."))
+        // Frameworks on macOS are found through the sysroot instead
+        .filter(|line| !line.ends_with("(framework directory)"))
+        .map(|line| PathBuf::from(line.trim()))
+        .collect()
+}
+
+/// Find the SDK to use as the sysroot on macOS
+fn detect_sysroot() -> Option<PathBuf> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    let output = Command::new("xcrun").arg("--show-sdk-path").output().ok()?;
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    (output.status.success() && !path.is_empty()).then(|| PathBuf::from(path))
+}
+
+/// Get the arguments for finding the standard library and other system
+/// headers, unless the compile args already say where they are
+fn system_include_args(config: Arc<Config>, args: &[String]) -> Vec<String> {
+    if args.iter().any(|a| a == "-nostdinc" || a == "-nostdinc++") {
+        return Vec::new();
+    }
+    let mut res = Vec::new();
+
+    if !args
+        .iter()
+        .any(|a| a.starts_with("-isysroot") || a.starts_with("--sysroot"))
+        && let Some(sysroot) = config.analysis.sysroot.clone().or_else(|| {
+            config
+                .analysis
+                .detect_system_includes
+                .then(detect_sysroot)
+                .flatten()
+        })
+    {
+        debug!("Using sysroot {}", sysroot.to_string_lossy());
+        res.extend(["-isysroot".into(), sysroot.to_string_lossy().into_owned()]);
+    }
+
+    // The host compiler's directories are for the host, so they'd be wrong
+    // when the args target something else
+    let cross_compiling = args.iter().any(|a| {
+        a == "-target"
+            || a.starts_with("--target")
+            || a.starts_with("--sysroot")
+            || a.starts_with("-isysroot")
+    });
+    let includes = match config.analysis.system_includes {
+        Some(ref includes) => includes.clone(),
+        None if config.analysis.detect_system_includes && !cross_compiling => {
+            detect_system_includes(config.clone())
+        }
+        None => Vec::new(),
+    };
+    for dir in includes {
+        debug!("Using system include directory {}", dir.to_string_lossy());
+        res.extend(["-isystem".into(), dir.to_string_lossy().into_owned()]);
+    }
+    res
+}
+
+/// Get the language for `-x` to parse headers as
+fn header_language(config: &Config) -> &'static str {
+    if config.is_c() {
+        "c-header"
+    } else {
+        "c++-header"
+    }
+}
+
+/// Precompile the header in `analysis.precompile` with the given args, unless
+/// it was already precompiled with the same args in an earlier run
+fn precompile_header(
+    config: Arc<Config>,
+    index: &clang::Index,
+    args: &[String],
+    force: bool,
+) -> Result<Option<PathBuf>, String> {
+    let Some(ref header) = config.analysis.precompile else {
+        return Ok(None);
+    };
+    let pch = config.output_dir.join("_precompiled.pch");
+    let args_file = config.output_dir.join("_precompiled.args");
+    let args_text = args.join("\n");
+    if !force && pch.exists() && fs::read_to_string(&args_file).is_ok_and(|a| a == args_text) {
+        debug!("Reusing precompiled header {}", pch.to_string_lossy());
+        return Ok(Some(pch));
+    }
+
+    info!("Precompiling {}", header.to_string_lossy());
+    let mut header_args = args.to_vec();
+    header_args.extend(["-x".into(), header_language(&config).into()]);
+    let unit = index
+        .parser(config.input_dir.join(header))
+        .arguments(&header_args)
+        .incomplete(true)
+        .parse()
+        .map_err(|e| format!("Unable to precompile {}: {e}", header.to_string_lossy()))?;
+    unit.save(&pch)
+        .map_err(|e| format!("Unable to save precompiled header: {e}"))?;
+    fs::write(&args_file, args_text)
+        .map_err(|e| format!("Unable to save precompiled header: {e}"))?;
+    Ok(Some(pch))
+}
+
+/// Log the errors clang found while parsing the headers, returning how many
+/// there were
+fn report_clang_errors(unit: &clang::TranslationUnit) -> usize {
+    let errors = unit
+        .get_diagnostics()
+        .into_iter()
+        .filter(|d| matches!(d.get_severity(), Severity::Error | Severity::Fatal))
+        .collect::<Vec<_>>();
+    for diagnostic in &errors {
+        let location = diagnostic.get_location().get_file_location();
+        let file = location
+            .file
+            .map(|f| f.get_path().to_string_lossy().into_owned())
+            .unwrap_or_default();
+        error!(
+            file = file.as_str(), line = location.line;
+            "{}", diagnostic.get_text()
+        );
+    }
+    errors.len()
+}
+
 async fn analyze_with_clang(config: Arc<Config>, args: &[String]) -> Result<(), String> {
+    let mut args = [args, system_include_args(config.clone(), args).as_slice()].concat();
+    if config.is_c() {
+        args.extend(["-x".into(), "c".into()]);
+    }
+    let args = &args;
+
     // Initialize clang
     let clang = clang::Clang::new()?;
     let index = clang::Index::new(&clang, false, true);
@@ -42,7 +240,11 @@ async fn analyze_with_clang(config: Arc<Config>, args: &[String]) -> Result<(),
     // Create a single source file that includes all headers
     let target_src = create_analyzable_file(config.clone())?;
 
-    let pbar = Arc::from(ProgressBar::new_spinner());
+    let pbar = Arc::from(if config.quiet {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new_spinner()
+    });
     pbar.set_style(
         ProgressStyle::with_template("{msg:>15} {spinner} [{elapsed_precise}]")
             .unwrap()
@@ -63,17 +265,88 @@ async fn analyze_with_clang(config: Arc<Config>, args: &[String]) -> Result<(),
     pbar.set_message("Analyzing");
     pbar.enable_steady_tick(Duration::from_millis(50));
 
+    info!(phase = "parse"; "Parsing headers");
+
     // Create parser
-    let unit = index.parser(&target_src).arguments(args).parse()?;
+    let parse = |pch: Option<PathBuf>| {
+        let mut unit_args = args.clone();
+        if let Some(pch) = pch {
+            unit_args.extend(["-include-pch".into(), pch.to_string_lossy().into_owned()]);
+        }
+        index
+            .parser(&target_src)
+            .arguments(&unit_args)
+            .parse()
+            .map(|unit| (unit, unit_args))
+    };
+    let (mut unit, mut unit_args) = parse(precompile_header(config.clone(), &index, args, false)?)?;
+    // A precompiled header from an earlier run is rejected by clang if any of
+    // the headers in it have changed since, so it has to be made again
+    if config.analysis.precompile.is_some()
+        && unit
+            .get_diagnostics()
+            .iter()
+            .any(|d| d.get_severity() == Severity::Fatal)
+    {
+        info!("Precompiled header is out of date");
+        (unit, unit_args) = parse(precompile_header(config.clone(), &index, args, true)?)?;
+    }
+    // Errors in the headers would otherwise only show up as missing entities,
+    // which is easy to miss when nothing is being looked at
+    let header_errors = if config.check_only {
+        report_clang_errors(&unit)
+    } else {
+        0
+    };
+    // Parse the headers again for each platform, so entities only declared
+    // on some of them can be added to the docs as well. The precompiled
+    // header can't be used for these since clang rejects it if the defines
+    // don't match
+    let platform_units = config
+        .analysis
+        .platforms
+        .iter()
+        .map(|platform| {
+            info!("Parsing headers for {}", platform.name);
+            let mut platform_args = args.clone();
+            platform_args.extend(platform.defines.iter().map(|d| format!("-D{d}")));
+            index
+                .parser(&target_src)
+                .arguments(&platform_args)
+                .parse()
+                .map(|unit| (platform.name.clone(), unit))
+                .map_err(|e| format!("Unable to parse headers for {}: {e}", platform.name))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let args = &unit_args;
 
     // Build the navbar first
     pbar.set_message("Setting up");
-    let builder = Builder::new(config, unit.get_entity(), &clang, &index, args)?;
+    let builder = Builder::new(
+        config,
+        unit.get_entity(),
+        platform_units
+            .iter()
+            .map(|(name, unit)| (name.clone(), unit.get_entity()))
+            .collect(),
+        &clang,
+        &index,
+        args,
+    )?;
+    info!("Parsed {} entities", builder.root.count());
 
     // Build the doc files
+    info!(phase = "build"; "Building docs");
     pbar.set_message("Building docs");
     builder.build(Some(pbar.clone())).await?;
 
+    if header_errors > 0 {
+        pbar.finish_and_clear();
+        return Err(format!(
+            "Found {header_errors} errors while parsing the headers"
+        ));
+    }
+
     pbar.set_message("Cleaning up files");
 
     // Clean up analyzable file
@@ -84,9 +357,12 @@ async fn analyze_with_clang(config: Arc<Config>, args: &[String]) -> Result<(),
     Ok(())
 }
 
-async fn analyze_with_cmake(config: Arc<Config>, skip_build: bool) -> Result<(), String> {
+fn configure_cmake(config: Arc<Config>, skip_build: bool) -> Result<(), String> {
     // Configure the cmake project
     if !skip_build {
+        if config.cmake.as_ref().unwrap().target.is_some() {
+            cmake::cmake_query_file_api(config.clone())?;
+        }
         cmake::cmake_configure(
             &config.cmake.as_ref().unwrap().build_dir,
             &config.cmake.as_ref().unwrap().config_args,
@@ -101,6 +377,12 @@ async fn analyze_with_cmake(config: Arc<Config>, skip_build: bool) -> Result<(),
         )?;
     }
 
+    Ok(())
+}
+
+async fn analyze_with_cmake(config: Arc<Config>, skip_build: bool) -> Result<(), String> {
+    configure_cmake(config.clone(), skip_build)?;
+
     analyze_with_clang(
         config.clone(),
         &cmake::cmake_compile_args_for(config).expect("Unable to infer CMake compile args"),
@@ -114,16 +396,44 @@ pub async fn create_docs(config: Arc<Config>, skip_build: bool) -> Result<(), St
     // Execute prebuild commands
     if let Some(cmds) = config.run.as_ref().map(|c| &c.prebuild) {
         for cmd in cmds {
-            run_command(cmd)?;
+            run_command("prebuild", cmd, &hook_env(config.clone())).await?;
         }
     }
 
     // Build based on mode
     if config.cmake.is_some() {
-        analyze_with_cmake(config, skip_build).await
+        analyze_with_cmake(config.clone(), skip_build).await?;
     }
     // Build with extra compile args only
     else {
-        analyze_with_clang(config.clone(), &config.analysis.compile_args).await
+        analyze_with_clang(config.clone(), &config.analysis.compile_args).await?;
+    }
+
+    // Execute postbuild commands, which expect the docs to have been written
+    if !config.check_only
+        && let Some(cmds) = config.run.as_ref().map(|c| &c.postbuild)
+    {
+        for cmd in cmds {
+            // The docs are already written, so a failing hook doesn't fail
+            // the build
+            if let Err(e) = run_command("postbuild", cmd, &hook_env(config.clone())).await {
+                warn!("{e}");
+            }
+        }
     }
+
+    Ok(())
+}
+
+pub async fn check_examples(config: Arc<Config>, skip_build: bool) -> Result<(), String> {
+    // Examples are compiled with the same arguments the headers are parsed with
+    let mut args = if config.cmake.is_some() {
+        configure_cmake(config.clone(), skip_build)?;
+        cmake::cmake_compile_args_for(config.clone()).expect("Unable to infer CMake compile args")
+    } else {
+        config.analysis.compile_args.clone()
+    };
+    args.extend(system_include_args(config.clone(), &args));
+
+    examples::check_examples(config, &args)
 }
@@ -1,75 +1,232 @@
+use base64::{Engine, prelude::BASE64_STANDARD};
 use clang::{Clang, Entity};
-use indicatif::ProgressBar;
+use indicatif::{ProgressBar, ProgressStyle};
+use log::{debug, info, warn};
+use regex_lite::{Captures, Regex};
 use serde_json::json;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, Instant},
+};
 use strfmt::strfmt;
-use tokio::task::JoinHandle;
+use tokio::{
+    runtime::Handle,
+    sync::{OwnedSemaphorePermit, Semaphore},
+    task::JoinHandle,
+};
 
 use crate::{
+    analyze::{hook_env, run_command},
     config::Config,
+    diff::{ApiDump, ApiSymbol},
     html::{
-        process::{minify_css, minify_html, minify_js},
-        GenHtml, Html,
+        GenHtml, Html, HtmlElement, RawHtml, escape_attr,
+        process::{
+            add_csp_hashes, fingerprint, minify_css, minify_html, minify_js, relativize_links,
+            resolve_link, rewrite_asset_links,
+        },
     },
+    i18n::DEFAULT_STRINGS,
     url::UrlPath,
 };
 
 use super::{
+    feed::fmt_feed,
     files::Root,
-    namespace::Namespace,
-    traits::{BuildResult, Entry, OutputEntry},
-    tutorial::TutorialFolder,
+    group::Groups,
+    manifest::{Manifest, ManifestFile},
+    markdown::fmt_banner,
+    namespace::{CppItemKind, Namespace},
+    search::{build_search_index, search_members},
+    shared::{declaration_text, fmt_breadcrumbs},
+    since::{SinceIndex, infer_from_tags},
+    tags::Tags,
+    traits::{
+        Access, BuildResult, EntityMethods, Entry, Include, NavItem, OutputEntry, collect_usrs,
+        resolve_url_collisions, set_namespace_aliases, set_platforms,
+    },
+    tutorial::{SeriesPart, TutorialFolder},
+    undocumented::UndocumentedReport,
+    usage::UsageIndex,
 };
 
+/// `href` and `src` attributes in the content of a page, with the whitespace
+/// before them in the first group, the attribute in the second and the link
+/// in the third
+static CONTENT_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(\s)(href|src)="([^"]*)""#).unwrap());
+
+/// `id` attributes in the content of a page, with the whitespace before them
+/// in the first group and the ID in the second
+static CONTENT_ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(\s)id="([^"]*)""#).unwrap());
+
+/// The `srcset` and `sizes` attributes of responsive images
+static IMAGE_SIZES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\s(?:srcset|sizes)="[^"]*""#).unwrap());
+
+/// Shared state for the tasks writing pages
+struct OutputTracker {
+    /// Limits how many pages are being built and written at once, so the
+    /// contents of every page aren't held in memory at the same time
+    permits: Arc<Semaphore>,
+    /// How long each page took to write, for build statistics
+    timings: Mutex<Vec<(UrlPath, Duration)>>,
+    /// Fingerprinted file names of the scripts and stylesheets by their
+    /// original names
+    assets: Mutex<Vec<(String, String)>>,
+    /// Every file written for the pages and before them, for the manifest
+    files: Mutex<Vec<ManifestFile>>,
+    /// Slow tasks writing files in the background, like resizing images,
+    /// which return the paths of the files they wrote
+    tasks: Mutex<Vec<JoinHandle<Vec<PathBuf>>>>,
+}
+
 pub struct Builder<'e> {
     pub config: Arc<Config>,
     pub root: Namespace<'e>,
     pub _clang: &'e Clang,
     pub index: &'e clang::Index<'e>,
     pub args: &'e [String],
+    /// Docs URLs of all documented types by their fully qualified name, for
+    /// linking types that LibClang can't resolve to a declaration
+    pub type_urls: HashMap<String, UrlPath>,
+    /// Docs URLs of types by every partially qualified name they can be
+    /// written with, like `Mod` for `geode::Mod`, or `None` if the name is
+    /// ambiguous
+    pub type_suffixes: HashMap<String, Option<UrlPath>>,
+    /// Source files from `examples.usage-dirs`, for listing where entities
+    /// are used
+    pub example_usage: Option<UsageIndex>,
     file_roots: Vec<Root>,
     tutorials: TutorialFolder,
+    /// Tutorials translated to other languages
+    locale_tutorials: Vec<TutorialFolder>,
+    tags: Tags,
+    groups: Groups<'e>,
     nav_cache: Option<String>,
+    banner: String,
+    output: Arc<OutputTracker>,
 }
 
 impl<'e> Builder<'e> {
     pub fn new(
         config: Arc<Config>,
         root: Entity<'e>,
+        platforms: Vec<(String, Entity<'e>)>,
         clang: &'e Clang,
         index: &'e clang::Index<'e>,
         args: &'e [String],
     ) -> Result<Self, String> {
+        set_namespace_aliases(
+            config.namespaces.rename.clone(),
+            config.namespaces.collapse.clone(),
+        );
+        if config.since.infer_from_tags {
+            infer_from_tags(config.clone())?;
+        }
+        let mut root = Namespace::new_root(root, config.clone());
+        if !platforms.is_empty() {
+            let mut found = Vec::new();
+            for (name, entity) in platforms {
+                let mut usrs = HashSet::new();
+                collect_usrs(&entity, &mut usrs);
+                found.push((name, usrs));
+                root.merge_platform(Namespace::new_root(entity, config.clone()));
+            }
+            set_platforms(found);
+        }
+        let mut docs_urls = Vec::new();
+        root.collect_docs_urls(&mut docs_urls);
+        resolve_url_collisions(&config, docs_urls);
+        let mut type_urls = HashMap::new();
+        root.collect_type_urls(&mut type_urls);
+        let mut type_suffixes = HashMap::<String, Option<UrlPath>>::new();
+        for (full, url) in &type_urls {
+            for (i, _) in full.match_indices("::") {
+                type_suffixes
+                    .entry(full[i + 2..].to_owned())
+                    .and_modify(|found| *found = None)
+                    .or_insert_with(|| Some(url.clone()));
+            }
+        }
+        let groups = Groups::new(config.clone(), &root);
+        let tutorials = TutorialFolder::from_config(config.clone());
         Self {
             config: config.clone(),
-            root: Namespace::new_root(root, config.clone()),
+            root,
             _clang: clang,
             index,
             args,
+            type_urls,
+            type_suffixes,
+            example_usage: config
+                .examples
+                .as_ref()
+                .is_some_and(|e| !e.usage_dirs.is_empty())
+                .then(|| UsageIndex::new(config.clone()))
+                .transpose()?,
             file_roots: Root::from_config(config.clone()),
-            tutorials: TutorialFolder::from_config(config),
+            tags: Tags::new(&tutorials),
+            tutorials,
+            locale_tutorials: TutorialFolder::from_locales(config.clone()),
+            groups,
             nav_cache: None,
+            banner: String::new(),
+            output: Arc::new(OutputTracker {
+                permits: Arc::new(Semaphore::new(config.jobs.max(1))),
+                timings: Mutex::new(Vec::new()),
+                assets: Mutex::new(Vec::new()),
+                files: Mutex::new(Vec::new()),
+                tasks: Mutex::new(Vec::new()),
+            }),
         }
         .setup()
     }
 
     fn setup(mut self) -> Result<Self, String> {
+        // Nothing gets written when only checking the docs
+        if self.config.check_only {
+            self.prebuild()?;
+            return Ok(self);
+        }
+
+        let mut assets = Vec::new();
+
         // copy & minify CSS
         for script in &self.config.scripts.css {
-            std::fs::write(
-                self.config.output_dir.join(&script.name),
-                minify_css(script.content.to_string())?,
-            )
-            .map_err(|e| format!("Unable to copy {}: {e}", script.name))?;
+            let content = if self.config.minify {
+                minify_css(script.content.to_string())?
+            } else {
+                script.content.to_string()
+            };
+            assets.push((script.name.clone(), content));
         }
 
         // transpile, minify, and copy JS
         for script in &self.config.scripts.js {
-            std::fs::write(
-                self.config.output_dir.join(&script.name),
-                minify_js(script.content.to_string())?,
-            )
-            .map_err(|e| format!("Unable to copy {}: {e}", script.name))?;
+            let content = if self.config.minify {
+                minify_js(script.content.to_string())?
+            } else {
+                script.content.to_string()
+            };
+            assets.push((script.name.clone(), content));
+        }
+
+        for (name, content) in assets {
+            let file_name = if self.config.fingerprint_assets {
+                fingerprint(&name, &content)
+            } else {
+                name.clone()
+            };
+            let entry = ManifestFile::new(file_name.clone(), content.as_bytes());
+            std::fs::write(self.config.output_dir.join(&file_name), content)
+                .map_err(|e| format!("Unable to copy {name}: {e}"))?;
+            self.output.files.lock().unwrap().push(entry);
+            if file_name != name {
+                self.output.assets.lock().unwrap().push((name, file_name));
+            }
         }
 
         // copy icon
@@ -89,18 +246,22 @@ impl<'e> Builder<'e> {
             let ico_file =
                 std::fs::File::create(self.config.output_dir.join("favicon.ico")).unwrap();
             icon_dir.write(ico_file).unwrap();
+            self.output.files.lock().unwrap().extend(
+                ["icon.png", "favicon.ico"]
+                    .into_iter()
+                    .filter_map(|f| ManifestFile::from_output(&self.config, f)),
+            );
         }
 
         // copy tutorial assets
         if let Some(ref tutorials) = self.config.tutorials {
             for asset in &tutorials.assets {
-                let output = self.config.output_dir.join(
-                    // if the tutorials are in docs and the assets are in
-                    // docs/assets, then they are probably referenced with
-                    // just assets/image.png so we should strip the docs
-                    // part
-                    asset.strip_prefix(&tutorials.dir).unwrap_or(asset),
-                );
+                // if the tutorials are in docs and the assets are in
+                // docs/assets, then they are probably referenced with
+                // just assets/image.png so we should strip the docs
+                // part
+                let rel = asset.strip_prefix(&tutorials.dir).unwrap_or(asset);
+                let output = self.config.output_dir.join(rel);
                 if let Some(parent) = output.parent() {
                     std::fs::create_dir_all(self.config.output_dir.join(parent)).map_err(|e| {
                         format!(
@@ -116,6 +277,26 @@ impl<'e> Builder<'e> {
                         self.config.input_dir.join(asset).to_string_lossy(),
                     )
                 })?;
+                if let Some(entry) = ManifestFile::from_output(
+                    &self.config,
+                    &rel.to_string_lossy().replace('\\', "/"),
+                ) {
+                    self.output.files.lock().unwrap().push(entry);
+                }
+            }
+        }
+
+        // copy static files verbatim
+        if let Some(ref dir) = self.config.static_dir {
+            let mut copied = Vec::new();
+            copy_dir_all(
+                &self.config.input_dir.join(dir),
+                &self.config.output_dir,
+                &mut copied,
+            )
+            .map_err(|e| format!("Unable to copy static directory: {e}"))?;
+            for file in copied {
+                self.track_file(&file);
             }
         }
 
@@ -125,43 +306,105 @@ impl<'e> Builder<'e> {
         Ok(self)
     }
 
+    /// Wait until there's room for building another page, blocking since
+    /// pages are built from synchronous code
+    fn wait_for_permit(&self) -> Result<OwnedSemaphorePermit, String> {
+        let permits = self.output.permits.clone();
+        tokio::task::block_in_place(|| Handle::current().block_on(permits.acquire_owned()))
+            .map_err(|e| format!("Unable to start building a page: {e}"))
+    }
+
     pub fn create_output_for<E: OutputEntry<'e>>(&'e self, entry: &E) -> BuildResult {
-        let (template, vars) = entry.output(self);
+        // The permit is held until the page is written
+        let permit = self.wait_for_permit()?;
+        let (template, mut vars) = entry.output(self);
+        // Formatting the page is what reports problems in its doc comments
+        // and markdown, so when checking there's nothing else to do
+        if self.config.check_only {
+            return Ok(Vec::new());
+        }
+        vars.push((
+            "breadcrumbs",
+            fmt_breadcrumbs(self.config.clone(), entry.breadcrumbs(self), entry.name()),
+        ));
+        vars.push((
+            "structured_data",
+            fmt_structured_data(
+                self.config.clone(),
+                entry.schema_type(),
+                &entry.name(),
+                &entry.description(self),
+                &entry.url(),
+            ),
+        ));
         Ok(vec![Self::create_output_in_thread(
             self.config.clone(),
+            self.output.clone(),
+            permit,
             self.build_nav()?,
+            self.banner.clone(),
             entry.name(),
             entry.description(self),
             entry.url(),
             template,
             vars,
+            self.config.llms_txt.then(|| entry.markdown(self)).flatten(),
+            entry.post_hooks(&self.config),
+            entry.source(),
         )])
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn create_output_in_thread(
         config: Arc<Config>,
+        output: Arc<OutputTracker>,
+        permit: OwnedSemaphorePermit,
         nav: String,
+        banner: String,
         name: String,
         description: String,
         target_url: UrlPath,
         template: Arc<String>,
         vars: Vec<(&'static str, Html)>,
+        markdown: Option<String>,
+        post_hooks: Vec<String>,
+        source: Option<String>,
     ) -> JoinHandle<Result<UrlPath, String>> {
         tokio::spawn(async move {
+            let _permit = permit;
+            let start = Instant::now();
+
+            let output_dir = config.output_dir.join(target_url.to_pathbuf());
+            let mut env = hook_env(config.clone());
+            env.extend([
+                ("FLASH_PAGE_URL", target_url.to_string()),
+                ("FLASH_PAGE_DIR", output_dir.to_string_lossy().to_string()),
+            ]);
+            for cmd in config.run.iter().flat_map(|r| &r.prepage) {
+                if let Err(e) = run_command("prepage", cmd, &env).await {
+                    warn!("{e}");
+                }
+            }
+
             let title = if name.is_empty() {
                 format!("{} Docs", config.project.name)
             } else {
                 format!("{} - {} Docs", name, config.project.name)
             };
 
-            let mut fmt = default_format(config.clone());
+            let mut fmt = page_format(config.clone(), &target_url);
             fmt.extend(HashMap::from([
                 (
                     "page_url".to_owned(),
                     target_url.to_absolute(config.clone()).to_string(),
                 ),
-                ("page_title".to_owned(), title.clone()),
-                ("page_description".to_owned(), description.clone()),
+                // These go in both attributes and text in the head
+                ("page_title".to_owned(), escape_attr(&title)),
+                ("page_description".to_owned(), escape_attr(&description)),
+                (
+                    "canonical".to_owned(),
+                    fmt_canonical(config.clone(), &target_url),
+                ),
             ]));
             fmt.extend(
                 vars.into_iter()
@@ -169,12 +412,13 @@ impl<'e> Builder<'e> {
                     .collect::<Vec<_>>(),
             );
 
-            let content = minify_html(
+            let mut content = maybe_minify_html(
+                config.clone(),
                 strfmt(&template, &fmt)
                     .map_err(|e| format!("Unable to format {target_url}: {e}"))?,
             )?;
 
-            let mut page = default_format(config.clone());
+            let mut page = page_format(config.clone(), &target_url);
             page.extend(HashMap::from([
                 (
                     "head_content".to_owned(),
@@ -182,32 +426,59 @@ impl<'e> Builder<'e> {
                         .map_err(|e| format!("Unable to format head for {target_url}: {e}"))?,
                 ),
                 ("navbar_content".to_owned(), nav),
+                ("banner".to_owned(), banner),
                 ("main_content".to_owned(), content.clone()),
             ]));
-            let page = minify_html(
+            let mut page = maybe_minify_html(
+                config.clone(),
                 strfmt(&config.templates.page, &page)
                     .map_err(|e| format!("Unable to format {target_url}: {e}"))?,
             )?;
+            let assets = output.assets.lock().unwrap().clone();
+            page = rewrite_asset_links(
+                page,
+                &assets,
+                &absolute_page_dir(config.clone(), &target_url),
+                &config,
+            );
+            if let Some(csp) = config.html.as_ref().and_then(|h| h.csp.as_ref()) {
+                page = add_csp_hashes(page, csp);
+            }
 
-            let output_dir = config.output_dir.join(target_url.to_pathbuf());
+            if config.relative_links {
+                let page_dir = absolute_page_dir(config.clone(), &target_url);
+                content = relativize_links(content, &page_dir, &config);
+                page = relativize_links(page, &page_dir, &config);
+            }
 
             // Make sure output directory exists
             tokio::fs::create_dir_all(&output_dir)
                 .await
                 .map_err(|e| format!("Unable to create directory for {target_url}: {e}"))?;
 
+            let mut written = Vec::new();
+            let mut track = |file: &str, data: &str| {
+                written.push(ManifestFile::for_page(
+                    &target_url,
+                    source.clone(),
+                    file,
+                    data.as_bytes(),
+                ));
+            };
+
             // Save metadata to a file
-            tokio::fs::write(
-                output_dir.join("metadata.json"),
-                format!(
-                    r#"{{"title": "{}", "description": "{}"}}"#,
-                    title, description,
-                ),
-            )
-            .await
-            .map_err(|e| format!("Unable to save metadata for {target_url}: {e}"))?;
+            let metadata = json!({
+                "title": title,
+                "description": description,
+            })
+            .to_string();
+            track("metadata.json", &metadata);
+            tokio::fs::write(output_dir.join("metadata.json"), metadata)
+                .await
+                .map_err(|e| format!("Unable to save metadata for {target_url}: {e}"))?;
 
             // Write the plain content output
+            track("content.html", &content);
             tokio::fs::write(
                 config
                     .output_dir
@@ -219,6 +490,7 @@ impl<'e> Builder<'e> {
             .map_err(|e| format!("Unable to save {target_url}: {e}"))?;
 
             // Write the full page
+            track("index.html", &page);
             tokio::fs::write(
                 config
                     .output_dir
@@ -229,6 +501,27 @@ impl<'e> Builder<'e> {
             .await
             .map_err(|e| format!("Unable to save {target_url}: {e}"))?;
 
+            // Write the markdown version for LLM-based tools
+            if let Some(markdown) = markdown {
+                track("index.md", &markdown);
+                tokio::fs::write(output_dir.join("index.md"), markdown)
+                    .await
+                    .map_err(|e| format!("Unable to save markdown for {target_url}: {e}"))?;
+            }
+
+            for cmd in &post_hooks {
+                if let Err(e) = run_command("posttutorial", cmd, &env).await {
+                    warn!("{e}");
+                }
+            }
+
+            output
+                .timings
+                .lock()
+                .unwrap()
+                .push((target_url.clone(), start.elapsed()));
+            output.files.lock().unwrap().extend(written);
+
             Ok(target_url)
         })
     }
@@ -239,13 +532,20 @@ impl<'e> Builder<'e> {
             .iter()
             .map(|p| p.1 as &dyn Entry<'e>)
             .chain(self.file_roots.iter().map(|p| p as &dyn Entry<'e>))
-            .chain([&self.tutorials as &dyn Entry])
+            .chain([
+                &self.tutorials as &dyn Entry,
+                &self.tags as &dyn Entry,
+                &self.groups as &dyn Entry,
+            ])
+            .chain(self.locale_tutorials.iter().map(|t| t as &dyn Entry<'e>))
+            .chain(self.example_usage.iter().map(|u| u as &dyn Entry<'e>))
             .collect()
     }
 
     fn prebuild(&mut self) -> Result<(), String> {
         // Prebuild cached navbars for much faster docs builds
         self.prebuild_nav()?;
+        self.banner = fmt_banner(self).gen_html();
 
         Ok(())
     }
@@ -258,18 +558,49 @@ impl<'e> Builder<'e> {
             handles.extend(entry.build(self)?);
         }
 
+        let report = self
+            .config
+            .undocumented_report
+            .then(|| UndocumentedReport::new(self));
+        if let Some(report) = &report {
+            report.log();
+            info!("{} public entities have no documentation", report.count());
+            handles.extend(report.build(self)?);
+        }
+
+        if self.config.check_only {
+            return Ok(());
+        }
+
+        if self.config.since.version_index {
+            let versions = SinceIndex::new(self);
+            if !versions.is_empty() {
+                handles.extend(versions.build(self)?);
+            }
+        }
+
         if let Some(pbar) = pbar.clone() {
             pbar.set_message("Generating output".to_string());
+            pbar.set_style(
+                ProgressStyle::with_template(
+                    "{msg:>15} [{bar:30}] {pos}/{len} [{elapsed_precise}]",
+                )
+                .unwrap()
+                .progress_chars("█▓░"),
+            );
+            pbar.set_length(handles.len() as u64);
+            pbar.set_position(0);
         }
 
-        futures::future::join_all(handles.into_iter().map(|handle| {
+        let built = futures::future::join_all(handles.into_iter().map(|handle| {
             let pbar = pbar.clone();
             tokio::spawn(async move {
                 let res = handle.await.map_err(|e| format!("Unable to join {e}"))??;
                 if let Some(pbar) = pbar {
-                    pbar.set_message(format!("Built {res}"));
+                    pbar.inc(1);
+                    debug!("Built {res}");
                 }
-                Result::<(), String>::Ok(())
+                Result::<UrlPath, String>::Ok(res)
             })
         }))
         .await
@@ -277,6 +608,73 @@ impl<'e> Builder<'e> {
         .collect::<Result<Result<Vec<_>, _>, _>>()
         .map_err(|e| format!("Unable to join {e}"))??;
 
+        if let Some(pbar) = pbar.clone() {
+            pbar.set_style(
+                ProgressStyle::with_template("{msg:>15} {spinner} [{elapsed_precise}]").unwrap(),
+            );
+        }
+        self.log_stats(&built);
+
+        // Files written outside of pages, for the manifest
+        let mut files = Vec::<&str>::new();
+
+        // Unlisted tutorials are left out of indexes
+        let unlisted = [&self.tutorials]
+            .into_iter()
+            .chain(&self.locale_tutorials)
+            .flat_map(|t| t.unlisted_urls())
+            .chain(report.as_ref().map(|r| r.url()))
+            .collect::<Vec<_>>();
+        let listed = built
+            .iter()
+            .filter(|url| !unlisted.contains(url))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if self.config.llms_txt {
+            if let Some(pbar) = pbar.clone() {
+                pbar.set_message("Generating llms.txt".to_string());
+            }
+            self.build_llms_txt(&listed).await?;
+            files.push("llms.txt");
+        }
+
+        if self.config.tutorials.as_ref().is_some_and(|t| t.feed) {
+            if let Some(pbar) = pbar.clone() {
+                pbar.set_message("Generating feed".to_string());
+            }
+            match fmt_feed(
+                self.config.clone(),
+                self.tutorials.feed_entries(self.config.clone()),
+            ) {
+                Some(feed) => {
+                    tokio::fs::write(self.config.output_dir.join("feed.xml"), feed)
+                        .await
+                        .map_err(|e| format!("Unable to save feed: {e}"))?;
+                    files.push("feed.xml");
+                }
+                None => warn!("Not generating the tutorial feed since canonical-url isn't set"),
+            }
+        }
+
+        if let Some(search) = &self.config.search {
+            if let Some(pbar) = pbar.clone() {
+                pbar.set_message("Generating search index".to_string());
+            }
+            if search.algolia {
+                self.build_algolia_records(&listed).await?;
+                files.push("algolia.json");
+            }
+        }
+
+        if self.config.single_page {
+            if let Some(pbar) = pbar.clone() {
+                pbar.set_message("Generating single page export".to_string());
+            }
+            self.build_single_page(built).await?;
+            files.push("single-page.html");
+        }
+
         if let Some(pbar) = pbar.clone() {
             pbar.set_message("Generating metadata".to_string());
         }
@@ -303,25 +701,553 @@ impl<'e> Builder<'e> {
         .await
         .map_err(|e| format!("Unable to save metadata {e}"))?;
 
+        tokio::fs::write(
+            self.config.output_dir.join("search.json"),
+            serde_json::to_string(&build_search_index(self))
+                .map_err(|e| format!("Unable to save metadata {e}"))?,
+        )
+        .await
+        .map_err(|e| format!("Unable to save metadata {e}"))?;
+
+        tokio::fs::write(
+            self.config.output_dir.join("api.json"),
+            serde_json::to_string(&self.build_api_dump())
+                .map_err(|e| format!("Unable to save metadata {e}"))?,
+        )
+        .await
+        .map_err(|e| format!("Unable to save metadata {e}"))?;
+
         tokio::fs::write(
             self.config.output_dir.join("nav.json"),
             serde_json::to_string(&self.build_nav_metadata()).unwrap(),
         )
         .await
         .unwrap();
+        files.extend(["functions.json", "search.json", "api.json", "nav.json"]);
+
+        let tasks = std::mem::take(&mut *self.output.tasks.lock().unwrap());
+        for task in tasks {
+            for file in task
+                .await
+                .map_err(|e| format!("Unable to write output files: {e}"))?
+            {
+                self.track_file(&file);
+            }
+        }
+
+        // Remove the files of the last build that weren't written this time,
+        // like the pages of classes that have been renamed
+        let mut entries = self.output.files.lock().unwrap().clone();
+        entries.extend(
+            files
+                .into_iter()
+                .filter_map(|f| ManifestFile::from_output(&self.config, f)),
+        );
+        let manifest = Manifest::new(entries);
+        if let Some(last) = Manifest::read(&self.config) {
+            let removed = last.prune(&self.config, &manifest);
+            if removed > 0 {
+                info!("Removed {removed} stale files");
+            }
+        }
+        manifest.write(&self.config)?;
+
+        // Pagefind indexes the finished output, so this has to be last
+        if self.config.search.as_ref().is_some_and(|s| s.pagefind) {
+            if let Some(pbar) = pbar.clone() {
+                pbar.set_message("Running Pagefind".to_string());
+            }
+            let exit = std::process::Command::new("pagefind")
+                .arg("--site")
+                .arg(&self.config.output_dir)
+                .output()
+                .map_err(|e| format!("Unable to execute Pagefind: {e}"))?;
+            if !exit.status.success() {
+                return Err(format!(
+                    "Pagefind failed: {}",
+                    String::from_utf8_lossy(&exit.stderr)
+                ));
+            }
+        }
 
         Ok(())
     }
 
+    /// Write a record for every built page in the Algolia DocSearch format, so
+    /// they can be uploaded to a hosted search index
+    async fn build_algolia_records(&self, pages: &[UrlPath]) -> Result<(), String> {
+        let tags = self
+            .tutorials
+            .tagged_tutorials()
+            .into_iter()
+            .map(|t| (t.url, t.tags))
+            .collect::<Vec<_>>();
+        let category_of = |url: &UrlPath| {
+            if let Some(category) = self.config.category_of(url) {
+                Some(self.config.category_name(category))
+            } else {
+                match url.url_safe_parts().first().map(|p| p.as_str()) {
+                    Some("groups") => Some(self.config.tr("groups")),
+                    Some("files") => Some(self.config.tr("files")),
+                    Some("tags") => Some(self.config.tr("tags")),
+                    // Source browser pages would just be noise in the results
+                    Some("source") => None,
+                    _ => Some(self.config.tr("tutorials")),
+                }
+            }
+        };
+        let mut records = Vec::new();
+        for url in pages {
+            let Some(category) = category_of(url) else {
+                continue;
+            };
+            let metadata = tokio::fs::read_to_string(
+                self.config
+                    .output_dir
+                    .join(url.to_pathbuf())
+                    .join("metadata.json"),
+            )
+            .await
+            .ok()
+            .and_then(|m| serde_json::from_str::<serde_json::Value>(&m).ok())
+            .unwrap_or_default();
+            let title = metadata["title"]
+                .as_str()
+                .map(|s| s.to_owned())
+                .unwrap_or(url.to_raw_string());
+            let object_id = if url.is_empty() {
+                String::from("index")
+            } else {
+                url.to_raw_string()
+            };
+            records.push(json!({
+                "objectID": object_id,
+                "url": url.to_absolute(self.config.clone()).to_string(),
+                "type": "lvl1",
+                "hierarchy": {
+                    "lvl0": category,
+                    "lvl1": title,
+                },
+                "content": metadata["description"].as_str(),
+                "_tags": tags
+                    .iter()
+                    .find(|(tagged, _)| tagged == url)
+                    .map(|(_, tags)| tags.clone())
+                    .unwrap_or_default(),
+            }));
+        }
+
+        // Members get their own records that link straight to them
+        for member in search_members(self) {
+            let page = member.url.without_suffix();
+            if !pages.contains(&page) {
+                continue;
+            }
+            let Some(category) = category_of(&page) else {
+                continue;
+            };
+            records.push(json!({
+                "objectID": format!(
+                    "{}#{}",
+                    page.to_raw_string(),
+                    member.url.fragment().unwrap_or_default()
+                ),
+                "url": member.url.to_absolute(self.config.clone()).to_string(),
+                "type": "lvl2",
+                "hierarchy": {
+                    "lvl0": category,
+                    "lvl1": member.parent,
+                    "lvl2": member.name,
+                },
+                "content": Option::<String>::None,
+                "_tags": [member.kind],
+            }));
+        }
+
+        tokio::fs::write(
+            self.config.output_dir.join("algolia.json"),
+            serde_json::to_string(&records)
+                .map_err(|e| format!("Unable to save Algolia records: {e}"))?,
+        )
+        .await
+        .map_err(|e| format!("Unable to save Algolia records: {e}"))
+    }
+
+    /// Log how many pages of each kind were built and which were the slowest
+    fn log_stats(&self, pages: &[UrlPath]) {
+        let mut counts = HashMap::<String, usize>::new();
+        for url in pages {
+            let kind = self
+                .config
+                .category_of(url)
+                .map(String::from)
+                .or_else(|| {
+                    url.url_safe_parts()
+                        .first()
+                        .filter(|p| matches!(p.as_str(), "groups" | "files" | "source" | "tags"))
+                        .cloned()
+                })
+                .unwrap_or(String::from("tutorials"));
+            *counts.entry(kind).or_default() += 1;
+        }
+        let mut counts = counts.into_iter().collect::<Vec<_>>();
+        counts.sort();
+        info!(
+            "Built {} pages ({})",
+            pages.len(),
+            counts
+                .iter()
+                .map(|(kind, count)| format!("{count} {kind}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        let mut timings = self.output.timings.lock().unwrap().clone();
+        timings.sort_by(|a, b| b.1.cmp(&a.1));
+        for (url, time) in timings
+            .iter()
+            .take(if self.config.verbose { 10 } else { 3 })
+        {
+            info!("Slow page: {url} took {:.2}s", time.as_secs_f32());
+        }
+    }
+
+    /// Write an llms.txt index of all the pages that have a markdown version
+    async fn build_llms_txt(&self, pages: &[UrlPath]) -> Result<(), String> {
+        let mut tutorials = String::new();
+        let mut api = String::new();
+        for url in pages {
+            let dir = self.config.output_dir.join(url.to_pathbuf());
+            if !dir.join("index.md").exists() {
+                continue;
+            }
+            let title = tokio::fs::read_to_string(dir.join("metadata.json"))
+                .await
+                .ok()
+                .and_then(|m| serde_json::from_str::<serde_json::Value>(&m).ok())
+                .and_then(|m| m["title"].as_str().map(|s| s.to_owned()))
+                .unwrap_or(url.to_raw_string());
+            let line = format!(
+                "- [{title}]({}/index.md)\n",
+                url.to_absolute(self.config.clone())
+            );
+            if self.config.category_of(url).is_some() {
+                api.push_str(&line);
+            } else {
+                tutorials.push_str(&line);
+            }
+        }
+
+        tokio::fs::write(
+            self.config.output_dir.join("llms.txt"),
+            format!(
+                "# {name}\n\n> Documentation for {name} {version}\n\n## Tutorials\n\n{tutorials}\n## API reference\n\n{api}",
+                name = self.config.project.name,
+                version = self.config.project.version,
+            ),
+        )
+        .await
+        .map_err(|e| format!("Unable to save llms.txt: {e}"))
+    }
+
+    /// Concatenate all built pages into one self-contained HTML file with the
+    /// stylesheets, scripts and images inlined, for offline reading and
+    /// archival
+    async fn build_single_page(&self, mut pages: Vec<UrlPath>) -> Result<(), String> {
+        // Tutorials and the index come first since they have shorter URLs
+        pages.sort_by_key(|url| {
+            (
+                url.to_raw_string().matches('/').count(),
+                url.to_raw_string(),
+            )
+        });
+
+        let anchor_for = |url: &UrlPath| {
+            if url.is_empty() {
+                String::from("page-index")
+            } else {
+                format!("page-{}", url.url_safe_parts().join("-"))
+            }
+        };
+        let locale = self.config.locale_of(&UrlPath::new());
+
+        let mut toc = String::new();
+        let mut sections = String::new();
+        for url in &pages {
+            let dir = self.config.output_dir.join(url.to_pathbuf());
+            let content = tokio::fs::read_to_string(dir.join("content.html"))
+                .await
+                .map_err(|e| format!("Unable to read {url} for single page export: {e}"))?;
+            let title = tokio::fs::read_to_string(dir.join("metadata.json"))
+                .await
+                .ok()
+                .and_then(|m| serde_json::from_str::<serde_json::Value>(&m).ok())
+                .and_then(|m| m["title"].as_str().map(|s| s.to_owned()))
+                .unwrap_or(url.to_raw_string());
+
+            // IDs are prefixed with the page's anchor so headings with the
+            // same name on different pages don't clash
+            let anchor = anchor_for(url);
+            let content = CONTENT_ID.replace_all(&content, |caps: &Captures| {
+                format!("{}id=\"{anchor}-{}\"", &caps[1], &caps[2])
+            });
+            // Only the image in src is inlined, not all of its sizes
+            let content = IMAGE_SIZES.replace_all(&content, "");
+            let page_dir = absolute_page_dir(self.config.clone(), url);
+            let content = CONTENT_LINK.replace_all(&content, |caps: &Captures| {
+                let link = &caps[3];
+                let new_link = match link.strip_prefix('#') {
+                    Some(fragment) if !fragment.is_empty() => Some(format!("#{anchor}-{fragment}")),
+                    Some(_) => None,
+                    None => match resolve_link(&self.config, &page_dir, link) {
+                        // Links between pages point to their sections instead
+                        Some(target) if &caps[2] == "href" => pages
+                            .contains(&target.without_suffix())
+                            .then(|| match target.fragment() {
+                                Some(fragment) => {
+                                    format!("#{}-{fragment}", anchor_for(&target))
+                                }
+                                None => format!("#{}", anchor_for(&target)),
+                            }),
+                        Some(target) => {
+                            data_url(&self.config.output_dir.join(target.to_raw_string()))
+                        }
+                        None => None,
+                    },
+                };
+                match new_link {
+                    Some(new_link) => format!("{}{}=\"{new_link}\"", &caps[1], &caps[2]),
+                    None => caps[0].to_owned(),
+                }
+            });
+
+            toc.push_str(
+                &HtmlElement::new("li")
+                    .with_child(
+                        HtmlElement::new("a")
+                            .with_attr("href", format!("#{anchor}"))
+                            .with_text(title),
+                    )
+                    .gen_html(),
+            );
+            // Translated tutorials are marked with their own language
+            let page_locale = self.config.locale_of(url);
+            let lang = if page_locale == locale {
+                String::new()
+            } else {
+                format!(" lang=\"{}\"", escape_attr(page_locale))
+            };
+            sections.push_str(&format!(
+                "<section id=\"{anchor}\" class=\"single-page-section\"{lang}>{content}</section>",
+            ));
+        }
+
+        let mut css = String::new();
+        for script in &self.config.scripts.css {
+            if self.config.minify {
+                css.push_str(&minify_css(script.content.to_string())?);
+            } else {
+                css.push_str(&script.content);
+            }
+        }
+
+        // Each script is its own module like when they are loaded from files
+        let mut js = String::new();
+        for script in &self.config.scripts.js {
+            let content = if self.config.minify {
+                minify_js(script.content.to_string())?
+            } else {
+                script.content.to_string()
+            };
+            js.push_str(&format!(
+                "<script type=\"module\">{}</script>",
+                content.replace("</script", "<\\/script")
+            ));
+        }
+
+        let icon = self
+            .config
+            .project
+            .icon
+            .as_ref()
+            .and_then(|_| data_url(&self.config.output_dir.join("icon.png")))
+            .map(|icon| format!("<link rel=\"icon\" href=\"{icon}\">"))
+            .unwrap_or_default();
+
+        let page = maybe_minify_html(
+            self.config.clone(),
+            format!(
+                "<!DOCTYPE html>\
+            <html lang=\"{locale}\">\
+            <head>\
+                <meta charset=\"UTF-8\">\
+                <title>{name} {version} Docs</title>\
+                {icon}\
+                <style>{css}</style>\
+                {js}\
+            </head>\
+            <body class=\"flash-theme-dark single-page\">\
+                <main>\
+                    <h1>{name} {version} Docs</h1>\
+                    <ul class=\"single-page-toc\">{toc}</ul>\
+                    {sections}\
+                </main>\
+            </body>\
+            </html>",
+                locale = escape_attr(locale),
+                name = self.config.project.name,
+                version = self.config.project.version,
+            ),
+        )?;
+
+        tokio::fs::write(self.config.output_dir.join("single-page.html"), page)
+            .await
+            .map_err(|e| format!("Unable to save single page export: {e}"))
+    }
+
     fn build_nav_metadata(&self) -> serde_json::Value {
         let tutorials = self.tutorials.nav().to_json(self.config.clone());
         let entities = self.root.nav().to_json(self.config.clone());
+        let groups = self.groups.nav().to_json(self.config.clone());
+        let files = NavItem::new_root(None, self.file_roots.iter().map(|r| r.nav()).collect())
+            .to_json(self.config.clone());
         json!({
             "tutorials": tutorials,
             "entities": entities,
+            "groups": groups,
+            "files": files,
         })
     }
 
+    /// Get the public API as a list of symbols with their declarations, for
+    /// comparing versions with `flash diff`
+    fn build_api_dump(&self) -> ApiDump {
+        let symbol = |entity: &Entity, kind: &str, url: UrlPath| ApiSymbol {
+            name: entity.full_name().join("::"),
+            kind: kind.to_owned(),
+            signature: declaration_text(entity),
+            url: url.to_absolute(self.config.clone()).to_string(),
+        };
+        let mut symbols = Vec::new();
+        let entries = self.root.get(&|entry| {
+            !matches!(
+                CppItemKind::from(entry.entity()),
+                Some(CppItemKind::Namespace) | None
+            )
+        });
+        for entry in entries {
+            let entity = entry.entity();
+            symbols.push(symbol(entity, entry.category(), entry.url()));
+            for member in entity
+                .get_constructors(Access::Public)
+                .into_iter()
+                .chain(entity.get_member_functions(Access::Public, Include::All))
+                .filter(|m| !m.is_hidden(self.config.clone()))
+            {
+                let url = entry
+                    .url()
+                    .with_fragment(&member.get_name().unwrap_or_default());
+                symbols.push(symbol(&member, "method", url));
+            }
+        }
+        symbols.sort_by(|a, b| (&a.name, &a.signature).cmp(&(&b.name, &b.signature)));
+        ApiDump {
+            project: self.config.project.name.clone(),
+            version: self.config.project.version.clone(),
+            symbols,
+        }
+    }
+
+    /// Get the breadcrumbs for a tutorial or tutorial folder
+    pub fn tutorial_breadcrumbs(&self, url: &UrlPath) -> Vec<(String, Option<UrlPath>)> {
+        self.tutorial_tree(url)
+            .breadcrumbs(&self.config.project.name, url)
+    }
+
+    /// Find the tutorial or tutorial folder a wiki link like `[[Getting
+    /// Started]]` or `[[folder/page]]` on a page points to, by its path in
+    /// the same tree or by its title, ignoring case. Returns its title and URL
+    pub fn resolve_wiki_link(&self, from: &UrlPath, target: &str) -> Option<(String, UrlPath)> {
+        let tree = self.tutorial_tree(from);
+        let path = target
+            .trim()
+            .trim_matches('/')
+            .trim_end_matches(".md")
+            .to_lowercase();
+        let pages = tree.pages();
+        pages
+            .iter()
+            .find(|(_, url)| {
+                url.strip_prefix(tree.prefix())
+                    .to_raw_string()
+                    .to_lowercase()
+                    == path
+            })
+            .or_else(|| pages.iter().find(|(title, _)| title.to_lowercase() == path))
+            .cloned()
+    }
+
+    /// Get the series a tutorial is a part of, as its name, the position of
+    /// the tutorial in it and all of its parts
+    pub fn tutorial_series(&self, url: &UrlPath) -> Option<(String, usize, Vec<SeriesPart>)> {
+        self.tutorial_tree(url)
+            .series()
+            .into_iter()
+            .find_map(|(name, parts)| {
+                let pos = parts.iter().position(|part| part.url == *url)?;
+                Some((name, pos, parts))
+            })
+    }
+
+    /// Get the tree of tutorials a tutorial or tutorial folder is in
+    fn tutorial_tree(&self, url: &UrlPath) -> &TutorialFolder {
+        self.locale_tutorials
+            .iter()
+            .find(|t| url.starts_with(t.prefix()))
+            .unwrap_or(&self.tutorials)
+    }
+
+    /// Get the same tutorial page in every language it has been translated
+    /// to, including the one it's in, as the name of the language and the
+    /// URL of the page
+    pub fn tutorial_translations(&self, url: &UrlPath) -> Vec<(String, UrlPath)> {
+        let Some(ref i18n) = self.config.i18n else {
+            return Vec::new();
+        };
+        let path = url.strip_prefix(self.tutorial_tree(url).prefix());
+        [(&i18n.locale_name, &self.tutorials)]
+            .into_iter()
+            .chain(self.locale_tutorials.iter().filter_map(|t| {
+                let code = t.prefix().to_raw_string();
+                Some((&i18n.locales.get(&code)?.name, t))
+            }))
+            .map(|(name, tree)| (name.clone(), tree.prefix().join(&path)))
+            .filter(|(_, url)| self.tutorial_tree(url).has_url(url))
+            .collect()
+    }
+
+    /// Add a file that was written to the output directory outside of the
+    /// files of a page, like a resized image, to the manifest
+    pub fn track_file(&self, path: &Path) {
+        if let Ok(rel) = path.strip_prefix(&self.config.output_dir)
+            && let Some(entry) =
+                ManifestFile::from_output(&self.config, &rel.to_string_lossy().replace('\\', "/"))
+        {
+            self.output.files.lock().unwrap().push(entry);
+        }
+    }
+
+    /// Run a slow task that writes files to the output directory on a
+    /// blocking thread, adding the files it returns to the manifest once the
+    /// build is done
+    pub fn spawn_blocking_output(&self, task: impl FnOnce() -> Vec<PathBuf> + Send + 'static) {
+        self.output
+            .tasks
+            .lock()
+            .unwrap()
+            .push(tokio::task::spawn_blocking(task));
+    }
+
     pub fn build_nav(&self) -> Result<String, String> {
         if let Some(ref cached) = self.nav_cache {
             return Ok(cached.to_owned());
@@ -337,8 +1263,164 @@ impl<'e> Builder<'e> {
     }
 }
 
+/// Copy the contents of a directory into another one, recursively, adding
+/// the paths of the copied files to a list
+fn copy_dir_all(from: &Path, to: &Path, copied: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &to.join(entry.file_name()), copied)?;
+        } else {
+            std::fs::copy(entry.path(), to.join(entry.file_name()))?;
+            copied.push(to.join(entry.file_name()));
+        }
+    }
+    Ok(())
+}
+
+fn maybe_minify_html(config: Arc<Config>, html: String) -> Result<String, String> {
+    if config.minify {
+        minify_html(html)
+    } else {
+        Ok(html)
+    }
+}
+
+/// Get the absolute directory a page is output to, with its parts encoded the
+/// same way as the links pointing to it
+fn absolute_page_dir(config: Arc<Config>, target_url: &UrlPath) -> UrlPath {
+    UrlPath::parse(&target_url.to_absolute(config).to_string()).unwrap()
+}
+
+/// Get a file in the output as a `data:` URL, for inlining images and the
+/// favicon into the single page export
+fn data_url(path: &Path) -> Option<String> {
+    let mime = match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => return None,
+    };
+    let data = std::fs::read(path).ok()?;
+    Some(format!(
+        "data:{mime};base64,{}",
+        BASE64_STANDARD.encode(data)
+    ))
+}
+
+/// Link to the canonical version of a page, so mirrors of the docs aren't
+/// considered duplicates of it
+fn fmt_canonical(config: Arc<Config>, target_url: &UrlPath) -> String {
+    config
+        .canonical_url
+        .as_ref()
+        .map(|base| {
+            format!(
+                "<link rel=\"canonical\" href=\"{}\">",
+                escape_attr(&format!(
+                    "{}{}",
+                    base.trim_end_matches('/'),
+                    target_url.to_absolute(config.clone())
+                ))
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Same as default_format, but with the output URL relative to the page when
+/// emitting relative links
+fn page_format(config: Arc<Config>, target_url: &UrlPath) -> HashMap<String, String> {
+    let mut fmt = default_format(config.clone());
+    // Translated tutorials get the UI strings in their own language
+    fmt.extend(tr_format(config.clone(), config.locale_of(target_url)));
+    if config.relative_links {
+        let root = UrlPath::parse(
+            &config
+                .output_url
+                .as_ref()
+                .unwrap_or(&UrlPath::new())
+                .to_string(),
+        )
+        .unwrap();
+        fmt.insert(
+            "output_url".into(),
+            root.relative_to(absolute_page_dir(config.clone(), target_url)),
+        );
+    }
+    fmt
+}
+
+/// Format the schema.org JSON-LD data of a page for search engines
+fn fmt_structured_data(
+    config: Arc<Config>,
+    schema_type: &str,
+    name: &str,
+    description: &str,
+    url: &UrlPath,
+) -> Html {
+    let mut data = json!({
+        "@context": "https://schema.org",
+        "@type": schema_type,
+        "name": if name.is_empty() { config.project.name.as_str() } else { name },
+        "description": description,
+        "version": config.project.version,
+    });
+    if schema_type == "TechArticle" {
+        data["headline"] = data["name"].clone();
+    } else if schema_type == "SoftwareSourceCode" {
+        data["programmingLanguage"] = json!(if config.is_c() { "C" } else { "C++" });
+        if let Some(ref repository) = config.project.repository {
+            data["codeRepository"] = json!(repository);
+        }
+    }
+    if let Some(ref base) = config.canonical_url {
+        data["url"] = json!(format!(
+            "{}{}",
+            base.trim_end_matches('/'),
+            url.to_absolute(config.clone())
+        ));
+    }
+    // Descriptions could contain a closing script tag
+    Html::Raw(RawHtml::trusted(format!(
+        "<script type=\"application/ld+json\">{}</script>",
+        data.to_string().replace("</", "<\\/")
+    )))
+}
+
+/// Override the color variables of the themes with the ones from the config
+fn fmt_theme_colors(config: Arc<Config>) -> String {
+    let Some(ref theme) = config.theme else {
+        return String::new();
+    };
+    let mut colors = theme.colors.iter().collect::<Vec<_>>();
+    colors.sort();
+
+    let mut vars = String::new();
+    for (name, value) in colors {
+        if value.contains([';', '{', '}', '<']) {
+            warn!("Invalid value for theme color {name}: {value}");
+            continue;
+        }
+        let name = name.trim_start_matches("--").trim_start_matches("flash-");
+        vars += &format!("--flash-{name}:{value};");
+    }
+    if vars.is_empty() {
+        return String::new();
+    }
+    // Attribute selector so this beats the specificity of the theme classes
+    format!("<style>body[class*=\"flash-theme-\"]{{{vars}}}</style>")
+}
+
 fn default_format(config: Arc<Config>) -> HashMap<String, String> {
-    HashMap::from([
+    let locale = config.i18n.as_ref().map_or("en", |i| i.locale.as_str());
+    let mut fmt = HashMap::from([
         ("project_name".into(), config.project.name.clone()),
         ("project_version".into(), config.project.version.clone()),
         (
@@ -365,5 +1447,72 @@ fn default_format(config: Arc<Config>) -> HashMap<String, String> {
                 .unwrap_or(&UrlPath::new())
                 .to_string(),
         ),
-    ])
+        (
+            "twemoji".into(),
+            config
+                .markdown
+                .as_ref()
+                .is_none_or(|m| m.twemoji)
+                .to_string(),
+        ),
+        ("theme_colors".into(), fmt_theme_colors(config.clone())),
+        (
+            "csp".into(),
+            config
+                .html
+                .as_ref()
+                .and_then(|h| h.csp.as_ref())
+                .map(|csp| {
+                    format!(
+                        "<meta http-equiv=\"Content-Security-Policy\" content=\"{}\">",
+                        escape_attr(csp)
+                    )
+                })
+                .unwrap_or_default(),
+        ),
+        (
+            "feed_link".into(),
+            if config.tutorials.as_ref().is_some_and(|t| t.feed) && config.canonical_url.is_some() {
+                format!(
+                    "<link rel=\"alternate\" type=\"application/atom+xml\" href=\"{}/feed.xml\">",
+                    config.output_url.as_ref().unwrap_or(&UrlPath::new())
+                )
+            } else {
+                String::new()
+            },
+        ),
+        (
+            "head_extra".into(),
+            config
+                .html
+                .as_ref()
+                .map(|h| h.head_extra.clone())
+                .unwrap_or_default(),
+        ),
+        // Only tutorials have translations, but other pages share the
+        // tutorial index template
+        ("languages".into(), String::new()),
+    ]);
+    fmt.extend(tr_format(config.clone(), locale));
+    fmt
+}
+
+/// Get the UI strings in a locale and the locale itself as template variables
+fn tr_format(config: Arc<Config>, locale: &str) -> Vec<(String, String)> {
+    DEFAULT_STRINGS
+        .iter()
+        .map(|(key, _)| {
+            // C projects have no classes, so the tab is for the whole API
+            let string = if *key == "entities" && config.is_c() {
+                "api"
+            } else {
+                key
+            };
+            (
+                format!("tr_{key}"),
+                escape_attr(&config.tr_in(locale, string)),
+            )
+        })
+        .chain([("locale".into(), escape_attr(locale))])
+        .collect()
 }
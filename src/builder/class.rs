@@ -1,21 +1,40 @@
 use std::sync::Arc;
 
-use crate::{html::Html, url::UrlPath};
+use crate::{config::Config, html::Html, url::UrlPath};
 use clang::Entity;
 
 use super::{
     builder::Builder,
-    shared::output_classlike,
+    namespace::{CppItem, load_nested},
+    shared::{entity_breadcrumbs, output_classlike, output_entity_markdown},
     traits::{ASTEntry, BuildResult, EntityMethods, Entry, NavItem, OutputEntry, SubItem},
 };
 
 pub struct Class<'e> {
     entity: Entity<'e>,
+    config: Arc<Config>,
+    specializations: Vec<Entity<'e>>,
+    /// Classes and structs declared inside this one, which get their own
+    /// pages under this one's URL
+    nested: Vec<CppItem<'e>>,
 }
 
 impl<'e> Class<'e> {
-    pub fn new(entity: Entity<'e>) -> Self {
-        Self { entity }
+    pub fn new(entity: Entity<'e>, config: Arc<Config>) -> Self {
+        Self {
+            entity,
+            nested: load_nested(&entity, config.clone()),
+            config,
+            specializations: Vec::new(),
+        }
+    }
+
+    pub fn add_specialization(&mut self, spec: Entity<'e>) {
+        self.specializations.push(spec);
+    }
+
+    pub fn nested(&self) -> &[CppItem<'e>] {
+        &self.nested
     }
 }
 
@@ -27,11 +46,17 @@ impl<'e> Entry<'e> for Class<'e> {
     }
 
     fn url(&self) -> UrlPath {
-        self.entity.rel_docs_url().expect("Unable to get class URL")
+        self.entity
+            .rel_docs_url(self.config.clone())
+            .expect("Unable to get class URL")
     }
 
     fn build(&self, builder: &Builder<'e>) -> BuildResult {
-        builder.create_output_for(self)
+        let mut handles = builder.create_output_for(self)?;
+        for entry in &self.nested {
+            handles.extend(entry.build(builder)?);
+        }
+        Ok(handles)
     }
 
     fn nav(&self) -> NavItem {
@@ -52,6 +77,10 @@ impl<'e> ASTEntry<'e> for Class<'e> {
     fn category(&self) -> &'static str {
         "class"
     }
+
+    fn specializations(&self) -> &[Entity<'e>] {
+        &self.specializations
+    }
 }
 
 impl<'e> OutputEntry<'e> for Class<'e> {
@@ -65,4 +94,20 @@ impl<'e> OutputEntry<'e> for Class<'e> {
     fn description(&self, builder: &'e Builder<'e>) -> String {
         self.output_description(builder)
     }
+
+    fn markdown(&self, builder: &'e Builder<'e>) -> Option<String> {
+        Some(output_entity_markdown(self, builder))
+    }
+
+    fn breadcrumbs(&self, builder: &'e Builder<'e>) -> Vec<(String, Option<UrlPath>)> {
+        entity_breadcrumbs(builder.config.clone(), self)
+    }
+
+    fn schema_type(&self) -> &'static str {
+        "SoftwareSourceCode"
+    }
+
+    fn source(&self) -> Option<String> {
+        self.entity().get_usr().map(|usr| usr.0)
+    }
 }
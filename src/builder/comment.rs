@@ -1,23 +1,30 @@
-use std::{collections::HashMap, fs, str::Chars};
+use std::{collections::HashMap, fmt::Arguments, fs, str::Chars, sync::Arc};
 
 use clang::{
     token::{Token, TokenKind},
     Entity, EntityKind,
 };
-use log::{error, warn};
+use log::{debug, error};
 use multipeek::{IteratorExt, MultiPeek};
 
 use crate::{
+    config::Config,
+    diagnostics::{WarningKind, is_suppressed, nowarn_kinds, report},
     html::{Html, HtmlElement, HtmlList, HtmlText},
     url::UrlPath,
 };
 
 use super::{
-    builder::Builder, markdown::fmt_markdown, shared::fmt_autolinks, traits::EntityMethods,
+    builder::Builder,
+    markdown::{extract_cpp_blocks, fmt_markdown},
+    shared::{fmt_autolinks, resolve_reference},
+    traits::EntityMethods,
 };
 
 struct CommentLexer<'s> {
     raw: MultiPeek<Chars<'s>>,
+    /// Warning kinds suppressed with `@nowarn` in this comment
+    nowarn: Vec<String>,
 }
 
 impl<'s> CommentLexer<'s> {
@@ -28,6 +35,7 @@ impl<'s> CommentLexer<'s> {
                 .trim_start_matches("/*")
                 .chars()
                 .multipeek(),
+            nowarn: nowarn_kinds(raw),
         }
     }
 
@@ -194,23 +202,27 @@ impl<'s> CommentLexer<'s> {
 
     pub fn param_for(&mut self, cmd: &ParsedCommand) -> String {
         self.next_param().unwrap_or_else(|| {
-            warn!(
-                "Warning parsing JSDoc comment: Expected parameter for command {}",
-                cmd.cmd
-            );
+            self.syntax_warning(format_args!("Expected parameter for command {}", cmd.cmd));
             String::new()
         })
     }
 
     pub fn value_for(&mut self, cmd: &ParsedCommand) -> String {
         self.next_value().unwrap_or_else(|| {
-            warn!(
-                "Warning parsing JSDoc comment: Expected value for command {}",
-                cmd.cmd
-            );
+            self.syntax_warning(format_args!("Expected value for command {}", cmd.cmd));
             String::new()
         })
     }
+
+    fn syntax_warning(&self, message: Arguments) {
+        if !is_suppressed(&self.nowarn, WarningKind::CommentSyntax) {
+            report(
+                WarningKind::CommentSyntax,
+                ("", 0),
+                format_args!("Warning parsing JSDoc comment: {message}"),
+            );
+        }
+    }
 }
 
 struct ParsedCommand {
@@ -343,7 +355,7 @@ fn annotate(base: Entity, annotations: &[Annotation]) -> Vec<Html> {
 
 fn print(entity: &Entity) {
     for child in entity.get_children() {
-        println!(
+        debug!(
             "{:?} :: {}:{}..{}:{} => {:?}",
             entity.get_kind(),
             child
@@ -375,10 +387,38 @@ fn print(entity: &Entity) {
     }
 }
 
+/// Load the code of an example file referenced with `@example path`, relative
+/// to the examples directory in the config
+pub fn load_example_file(config: Arc<Config>, value: &str) -> Option<String> {
+    let path = value.trim();
+    if path.is_empty() || path.contains(char::is_whitespace) {
+        return None;
+    }
+    let dir = config.examples.as_ref()?.dir.as_ref()?;
+    fs::read_to_string(config.input_dir.join(dir).join(path))
+        .inspect_err(|e| {
+            // Code examples that happen to be one word are fine, but ones
+            // that look like a file name were probably meant to be a file
+            if path
+                .rsplit_once('.')
+                .is_some_and(|(_, ext)| matches!(ext, "cpp" | "cc" | "cxx" | "hpp" | "h" | "mm"))
+            {
+                report(
+                    WarningKind::MissingExample,
+                    ("", 0),
+                    format_args!("Unable to read example file {path}: {e}"),
+                );
+            }
+        })
+        .ok()
+}
+
 pub struct Example<'e> {
     builder: &'e Builder<'e>,
     data: String,
     analyze: bool,
+    /// Name of the file this example was loaded from, if any
+    file: Option<String>,
 }
 
 impl<'e> Example<'e> {
@@ -387,6 +427,19 @@ impl<'e> Example<'e> {
             builder,
             data,
             analyze,
+            file: None,
+        }
+    }
+
+    /// Create an example from `@example`, which is either the code itself or
+    /// the path of a file in the examples directory
+    pub fn from_value(value: String, analyze: bool, builder: &'e Builder<'e>) -> Self {
+        match load_example_file(builder.config.clone(), &value) {
+            Some(data) => Self {
+                file: Some(value.trim().to_owned()),
+                ..Self::new(data, analyze, builder)
+            },
+            None => Self::new(value, analyze, builder),
         }
     }
 
@@ -482,6 +535,22 @@ impl<'e> Example<'e> {
     }
 
     pub fn to_html(&self) -> Html {
+        let code = self.code_to_html();
+        match self.file {
+            Some(ref file) => HtmlElement::new("div")
+                .with_class("example-file")
+                .with_child(
+                    HtmlElement::new("p")
+                        .with_child(Html::feather("file"))
+                        .with_child(HtmlText::new(file)),
+                )
+                .with_child(code)
+                .into(),
+            None => code,
+        }
+    }
+
+    fn code_to_html(&self) -> Html {
         // Custom syntax highlighting with links
         if self.analyze
             && let Ok(sweet) = self
@@ -503,6 +572,84 @@ impl<'e> Example<'e> {
     }
 }
 
+/// A topic group defined with `@defgroup name Title`
+pub struct GroupDef {
+    /// The identifier used to add entities to the group with `@ingroup`
+    pub name: String,
+    pub title: String,
+    pub description: Option<String>,
+}
+
+/// Find the groups defined in a doc comment with `@defgroup`, and the names
+/// of the groups added to with `@ingroup`
+pub fn parse_group_commands(raw: &str) -> (Vec<GroupDef>, Vec<String>) {
+    let mut lexer = CommentLexer::new(raw);
+    let mut defs = Vec::new();
+    let mut groups = Vec::new();
+
+    while let Some(cmd) = lexer.next_command() {
+        match cmd.cmd.as_str() {
+            "defgroup" => {
+                let name = lexer.param_for(&cmd);
+                let value = lexer.next_value().unwrap_or_default();
+                // The title is the rest of the line and the description is
+                // everything after it
+                let (title, description) = value.split_once('\n').unwrap_or((&value, ""));
+                defs.push(GroupDef {
+                    title: if title.trim().is_empty() {
+                        name.clone()
+                    } else {
+                        title.trim().to_owned()
+                    },
+                    name,
+                    description: (!description.trim().is_empty())
+                        .then(|| description.trim().to_owned()),
+                });
+            }
+            "ingroup" => {
+                // Only the first line lists groups
+                let value = lexer.value_for(&cmd);
+                groups.extend(
+                    value
+                        .lines()
+                        .next()
+                        .unwrap_or_default()
+                        .split_whitespace()
+                        .map(|s| s.to_owned()),
+                );
+            }
+            _ => {
+                lexer.next_value();
+            }
+        }
+    }
+
+    (defs, groups)
+}
+
+/// Find the code of the `@example` and `@code` commands in a doc comment, as
+/// well as the C++ code blocks in the rest of it
+pub fn parse_code_examples(raw: &str) -> Vec<String> {
+    let mut lexer = CommentLexer::new(raw);
+    let mut examples = Vec::new();
+
+    while let Some(cmd) = lexer.next_command() {
+        match cmd.cmd.as_str() {
+            "example" | "code" => examples.push(lexer.value_for(&cmd)),
+            _ => examples.extend(
+                lexer
+                    .next_value()
+                    .map(|value| extract_cpp_blocks(&value))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(code, _)| code),
+            ),
+        }
+    }
+
+    examples
+}
+
 pub struct JSDocComment<'e> {
     /// Description (duh)
     description: Option<String>,
@@ -528,6 +675,10 @@ pub struct JSDocComment<'e> {
     since: Option<String>,
     /// Examples
     examples: Vec<Example<'e>>,
+    /// Whether this enum is a set of bit flags; specified with @flags
+    flags: bool,
+    /// Warning kinds suppressed with @nowarn
+    nowarn: Vec<String>,
     /// Reference to builder
     builder: &'e Builder<'e>,
 }
@@ -535,6 +686,7 @@ pub struct JSDocComment<'e> {
 impl<'e> JSDocComment<'e> {
     fn parse_mut(mut self, raw: String) -> Self {
         let mut lexer = CommentLexer::new(&raw);
+        self.nowarn = lexer.nowarn.clone();
 
         while let Some(cmd) = lexer.next_command() {
             match cmd.cmd.as_str() {
@@ -553,7 +705,7 @@ impl<'e> JSDocComment<'e> {
                     .push((lexer.param_for(&cmd), lexer.value_for(&cmd))),
                 "return" | "returns" => self.returns = lexer.value_for(&cmd).into(),
                 "throws" => self.throws = lexer.value_for(&cmd).into(),
-                "see" => self.see.push(lexer.value_for(&cmd)),
+                "see" | "sa" => self.see.push(lexer.value_for(&cmd)),
                 "note" => {
                     if cmd.attrs.contains_key("short") {
                         self.short_notes.push(lexer.value_for(&cmd))
@@ -564,11 +716,20 @@ impl<'e> JSDocComment<'e> {
                 "warning" | "warn" => self.warnings.push(lexer.value_for(&cmd)),
                 "version" => self.version = lexer.value_for(&cmd).into(),
                 "since" => self.since = lexer.value_for(&cmd).into(),
-                "example" | "code" => self.examples.push(Example::new(
+                "example" => self.examples.push(Example::from_value(
                     lexer.value_for(&cmd),
                     cmd.attrs.contains_key("flash"),
                     self.builder,
                 )),
+                "code" => self.examples.push(Example::new(
+                    lexer.value_for(&cmd),
+                    cmd.attrs.contains_key("flash"),
+                    self.builder,
+                )),
+                "flags" => {
+                    self.flags = true;
+                    lexer.next_value();
+                }
                 // _ => println!("Warning parsing JSDoc comment: Unknown command {cmd}"),
                 _ => {
                     // eat a value even though this is an unknown command
@@ -594,6 +755,8 @@ impl<'e> JSDocComment<'e> {
             version: None,
             since: None,
             examples: Vec::new(),
+            flags: false,
+            nowarn: Vec::new(),
             builder,
         }
     }
@@ -603,118 +766,202 @@ impl<'e> JSDocComment<'e> {
     }
 
     pub fn to_html(&self, include_examples: bool) -> Html {
-        HtmlList::new(vec![HtmlElement::new("div")
-            .with_class("description")
-            .with_child_opt(
-                if self.version.is_some() || self.since.is_some() || !self.short_notes.is_empty() {
-                    HtmlElement::new("div")
-                        .with_class("tags")
-                        .with_child_opt(
-                            self.version
-                                .as_ref()
-                                .map(|v| Html::p(format!("Version {v}"))),
-                        )
-                        .with_child_opt(self.since.as_ref().map(|v| Html::p(format!("Since {v}"))))
-                        .with_children(self.short_notes.iter().map(Html::p).collect())
-                        .into()
-                } else {
-                    None
-                },
-            )
-            .with_child(
-                self.description
-                    .as_ref()
-                    .map(|d| {
-                        fmt_markdown(
-                            self.builder,
-                            &fmt_autolinks(self.builder, d),
-                            None::<fn(_) -> _>,
-                        )
-                    })
-                    .unwrap_or(Html::span(&["no-desc"], "No description provided")),
-            )
-            .with_child_opt(
-                (!self.params.is_empty()).then_some(
-                    HtmlElement::new("section")
-                        .with_class("params")
-                        .with_child(Html::span(&["title"], "Parameters"))
-                        .with_child(
-                            HtmlElement::new("div").with_class("grid").with_children(
-                                self.params
-                                    .iter()
-                                    .flat_map(|param| {
-                                        vec![Html::p(param.0.clone()), Html::div(param.1.clone())]
-                                    })
-                                    .collect(),
+        HtmlList::new(vec![
+            HtmlElement::new("div")
+                .with_class("description")
+                .with_child_opt(
+                    if self.version.is_some()
+                        || self.since.is_some()
+                        || !self.short_notes.is_empty()
+                    {
+                        HtmlElement::new("div")
+                            .with_class("tags")
+                            .with_child_opt(
+                                self.version
+                                    .as_ref()
+                                    .map(|v| Html::p(format!("Version {v}"))),
+                            )
+                            .with_child_opt(
+                                self.since.as_ref().map(|v| Html::p(format!("Since {v}"))),
+                            )
+                            .with_children(self.short_notes.iter().map(Html::p).collect())
+                            .into()
+                    } else {
+                        None
+                    },
+                )
+                .with_child(
+                    self.description
+                        .as_ref()
+                        .map(|d| {
+                            fmt_markdown(
+                                self.builder,
+                                &fmt_autolinks(self.builder, d),
+                                None::<fn(_) -> _>,
+                            )
+                        })
+                        .unwrap_or(Html::span(
+                            &["no-desc"],
+                            &self.builder.config.tr("no_description"),
+                        )),
+                )
+                .with_child_opt(
+                    (!self.params.is_empty()).then_some(
+                        HtmlElement::new("section")
+                            .with_class("params")
+                            .with_child(Html::span(
+                                &["title"],
+                                &self.builder.config.tr("parameters"),
+                            ))
+                            .with_child(
+                                HtmlElement::new("div").with_class("grid").with_children(
+                                    self.params
+                                        .iter()
+                                        .flat_map(|param| {
+                                            vec![
+                                                Html::p(param.0.clone()),
+                                                Html::div(param.1.clone()),
+                                            ]
+                                        })
+                                        .collect(),
+                                ),
                             ),
-                        ),
-                ),
-            )
-            .with_child_opt(
-                (!self.tparams.is_empty()).then_some(
+                    ),
+                )
+                .with_child_opt(
+                    (!self.tparams.is_empty()).then_some(
+                        HtmlElement::new("section")
+                            .with_classes(&["params", "template"])
+                            .with_child(Html::span(
+                                &["title"],
+                                &self.builder.config.tr("template_parameters"),
+                            ))
+                            .with_child(
+                                HtmlElement::new("div").with_class("grid").with_children(
+                                    self.tparams
+                                        .iter()
+                                        .flat_map(|tparam| {
+                                            vec![
+                                                Html::p(tparam.0.clone()),
+                                                Html::div(tparam.1.clone()),
+                                            ]
+                                        })
+                                        .collect(),
+                                ),
+                            ),
+                    ),
+                )
+                .with_child_opt(self.returns.as_ref().map(|ret| {
+                    HtmlElement::new("section")
+                        .with_classes(&["params", "returns", "grid"])
+                        .with_child(Html::span(
+                            &["title"],
+                            &self.builder.config.tr("return_value"),
+                        ))
+                        .with_child(Html::div(ret.clone()))
+                }))
+                .with_child_opt(self.throws.as_ref().map(|ret| {
                     HtmlElement::new("section")
-                        .with_classes(&["params", "template"])
-                        .with_child(Html::span(&["title"], "Template parameters"))
+                        .with_classes(&["params", "throws", "grid"])
+                        .with_child(Html::span(
+                            &["title"],
+                            &self.builder.config.tr("exceptions"),
+                        ))
+                        .with_child(Html::div(ret.clone()))
+                }))
+                .with_child_opt((!self.see.is_empty()).then(|| {
+                    HtmlElement::new("section")
+                        .with_classes(&["params", "see", "grid"])
+                        .with_child(Html::span(&["title"], &self.builder.config.tr("see_also")))
                         .with_child(
-                            HtmlElement::new("div").with_class("grid").with_children(
-                                self.tparams
-                                    .iter()
-                                    .flat_map(|tparam| {
-                                        vec![Html::p(tparam.0.clone()), Html::div(tparam.1.clone())]
-                                    })
-                                    .collect(),
+                            HtmlElement::new("div").with_children(
+                                self.see.iter().map(|see| self.fmt_see(see)).collect(),
                             ),
-                        ),
-                ),
-            )
-            .with_child_opt(self.returns.as_ref().map(|ret| {
-                HtmlElement::new("section")
-                    .with_classes(&["params", "returns", "grid"])
-                    .with_child(Html::span(&["title"], "Return value"))
-                    .with_child(Html::div(ret.clone()))
-            }))
-            .with_child_opt(self.throws.as_ref().map(|ret| {
-                HtmlElement::new("section")
-                    .with_classes(&["params", "throws", "grid"])
-                    .with_child(Html::span(&["title"], "Exceptions"))
-                    .with_child(Html::div(ret.clone()))
-            }))
-            // todo: see
-            .with_children(
-                self.notes
-                    .iter()
-                    .map(|note| {
-                        HtmlElement::new("blockquote")
-                            .with_class("info")
-                            .with_child(Html::p(format!("ℹ {}", note)))
-                            .into()
-                    })
-                    .collect(),
-            )
-            .with_children(
-                self.warnings
-                    .iter()
-                    .map(|warning| {
-                        HtmlElement::new("blockquote")
-                            .with_class("warning")
-                            .with_child(Html::p(format!("⚠️ {}", warning)))
-                            .into()
-                    })
-                    .collect(),
-            )
-            .with_children(if include_examples {
-                self.examples
-                    .iter()
-                    .map(|example| example.to_html())
-                    .collect()
-            } else {
-                Vec::new()
-            })
-            .into()])
+                        )
+                }))
+                .with_children(
+                    self.notes
+                        .iter()
+                        .map(|note| {
+                            HtmlElement::new("blockquote")
+                                .with_class("info")
+                                .with_child(Html::p(format!("ℹ {}", note)))
+                                .into()
+                        })
+                        .collect(),
+                )
+                .with_children(
+                    self.warnings
+                        .iter()
+                        .map(|warning| {
+                            HtmlElement::new("blockquote")
+                                .with_class("warning")
+                                .with_child(Html::p(format!("⚠️ {}", warning)))
+                                .into()
+                        })
+                        .collect(),
+                )
+                .with_children(if include_examples {
+                    self.examples
+                        .iter()
+                        .map(|example| example.to_html())
+                        .collect()
+                } else {
+                    Vec::new()
+                })
+                .into(),
+        ])
         .into()
     }
 
+    /// Format a reference from `@see`, which is the name of an entity or a
+    /// URL, optionally followed by a description
+    fn fmt_see(&self, see: &str) -> Html {
+        let see = see.trim();
+        let (target, rest) = see.split_once(char::is_whitespace).unwrap_or((see, ""));
+        let link = if target.starts_with("http://") || target.starts_with("https://") {
+            Some(target.to_owned())
+        } else {
+            resolve_reference(self.builder, target)
+        };
+        if link.is_none() && !is_suppressed(&self.nowarn, WarningKind::UnresolvedReference) {
+            report(
+                WarningKind::UnresolvedReference,
+                ("", 0),
+                format_args!("Unable to resolve @see reference {target}"),
+            );
+        }
+        HtmlElement::new("p")
+            .with_child::<Html>(match link {
+                Some(link) => HtmlElement::new("a")
+                    .with_attr("href", link)
+                    .with_child(HtmlElement::new("code").with_text(target))
+                    .into(),
+                None => HtmlElement::new("code").with_text(target).into(),
+            })
+            .with_child_opt((!rest.is_empty()).then(|| HtmlText::new(format!(" {}", rest.trim()))))
+            .into()
+    }
+
     pub fn examples(&self) -> &Vec<Example<'_>> {
         &self.examples
     }
+
+    pub fn since(&self) -> Option<&String> {
+        self.since.as_ref()
+    }
+
+    pub fn is_flags(&self) -> bool {
+        self.flags
+    }
+
+    /// Get the first sentence of the description, for summaries in lists
+    pub fn brief(&self) -> Option<String> {
+        let para = self.description.as_ref()?.split("\n\n").next()?.trim();
+        let sentence = match para.find(". ") {
+            Some(end) => &para[..=end],
+            None => para,
+        };
+        (!sentence.is_empty()).then(|| sentence.replace('\n', " "))
+    }
 }
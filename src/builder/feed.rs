@@ -0,0 +1,109 @@
+use std::{collections::HashMap, path::Path, process::Command, sync::Arc};
+
+use crate::{config::Config, url::UrlPath};
+
+/// A listed tutorial with the date it was published or last changed
+pub struct FeedEntry {
+    pub title: String,
+    pub url: UrlPath,
+    pub description: Option<String>,
+    pub date: String,
+}
+
+/// Get the date of the last commit that changed each file in a directory as
+/// an RFC 3339 timestamp in UTC, by the path of the file relative to the
+/// directory. This reads the history once instead of once per file, and is
+/// empty if the directory isn't in a git repository
+pub fn git_dates(dir: &Path) -> HashMap<String, String> {
+    let mut res = HashMap::new();
+    let Ok(output) = Command::new("git")
+        .current_dir(dir)
+        .env("TZ", "UTC")
+        .args([
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--relative",
+            "--name-only",
+        ])
+        .args(["--format=%x00%cd", "--date=iso-strict-local", "--", "."])
+        .output()
+    else {
+        return res;
+    };
+    if !output.status.success() {
+        return res;
+    }
+
+    // Commits are listed newest first, each with its date on a line starting
+    // with a null byte followed by the files it changed
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut date = "";
+    for line in stdout.lines() {
+        if let Some(commit_date) = line.strip_prefix('\0') {
+            date = commit_date.trim();
+        } else if !line.is_empty() && !date.is_empty() {
+            res.entry(line.to_owned())
+                .or_insert_with(|| date.to_owned());
+        }
+    }
+    res
+}
+
+/// Turn a date from the metadata of a tutorial, which may be just a day like
+/// `2024-05-01`, into an RFC 3339 timestamp
+pub fn normalize_date(date: &str) -> String {
+    if date.len() == 10 {
+        format!("{date}T00:00:00+00:00")
+    } else {
+        date.to_owned()
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Format an Atom feed of tutorials, newest first. Atom needs full URLs for
+/// the ids and links, so this is only possible with a canonical URL
+pub fn fmt_feed(config: Arc<Config>, mut entries: Vec<FeedEntry>) -> Option<String> {
+    entries.sort_by(|a, b| b.date.cmp(&a.date));
+
+    let base = config.canonical_url.as_deref()?.trim_end_matches('/');
+    let link = |url: &UrlPath| escape_xml(&format!("{base}{}", url.to_absolute(config.clone())));
+    let home = link(&UrlPath::new());
+
+    let mut feed = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+        <feed xmlns=\"http://www.w3.org/2005/Atom\">\n\
+        <title>{} Tutorials</title>\n\
+        <id>{home}/</id>\n\
+        <link href=\"{home}/\"/>\n\
+        <link rel=\"self\" href=\"{home}/feed.xml\"/>\n",
+        escape_xml(&config.project.name),
+    );
+    if let Some(latest) = entries.first() {
+        feed += &format!("<updated>{}</updated>\n", escape_xml(&latest.date));
+    }
+    for entry in &entries {
+        let url = link(&entry.url);
+        feed += &format!(
+            "<entry>\n\
+            <title>{}</title>\n\
+            <id>{url}</id>\n\
+            <link href=\"{url}\"/>\n\
+            <updated>{}</updated>\n",
+            escape_xml(&entry.title),
+            escape_xml(&entry.date),
+        );
+        if let Some(ref description) = entry.description {
+            feed += &format!("<summary>{}</summary>\n", escape_xml(description));
+        }
+        feed += "</entry>\n";
+    }
+    feed += "</feed>\n";
+    Some(feed)
+}
@@ -1,13 +1,14 @@
 use super::{
     builder::Builder,
     namespace::CppItemKind,
-    shared::{fmt_class_method, fmt_classlike_decl, fmt_section},
+    shared::{fmt_class_method, fmt_classlike_decl, fmt_section, fmt_variable},
+    source::SourceFile,
     traits::{ASTEntry, BuildResult, Entry, NavItem, OutputEntry},
 };
 use crate::{
     config::{Config, Source},
     html::{Html, HtmlText},
-    url::UrlPath,
+    url::{UrlPath, strip_path_prefix},
 };
 use std::{collections::HashMap, path::Path, sync::Arc};
 
@@ -22,6 +23,18 @@ impl File {
     }
 }
 
+/// Get the directories a file is in as breadcrumbs; directories don't have
+/// pages so they are left unlinked
+pub fn dir_breadcrumbs(path: &UrlPath) -> Vec<(String, Option<UrlPath>)> {
+    let mut dirs = path
+        .to_raw_string()
+        .split('/')
+        .map(|d| (d.to_owned(), None))
+        .collect::<Vec<_>>();
+    dirs.pop();
+    dirs
+}
+
 impl<'e> Entry<'e> for File {
     fn name(&self) -> String {
         self.path.raw_file_name().unwrap()
@@ -32,7 +45,11 @@ impl<'e> Entry<'e> for File {
     }
 
     fn build(&self, builder: &Builder<'e>) -> BuildResult {
-        builder.create_output_for(self)
+        let mut handles = builder.create_output_for(self)?;
+        if builder.config.source_browser {
+            handles.extend(SourceFile::new(self.source.clone(), self.path.clone()).build(builder)?);
+        }
+        Ok(handles)
     }
 
     fn nav(&self) -> NavItem {
@@ -66,14 +83,8 @@ impl<'e> OutputEntry<'e> for File {
                     HtmlText::new(
                         builder
                             .config
-                            .project
-                            .tree
-                            .as_ref()
-                            .map(|tree| {
-                                tree.to_owned()
-                                    + self.source.dir.join(&self.path).to_string().as_str()
-                            })
-                            .unwrap_or("".into()),
+                            .source_url(&self.source.dir.join(&self.path), None)
+                            .unwrap_or_default(),
                     )
                     .into(),
                 ),
@@ -84,7 +95,7 @@ impl<'e> OutputEntry<'e> for File {
                 (
                     "functions",
                     fmt_section(
-                        "Functions",
+                        &builder.config.category_name("functions"),
                         builder
                             .root
                             .get(&|entry| {
@@ -98,10 +109,27 @@ impl<'e> OutputEntry<'e> for File {
                             .collect(),
                     ),
                 ),
+                (
+                    "variables",
+                    fmt_section(
+                        &builder.config.category_name("variables"),
+                        builder
+                            .root
+                            .get(&|entry| {
+                                matches!(
+                                    CppItemKind::from(entry.entity()),
+                                    Some(CppItemKind::Variable)
+                                ) && matcher(entry)
+                            })
+                            .into_iter()
+                            .map(|var| fmt_variable(var.entity(), builder))
+                            .collect(),
+                    ),
+                ),
                 (
                     "classes",
                     fmt_section(
-                        "Classes",
+                        &builder.config.category_name("classes"),
                         builder
                             .root
                             .get(&|entry| {
@@ -118,7 +146,7 @@ impl<'e> OutputEntry<'e> for File {
                 (
                     "structs",
                     fmt_section(
-                        "Structs",
+                        &builder.config.category_name("structs"),
                         builder
                             .root
                             .get(&|entry| {
@@ -142,6 +170,18 @@ impl<'e> OutputEntry<'e> for File {
             self.path, builder.config.project.name
         )
     }
+
+    fn breadcrumbs(&self, _builder: &'e Builder<'e>) -> Vec<(String, Option<UrlPath>)> {
+        dir_breadcrumbs(&self.path)
+    }
+
+    fn schema_type(&self) -> &'static str {
+        "SoftwareSourceCode"
+    }
+
+    fn source(&self) -> Option<String> {
+        Some(self.source.dir.join(&self.path).to_raw_string())
+    }
 }
 
 pub struct Dir {
@@ -214,16 +254,16 @@ impl Root {
 
         for root in &mut roots {
             for file in root.source.include.clone() {
-                let Ok(cut_path) = file.strip_prefix(root.source.dir.to_pathbuf()) else {
+                let Some(cut_path) = strip_path_prefix(&file, &root.source.dir.to_pathbuf()) else {
                     continue;
                 };
 
                 // If this is a directory, just add the whole structure
                 if file.is_dir() {
-                    root.add_dirs(cut_path);
+                    root.add_dirs(&cut_path);
                 } else {
                     // Add to parent if one exists, or to root if one doesn't
-                    let url = UrlPath::try_from(&cut_path.to_path_buf()).unwrap();
+                    let url = UrlPath::try_from(&cut_path).unwrap();
                     let def = root.source.clone();
                     root.try_add_dirs(cut_path.parent())
                         .files
@@ -271,14 +311,17 @@ impl<'e> Entry<'e> for Root {
     }
 
     fn nav(&self) -> NavItem {
-        NavItem::Root(
-            Some(self.name()),
-            self.dir
-                .dirs
-                .iter()
-                .map(|e| e.1.nav())
-                .chain(self.dir.files.iter().map(|e| e.1.nav()))
-                .collect(),
-        )
+        let items = self
+            .dir
+            .dirs
+            .iter()
+            .map(|e| e.1.nav())
+            .chain(self.dir.files.iter().map(|e| e.1.nav()))
+            .collect();
+        if self.source.nav_title.is_some() {
+            NavItem::new_section(&self.source, items)
+        } else {
+            NavItem::Root(Some(self.name()), items)
+        }
     }
 }
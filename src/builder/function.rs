@@ -1,22 +1,31 @@
 use std::sync::Arc;
 
-use crate::{html::Html, url::UrlPath};
+use crate::{config::Config, html::Html, url::UrlPath};
 use clang::Entity;
 
 use super::{
     builder::Builder,
-    shared::output_function,
-    traits::{ASTEntry, BuildResult, EntityMethods, Entry, NavItem, OutputEntry},
+    operators::operator_title,
+    shared::{entity_breadcrumbs, output_entity_markdown, output_function},
+    traits::{
+        ASTEntry, BuildResult, EntityMethods, Entry, NavItem, OutputEntry, resolve_docs_url,
+        unresolved_docs_url,
+    },
 };
 
 pub struct Function<'e> {
     entity: Entity<'e>,
+    config: Arc<Config>,
     overload_index: Option<usize>,
 }
 
 impl<'e> Function<'e> {
-    pub fn new(entity: Entity<'e>) -> Self {
-        Self { entity, overload_index: None }
+    pub fn new(entity: Entity<'e>, config: Arc<Config>) -> Self {
+        Self {
+            entity,
+            config,
+            overload_index: None,
+        }
     }
 
     pub fn add_overload_index(&mut self, index: usize) {
@@ -26,16 +35,27 @@ impl<'e> Function<'e> {
 
 impl<'e> Entry<'e> for Function<'e> {
     fn name(&self) -> String {
-        self.entity
+        let name = self
+            .entity
             .get_name()
-            .unwrap_or("`Anonymous function`".into())
+            .unwrap_or("`Anonymous function`".into());
+        operator_title(&name).unwrap_or(name)
     }
 
     fn url(&self) -> UrlPath {
-        self.entity
-            .rel_docs_url()
-            .expect("Unable to get function URL")
-            .append_to_last(self.overload_index.map_or("".into(), |i| i.to_string()).as_str())
+        // The index is part of the URL that collisions are checked for
+        match self.overload_index {
+            Some(index) => resolve_docs_url(
+                &self.config,
+                unresolved_docs_url(&self.entity, &self.config)
+                    .expect("Unable to get function URL")
+                    .append_to_last(&index.to_string()),
+            ),
+            None => self
+                .entity
+                .rel_docs_url(self.config.clone())
+                .expect("Unable to get function URL"),
+        }
     }
 
     fn build(&self, builder: &Builder<'e>) -> BuildResult {
@@ -68,4 +88,20 @@ impl<'e> OutputEntry<'e> for Function<'e> {
     fn description(&self, builder: &'e Builder<'e>) -> String {
         self.output_description(builder)
     }
+
+    fn markdown(&self, builder: &'e Builder<'e>) -> Option<String> {
+        Some(output_entity_markdown(self, builder))
+    }
+
+    fn breadcrumbs(&self, builder: &'e Builder<'e>) -> Vec<(String, Option<UrlPath>)> {
+        entity_breadcrumbs(builder.config.clone(), self)
+    }
+
+    fn schema_type(&self) -> &'static str {
+        "SoftwareSourceCode"
+    }
+
+    fn source(&self) -> Option<String> {
+        self.entity().get_usr().map(|usr| usr.0)
+    }
 }
@@ -0,0 +1,211 @@
+use std::{collections::HashMap, fs, sync::Arc};
+
+use clang::Entity;
+use regex_lite::Regex;
+
+use crate::{
+    config::Config,
+    diagnostics::{WarningKind, report},
+    html::{Html, HtmlText},
+    url::UrlPath,
+};
+
+use super::{
+    builder::Builder,
+    comment::{GroupDef, parse_group_commands},
+    markdown::fmt_markdown,
+    namespace::{CppItemKind, Namespace},
+    shared::{fmt_autolinks, fmt_class_method, fmt_classlike_decl, fmt_section, fmt_variable},
+    traits::{BuildResult, Entry, NavItem, OutputEntry},
+};
+
+/// A topic page created with `@defgroup`, listing everything added to it with
+/// `@ingroup`
+pub struct Group<'e> {
+    def: GroupDef,
+    members: Vec<Entity<'e>>,
+}
+
+impl<'e> Group<'e> {
+    fn members_of_kind(&self, kind: CppItemKind) -> Vec<&Entity<'e>> {
+        self.members
+            .iter()
+            .filter(|e| CppItemKind::from(e).is_some_and(|k| k == kind))
+            .collect()
+    }
+}
+
+impl<'e> Entry<'e> for Group<'e> {
+    fn name(&self) -> String {
+        self.def.title.clone()
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::parse("groups")
+            .unwrap()
+            .join(UrlPath::part(&self.def.name))
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(
+            &self.name(),
+            self.url(),
+            Some(("layers", false)),
+            Vec::new(),
+        )
+    }
+}
+
+impl<'e> OutputEntry<'e> for Group<'e> {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+        (
+            builder.config.templates.group.clone(),
+            vec![
+                ("name", HtmlText::new(self.name()).into()),
+                (
+                    "description",
+                    self.def
+                        .description
+                        .as_ref()
+                        .map(|d| {
+                            fmt_markdown(builder, &fmt_autolinks(builder, d), None::<fn(_) -> _>)
+                        })
+                        .unwrap_or(Html::p("")),
+                ),
+                (
+                    "classes",
+                    fmt_section(
+                        &builder.config.category_name("classes"),
+                        self.members_of_kind(CppItemKind::Class)
+                            .into_iter()
+                            .map(|cls| fmt_classlike_decl(cls, "class", builder))
+                            .collect(),
+                    ),
+                ),
+                (
+                    "structs",
+                    fmt_section(
+                        &builder.config.category_name("structs"),
+                        self.members_of_kind(CppItemKind::Struct)
+                            .into_iter()
+                            .map(|cls| fmt_classlike_decl(cls, "struct", builder))
+                            .collect(),
+                    ),
+                ),
+                (
+                    "functions",
+                    fmt_section(
+                        &builder.config.category_name("functions"),
+                        self.members_of_kind(CppItemKind::Function)
+                            .into_iter()
+                            .map(|fun| fmt_class_method(fun, builder))
+                            .collect(),
+                    ),
+                ),
+                (
+                    "variables",
+                    fmt_section(
+                        &builder.config.category_name("variables"),
+                        self.members_of_kind(CppItemKind::Variable)
+                            .into_iter()
+                            .map(|var| fmt_variable(var, builder))
+                            .collect(),
+                    ),
+                ),
+            ],
+        )
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!(
+            "Documentation for the {} group in {}",
+            self.name(),
+            builder.config.project.name
+        )
+    }
+}
+
+pub struct Groups<'e> {
+    groups: Vec<Group<'e>>,
+}
+
+impl<'e> Groups<'e> {
+    pub fn new(config: Arc<Config>, root: &Namespace<'e>) -> Self {
+        let mut defs = Vec::new();
+        let mut members = HashMap::new();
+        root.collect_groups(&mut defs, &mut members);
+
+        // Groups are usually defined in a standalone comment that isn't
+        // attached to any entity, so look through the headers for those too
+        let comment = Regex::new(r"(?s:/\*\*.*?\*/)|(?m:(?:^[ \t]*///[^\n]*\n?)+)").unwrap();
+        for file in config.sources.iter().flat_map(|src| src.include.iter()) {
+            let Ok(data) = fs::read_to_string(config.input_dir.join(file)) else {
+                continue;
+            };
+            for found in comment.find_iter(&data) {
+                if found.as_str().contains("defgroup") {
+                    defs.extend(parse_group_commands(found.as_str()).0);
+                }
+            }
+        }
+
+        let mut groups: Vec<Group<'e>> = Vec::new();
+        for def in defs {
+            // The same comment may have been found both as an entity comment
+            // and while looking through the headers
+            if groups.iter().any(|g| g.def.name == def.name) {
+                continue;
+            }
+            groups.push(Group {
+                members: members.remove(&def.name).unwrap_or_default(),
+                def,
+            });
+        }
+        for (name, members) in members {
+            report(
+                WarningKind::UnknownGroup,
+                ("", 0),
+                format_args!(
+                    "Group '{name}' is used with @ingroup but never defined with @defgroup"
+                ),
+            );
+            groups.push(Group {
+                def: GroupDef {
+                    title: name.clone(),
+                    name,
+                    description: None,
+                },
+                members,
+            });
+        }
+        groups.sort_by(|a, b| a.def.title.cmp(&b.def.title));
+
+        Self { groups }
+    }
+}
+
+impl<'e> Entry<'e> for Groups<'e> {
+    fn name(&self) -> String {
+        String::from("Groups")
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::parse("groups").unwrap()
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        let mut handles = Vec::new();
+        for group in &self.groups {
+            handles.extend(group.build(builder)?);
+        }
+        Ok(handles)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_root(None, self.groups.iter().map(|g| g.nav()).collect())
+    }
+}
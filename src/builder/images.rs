@@ -0,0 +1,205 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use image::{DynamicImage, imageops::FilterType};
+use log::warn;
+
+use super::builder::Builder;
+use crate::{
+    config::ImagesConfig,
+    html::{GenHtml, HtmlElement},
+    url::UrlPath,
+};
+
+/// Where images referenced from a tutorial are read from and copied to
+pub struct ImageContext {
+    /// Directory of the markdown file, which image paths are relative to
+    pub source_dir: PathBuf,
+    /// URL of the page the tutorial is output to
+    pub page_url: UrlPath,
+}
+
+/// Copy an image next to the page that references it and get the names of
+/// the smaller versions of it for responsive loading, which are generated in
+/// the background since decoding and resizing is slow
+fn process_image(
+    builder: &Builder,
+    images: &ImagesConfig,
+    ctx: &ImageContext,
+    dest: &str,
+) -> Option<(String, Vec<(String, u32)>, Vec<(String, u32)>, (u32, u32))> {
+    let path = ctx.source_dir.join(dest);
+    let file_name = path.file_name()?.to_str()?.to_owned();
+    let stem = path.file_stem()?.to_str()?.to_owned();
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    let output_dir = builder.config.output_dir.join(ctx.page_url.to_pathbuf());
+
+    fs::create_dir_all(&output_dir)
+        .and_then(|_| fs::copy(&path, output_dir.join(&file_name)))
+        .inspect_err(|e| warn!("Unable to copy image '{}': {e}", path.to_string_lossy()))
+        .ok()?;
+    builder.track_file(&output_dir.join(&file_name));
+
+    // Only the header has to be read for the size
+    let size = image::image_dimensions(&path).ok()?;
+
+    // Vector images and gifs are left as is
+    let mut variants = Vec::new();
+    let mut webp_variants = Vec::new();
+    if matches!(ext.as_str(), "png" | "jpg" | "jpeg") {
+        for width in images.widths.iter().copied().filter(|w| *w < size.0) {
+            variants.push((format!("{stem}-{width}.{ext}"), width));
+            if images.webp {
+                webp_variants.push((format!("{stem}-{width}.webp"), width));
+            }
+        }
+        if images.webp {
+            webp_variants.push((format!("{stem}.webp"), size.0));
+        }
+        let outputs = variants
+            .iter()
+            .chain(&webp_variants)
+            .map(|(name, width)| (output_dir.join(name), *width))
+            .collect::<Vec<_>>();
+        if !outputs.is_empty() {
+            builder.spawn_blocking_output(move || resize_image(&path, outputs));
+        }
+    }
+
+    Some((file_name, variants, webp_variants, size))
+}
+
+/// Save an image at each of the given widths, in the format of the extension
+/// of each path, returning the paths of the files that were written
+fn resize_image(path: &Path, outputs: Vec<(PathBuf, u32)>) -> Vec<PathBuf> {
+    let img = match image::open(path) {
+        Ok(img) => DynamicImage::ImageRgba8(img.to_rgba8()),
+        Err(e) => {
+            warn!("Unable to read image '{}': {e}", path.to_string_lossy());
+            return Vec::new();
+        }
+    };
+    outputs
+        .into_iter()
+        .filter(|(dest, width)| {
+            let res = if *width < img.width() {
+                img.resize(*width, u32::MAX, FilterType::Lanczos3)
+                    .save(dest)
+            } else {
+                img.save(dest)
+            };
+            res.inspect_err(|e| warn!("Unable to save image '{}': {e}", dest.to_string_lossy()))
+                .is_ok()
+        })
+        .map(|(dest, _)| dest)
+        .collect()
+}
+
+/// Format a video from a `{{video path}}` shortcode, copying it next to the
+/// page if it's a local file. It isn't loaded until it's played
+pub fn fmt_video(builder: &Builder, ctx: Option<&ImageContext>, dest: &str) -> String {
+    let is_local = !dest.contains("://") && !dest.starts_with('/');
+    let src = match ctx {
+        Some(ctx) if is_local => {
+            let path = ctx.source_dir.join(dest);
+            let output_dir = builder.config.output_dir.join(ctx.page_url.to_pathbuf());
+            match path.file_name().map(|f| f.to_string_lossy().to_string()) {
+                Some(file_name) => {
+                    match fs::create_dir_all(&output_dir)
+                        .and_then(|_| fs::copy(&path, output_dir.join(&file_name)))
+                    {
+                        Ok(_) => builder.track_file(&output_dir.join(&file_name)),
+                        Err(e) => warn!("Unable to copy video '{}': {e}", path.to_string_lossy()),
+                    }
+                    ctx.page_url
+                        .join(UrlPath::part(&file_name))
+                        .to_absolute(builder.config.clone())
+                        .to_string()
+                }
+                None => dest.to_owned(),
+            }
+        }
+        _ => dest.to_owned(),
+    };
+    HtmlElement::new("video")
+        .with_class("video-embed")
+        .with_attr("src", src)
+        .with_bool_attr("controls", true)
+        .with_attr("preload", "none")
+        .gen_html()
+}
+
+/// Format an image in a tutorial, processing it if it's a local file
+pub fn fmt_image(
+    builder: &Builder,
+    ctx: &ImageContext,
+    dest: &str,
+    alt: &str,
+    title: &str,
+) -> String {
+    let config = builder.config.clone();
+    let images = config.tutorials.as_ref().and_then(|t| t.images.as_ref());
+    let is_local = !dest.contains("://") && !dest.starts_with('/') && !dest.starts_with("data:");
+
+    let processed = images
+        .filter(|_| is_local)
+        .and_then(|images| process_image(builder, images, ctx, dest));
+
+    let lazy = images.is_some_and(|i| i.lazy);
+    let img = HtmlElement::new("img")
+        .with_attr("alt", alt)
+        .with_attr_opt("title", (!title.is_empty()).then_some(title))
+        .with_attr_opt("loading", lazy.then_some("lazy"))
+        .with_attr("decoding", "async");
+
+    let Some((file_name, variants, webp_variants, (width, height))) = processed else {
+        return img.with_attr("src", dest).gen_html();
+    };
+
+    // Links to the page don't end in a slash, so the files next to it need
+    // absolute URLs
+    let url_for = |name: &str| {
+        ctx.page_url
+            .join(UrlPath::part(name))
+            .to_absolute(config.clone())
+            .to_string()
+    };
+    let srcset = |list: Vec<(String, u32)>| {
+        list.iter()
+            .map(|(name, w)| format!("{} {w}w", url_for(name)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let img = img
+        .with_attr("src", url_for(&file_name))
+        .with_attr("width", width)
+        .with_attr("height", height)
+        .with_attr_opt(
+            "srcset",
+            (!variants.is_empty()).then(|| {
+                srcset(
+                    variants
+                        .iter()
+                        .cloned()
+                        .chain([(file_name.clone(), width)])
+                        .collect(),
+                )
+            }),
+        );
+
+    if webp_variants.is_empty() {
+        img.gen_html()
+    } else {
+        HtmlElement::new("picture")
+            .with_child(
+                HtmlElement::new("source")
+                    .with_attr("type", "image/webp")
+                    .with_attr("srcset", srcset(webp_variants)),
+            )
+            .with_child(img)
+            .gen_html()
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Component, Path},
+};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{config::Config, url::UrlPath};
+
+/// Files made while parsing the headers, which aren't part of the docs
+const SCRATCH_FILES: &[&str] = &[
+    "_analyze.c",
+    "_analyze.cpp",
+    "_precompiled.pch",
+    "_precompiled.args",
+];
+
+/// A file written by a build
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ManifestFile {
+    /// Path relative to the output directory
+    pub path: String,
+    /// URL of the page the file is part of, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// What the page was made from, like the USR of an entity or the path of
+    /// a markdown file relative to the input directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// SHA-256 hash of the contents
+    pub hash: String,
+    /// Size in bytes
+    pub size: usize,
+}
+
+impl ManifestFile {
+    pub fn new(path: String, data: &[u8]) -> Self {
+        Self {
+            path,
+            url: None,
+            source: None,
+            hash: format!("{:x}", Sha256::digest(data)),
+            size: data.len(),
+        }
+    }
+
+    /// Make the entry for one of the files of a page
+    pub fn for_page(url: &UrlPath, source: Option<String>, file: &str, data: &[u8]) -> Self {
+        let dir = url.to_pathbuf().to_string_lossy().replace('\\', "/");
+        Self {
+            url: Some(url.to_raw_string()),
+            source,
+            ..Self::new(
+                if dir.is_empty() {
+                    file.to_owned()
+                } else {
+                    format!("{dir}/{file}")
+                },
+                data,
+            )
+        }
+    }
+
+    /// Make the entry for a file that has already been written to the output
+    /// directory, if it exists
+    pub fn from_output(config: &Config, path: &str) -> Option<Self> {
+        let data = fs::read(config.output_dir.join(path)).ok()?;
+        Some(Self::new(path.to_owned(), &data))
+    }
+}
+
+/// Every file a build wrote to the output directory, so deploys can upload
+/// only what changed, the next build can remove pages that don't exist
+/// anymore and `flash clean` can remove everything without touching files
+/// that Flash didn't create
+#[derive(Serialize, Deserialize, Default)]
+pub struct Manifest {
+    files: Vec<ManifestFile>,
+}
+
+impl Manifest {
+    pub fn new(mut files: Vec<ManifestFile>) -> Self {
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        files.dedup_by(|a, b| a.path == b.path);
+        Self { files }
+    }
+
+    /// Read the manifest of the last build into the output directory, if
+    /// there was one
+    pub fn read(config: &Config) -> Option<Self> {
+        let data = fs::read_to_string(config.output_dir.join(&config.manifest_file)).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    pub fn write(&self, config: &Config) -> Result<(), String> {
+        fs::write(
+            config.output_dir.join(&config.manifest_file),
+            serde_json::to_string(self).map_err(|e| format!("Unable to save manifest: {e}"))?,
+        )
+        .map_err(|e| format!("Unable to save manifest: {e}"))
+    }
+
+    /// Remove the files of this manifest that aren't in a newer one,
+    /// returning how many were removed
+    pub fn prune(&self, config: &Config, newer: &Manifest) -> usize {
+        let files = newer.files.iter().map(|f| &f.path).collect::<HashSet<_>>();
+        let mut removed = 0;
+        for file in self.files.iter().filter(|f| !files.contains(&f.path)) {
+            // The manifest is read from disk, so make sure it can't be used to
+            // remove anything outside of the output directory
+            if !Path::new(&file.path)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+            {
+                warn!(
+                    "Not removing '{}' since it's outside of the output directory",
+                    file.path
+                );
+                continue;
+            }
+            debug!("Removing stale file {}", file.path);
+            if remove_file(&config.output_dir, &config.output_dir.join(&file.path)) {
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    /// Remove everything in this manifest along with the manifest itself and
+    /// any scratch files, returning how many files were removed
+    pub fn clean(&self, config: &Config) -> usize {
+        let removed = self.prune(config, &Manifest::default());
+        for file in SCRATCH_FILES.iter().chain([&config.manifest_file.as_str()]) {
+            remove_file(&config.output_dir, &config.output_dir.join(file));
+        }
+        removed
+    }
+}
+
+/// Remove a file along with any directories it leaves empty, up to the output
+/// directory
+fn remove_file(output_dir: &Path, file: &Path) -> bool {
+    if fs::remove_file(file).is_err() {
+        return false;
+    }
+    let mut dir = file.parent();
+    while let Some(parent) = dir
+        && parent != output_dir
+        && parent.starts_with(output_dir)
+        && fs::remove_dir(parent).is_ok()
+    {
+        dir = parent.parent();
+    }
+    true
+}
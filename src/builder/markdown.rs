@@ -1,29 +1,23 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+
 use super::builder::Builder;
+use super::images::{ImageContext, fmt_image, fmt_video};
 use super::shared::fmt_emoji;
+use super::tags::fmt_tags;
 use super::traits::Entry;
-use crate::html::{Html, HtmlElement, HtmlText};
+use super::tutorial::SeriesPart;
+use crate::config::{Config, MarkdownStyle, SanitizeHtml};
+use crate::diagnostics::{WarningKind, report};
+use crate::html::{GenHtml, Html, HtmlElement, HtmlText, RawHtml, escape_attr};
 use crate::lookahead::{CachedLookahead, CreateCachedLookahead};
 use crate::url::UrlPath;
-use pulldown_cmark::{CowStr, Event, LinkType, Tag};
-use serde::{Deserialize, Deserializer};
-
-#[derive(Clone, PartialEq, Default)]
-pub enum Style {
-    #[default]
-    Default,
-    QnA,
-}
-
-fn parse_style<'de, D>(deserializer: D) -> Result<Style, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    match String::deserialize(deserializer)?.as_str() {
-        "default" => Ok(Style::Default),
-        "qna" => Ok(Style::QnA),
-        _ => Err(serde::de::Error::custom("Invalid style")),
-    }
-}
+use log::warn;
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, HeadingLevel, LinkType, Tag};
+use regex_lite::{Captures, Regex};
+use serde::Deserialize;
 
 #[derive(Deserialize, Clone, Default)]
 pub struct Metadata {
@@ -31,8 +25,28 @@ pub struct Metadata {
     pub description: Option<String>,
     pub icon: Option<String>,
     pub order: Option<usize>,
-    #[serde(default = "Style::default", deserialize_with = "parse_style")]
-    pub style: Style,
+    /// Name of a style from `markdown.styles` or a built-in one like `qna`
+    pub style: Option<String>,
+    /// Drafts are skipped entirely unless building with `--drafts`
+    #[serde(default)]
+    pub draft: bool,
+    /// Unlisted pages are built but left out of the navbar and search index
+    #[serde(default)]
+    pub unlisted: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Name of a template in the `templates.dir` directory to use instead of
+    /// the default tutorial template
+    pub template: Option<String>,
+    /// Publication date for the feed, like `2024-05-01`; defaults to the date
+    /// of the last commit changing the file
+    pub date: Option<String>,
+    /// Overrides `tutorials.shift-headings` for this page
+    pub shift_headings: Option<bool>,
+    /// Name of the series of tutorials this one is a part of
+    pub series: Option<String>,
+    /// Position of this tutorial in its series, starting from 1
+    pub series_index: Option<usize>,
 }
 
 impl Metadata {
@@ -44,27 +58,172 @@ impl Metadata {
     }
 }
 
-fn parse_markdown_metadata(doc: &str) -> (&str, Option<Metadata>) {
+/// Metadata at the start of a markdown document that couldn't be parsed
+pub struct MetadataError {
+    /// Line of the document the error is on
+    pub line: u32,
+    pub message: String,
+}
+
+/// Split the metadata at the start of a markdown document from the rest of
+/// it. The metadata is YAML between `---` lines or TOML between `+++` lines.
+/// If the metadata is invalid, the rest of the document is still returned
+/// along with the error
+fn split_markdown_metadata(doc: &str) -> (&str, Result<Option<Metadata>, MetadataError>) {
     // if the document has no metadata just parse it as markdown
-    if !doc.trim_start().starts_with("---") {
-        return (doc, None);
-    }
+    let Some(delimiter) = ["---", "+++"].into_iter().find(|d| {
+        doc.trim_start()
+            .lines()
+            .next()
+            .is_some_and(|line| line.trim_end() == *d)
+    }) else {
+        return (doc, Ok(None));
+    };
 
-    let doc = doc.trim_start().strip_prefix("---").unwrap();
+    // Lines before the metadata, for pointing errors at the right line
+    let leading_lines = doc[..doc.len() - doc.trim_start().len()]
+        .matches('\n')
+        .count();
+    let doc = doc.trim_start().strip_prefix(delimiter).unwrap();
 
     // make sure metadata ends properly
-    let Some(metadata_end) = doc.find("---") else {
-        return (doc, None);
+    let Some(metadata_end) = find_delimiter_line(doc, delimiter) else {
+        return (doc, Ok(None));
     };
     let metadata_str = &doc[..metadata_end];
 
-    // parse metadata
-    (
-        &doc[metadata_end + 3..],
-        serde_yaml::from_str(metadata_str).expect("Invalid metadata in markdown"),
-    )
+    // parse metadata, where the line of the opening delimiter is the first
+    // line of the metadata
+    let metadata = if delimiter == "+++" {
+        toml::from_str(metadata_str).map_err(|e| MetadataError {
+            line: (leading_lines
+                + e.span()
+                    .map_or(0, |span| metadata_str[..span.start].matches('\n').count())
+                + 1) as u32,
+            message: e.message().to_owned(),
+        })
+    } else {
+        serde_yaml::from_str(metadata_str).map_err(|e| {
+            let line = e.location().map_or(1, |l| l.line());
+            let message = e.to_string();
+            MetadataError {
+                line: (leading_lines + line) as u32,
+                // The location is already given by the line of the error
+                message: message
+                    .rsplit_once(" at line ")
+                    .map_or(message.as_str(), |(msg, _)| msg)
+                    .to_owned(),
+            }
+        })
+    };
+    (&doc[metadata_end + delimiter.len()..], metadata.map(Some))
+}
+
+/// Find the start of the first line in a document that has nothing but the
+/// delimiter on it, so horizontal rules and delimiters inside strings don't
+/// end the metadata
+fn find_delimiter_line(doc: &str, delimiter: &str) -> Option<usize> {
+    let mut offset = 0;
+    for line in doc.split_inclusive('\n') {
+        if line.trim_end() == delimiter {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+fn parse_markdown_metadata(doc: &str) -> (&str, Option<Metadata>) {
+    let (doc, metadata) = split_markdown_metadata(doc);
+    (doc, metadata.ok().flatten())
+}
+
+/// Get the language and tab name of a fenced code block that is part of a tab
+/// group, like ```` ```cpp tab="Windows" ````
+fn parse_code_tab(info: &str) -> Option<(String, String)> {
+    let (lang, rest) = info.split_once(' ').unwrap_or((info, ""));
+    let name = rest.split_once("tab=\"")?.1.split('"').next()?;
+    Some((lang.to_string(), name.to_string()))
+}
+
+/// Format a group of consecutive code blocks as tabs, of which only the
+/// first is shown to begin with
+fn fmt_code_tabs(tabs: Vec<((String, String), String)>) -> String {
+    let mut buttons = HtmlElement::new("div").with_class("code-tab-buttons");
+    let mut blocks = Vec::new();
+    for (i, ((lang, name), code)) in tabs.into_iter().enumerate() {
+        let selected = (i == 0).then_some("selected");
+        let lang_class = (!lang.is_empty()).then(|| format!("language-{lang}"));
+        buttons.add_child(
+            HtmlElement::new("button")
+                .with_class("code-tab-button")
+                .with_class_opt(selected)
+                .with_attr("data-tab", &name)
+                .with_text(&name),
+        );
+        blocks.push(
+            HtmlElement::new("pre")
+                .with_class("code-tab")
+                .with_class_opt(selected)
+                .with_attr("data-tab", &name)
+                .with_child(
+                    HtmlElement::new("code")
+                        .with_class_opt(lang_class.as_deref())
+                        .with_text(code),
+                )
+                .into(),
+        );
+    }
+    HtmlElement::new("div")
+        .with_class("code-tabs")
+        .with_child(buttons)
+        .with_children(blocks)
+        .gen_html()
 }
 
+/// Get the style a page picked in its metadata
+fn page_style(builder: &Builder, metadata: Option<&Metadata>) -> Option<MarkdownStyle> {
+    let name = metadata?.style.as_ref()?;
+    if name == "default" {
+        return None;
+    }
+    let style = builder.config.markdown_style(name);
+    if style.is_none() {
+        warn!("Unknown markdown style '{name}'");
+    }
+    style
+}
+
+/// Elements that raw HTML in tutorials can't use when sanitizing
+const UNSAFE_HTML_TAGS: &[&str] = &[
+    "script", "style", "iframe", "frame", "frameset", "object", "embed", "base", "link", "meta",
+    "form",
+];
+
+/// Raw HTML with unsafe elements, event handler attributes or `javascript:`
+/// URLs
+static UNSAFE_HTML: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"(?i)<\s*/?\s*({})\b|\son[a-z]+\s*=|javascript:",
+        UNSAFE_HTML_TAGS.join("|")
+    ))
+    .unwrap()
+});
+
+/// Opening tags of unsafe elements, with the name of the element in the first
+/// group
+static UNSAFE_OPEN_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(&format!(r"(?i)<\s*({})\b", UNSAFE_HTML_TAGS.join("|"))).unwrap());
+
+/// `{{youtube id}}` and `{{video path}}` shortcodes, with the kind of embed in
+/// the first group and its argument in the second
+static EMBED_SHORTCODE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*(youtube|video)\s+([^}\s]+)\s*\}\}").unwrap());
+
+/// Closing tags, with the name of the element in the first group
+static CLOSING_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"</\s*([A-Za-z][A-Za-z0-9-]*)\s*>").unwrap());
+
 #[derive(PartialEq)]
 enum InsertP {
     Dont,
@@ -76,9 +235,20 @@ struct MDStream<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlP
     iter: CachedLookahead<pulldown_cmark::Parser<'i, 'c>, SIZE>,
     url_fixer: Option<F>,
     builder: &'b Builder<'e>,
-    metadata: Option<Metadata>,
+    style: Option<MarkdownStyle>,
+    images: Option<ImageContext>,
     insert_para_stage: InsertP,
     inside_code_block: bool,
+    heading_ids: HashSet<String>,
+    /// Output headings one level lower so `#` doesn't compete with the
+    /// page's own title
+    shift_headings: bool,
+    /// Whether each link that's currently open leaves the docs
+    external_links: Vec<bool>,
+    /// The unsafe element raw HTML is currently inside of, when sanitizing
+    unsafe_html: Option<String>,
+    /// Whether the next inline code is a keyboard shortcut after `:kbd:`
+    next_code_is_kbd: bool,
 }
 
 impl<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlPath>>
@@ -88,17 +258,127 @@ impl<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlPath>>
         iter: pulldown_cmark::Parser<'i, 'c>,
         url_fixer: Option<F>,
         builder: &'b Builder<'e>,
-        metadata: Option<Metadata>,
+        style: Option<MarkdownStyle>,
+        images: Option<ImageContext>,
+        shift_headings: bool,
     ) -> MDStream<'i, 'c, 'b, 'e, SIZE, F> {
         MDStream {
             iter: iter.lookahead_cached::<SIZE>(),
             url_fixer,
             builder,
-            metadata,
+            style,
+            images,
             insert_para_stage: InsertP::Dont,
             inside_code_block: false,
+            heading_ids: HashSet::new(),
+            shift_headings,
+            external_links: Vec::new(),
+            unsafe_html: None,
+            next_code_is_kbd: false,
         }
     }
+
+    /// What to do with unsafe raw HTML, if it's sanitized at all
+    fn sanitize_mode(&self) -> Option<SanitizeHtml> {
+        self.builder
+            .config
+            .markdown
+            .as_ref()
+            .and_then(|m| m.sanitize_html)
+    }
+
+    /// Strip or escape raw HTML with elements that could run scripts or
+    /// change the rest of the page, along with all raw HTML inside them. The
+    /// markdown inside them is left out in `next` when stripping
+    fn sanitize_html(&mut self, html: CowStr<'i>, mode: SanitizeHtml) -> Event<'i> {
+        let closes = |tag: &str| {
+            CLOSING_TAG
+                .captures_iter(&html)
+                .any(|caps| caps[1].eq_ignore_ascii_case(tag))
+        };
+
+        let is_unsafe = self.unsafe_html.is_some() || UNSAFE_HTML.is_match(&html);
+        if let Some(ref tag) = self.unsafe_html {
+            if closes(tag) {
+                self.unsafe_html = None;
+            }
+        } else if let Some(caps) = UNSAFE_OPEN_TAG.captures(&html)
+            && !closes(&caps[1])
+            && !html.trim_end().ends_with("/>")
+        {
+            self.unsafe_html = Some(caps[1].to_lowercase());
+        }
+
+        if !is_unsafe {
+            return Event::Html(html);
+        }
+        match mode {
+            SanitizeHtml::Strip => Event::Html(CowStr::Borrowed("")),
+            SanitizeHtml::Escape => Event::Text(html),
+        }
+    }
+
+    /// Get the level a heading is output at. Styles still match the level
+    /// it was written at
+    fn output_level(&self, lvl: HeadingLevel) -> HeadingLevel {
+        if self.shift_headings {
+            HeadingLevel::try_from((lvl as usize + 1).min(6)).unwrap_or(lvl)
+        } else {
+            lvl
+        }
+    }
+
+    /// Make a generated heading id unique by adding `-1`, `-2` etc. to it if
+    /// another heading already has it
+    fn unique_heading_id(&mut self, id: String) -> String {
+        let mut res = id.clone();
+        let mut num = 0;
+        while !self.heading_ids.insert(res.clone()) {
+            num += 1;
+            res = format!("{id}-{num}");
+        }
+        res
+    }
+
+    /// Replace `{{youtube id}}` and `{{video path}}` shortcodes in some text
+    /// with embeds, escaping the rest of it. Returns `None` if the text has no
+    /// shortcodes
+    fn expand_embeds(&self, text: &str) -> Option<String> {
+        if !EMBED_SHORTCODE.is_match(text) {
+            return None;
+        }
+        let mut res = String::new();
+        let mut last = 0;
+        for caps in EMBED_SHORTCODE.captures_iter(text) {
+            let shortcode = caps.get(0).unwrap();
+            let arg = &caps[2];
+            let embed = match &caps[1] {
+                "youtube" => fmt_youtube(self.builder, arg),
+                _ => Some(fmt_video(self.builder, self.images.as_ref(), arg)),
+            };
+            let Some(embed) = embed else {
+                continue;
+            };
+            res += &HtmlText::new(&text[last..shortcode.start()]).gen_html();
+            res += &embed;
+            last = shortcode.end();
+        }
+        res += &HtmlText::new(&text[last..]).gen_html();
+        Some(res)
+    }
+
+    /// Consume the rest of a code block and get its contents
+    fn take_code_block(&mut self) -> String {
+        let mut code = String::new();
+        for ev in self.iter.by_ref() {
+            match ev {
+                Event::End(Tag::CodeBlock(_)) => break,
+                Event::Text(t) => code.push_str(&t),
+                _ => {}
+            }
+        }
+        code
+    }
 }
 
 impl<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlPath>> Iterator
@@ -107,41 +387,176 @@ impl<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlPath>> Itera
     type Item = Event<'i>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.insert_para_stage == InsertP::Start {
-            self.insert_para_stage = InsertP::ToEnd;
-            return Some(Event::Start(Tag::BlockQuote));
-        } else if self.insert_para_stage == InsertP::ToEnd
-            && match self.iter.peek() {
-                Some(Event::Start(Tag::Heading(lvl, _, _))) => (*lvl as usize) == 2,
-                None => true,
-                _ => false,
-            }
+        // Wrap the content after headings for styles that ask for it
+        if let Some(ref style) = self.style
+            && let Some(wrap_after) = style.wrap_after
         {
-            self.insert_para_stage = InsertP::Dont;
-            return Some(Event::End(Tag::BlockQuote));
+            if self.insert_para_stage == InsertP::Start {
+                self.insert_para_stage = InsertP::ToEnd;
+                let open = match style.wrap_class {
+                    Some(ref class) => {
+                        format!("<{} class=\"{}\">", style.wrap_tag, escape_attr(class))
+                    }
+                    None => format!("<{}>", style.wrap_tag),
+                };
+                return Some(Event::Html(CowStr::Boxed(Box::from(open.as_str()))));
+            } else if self.insert_para_stage == InsertP::ToEnd
+                && match self.iter.peek() {
+                    Some(Event::Start(Tag::Heading(lvl, _, _))) => (*lvl as usize) <= wrap_after,
+                    None => true,
+                    _ => false,
+                }
+            {
+                self.insert_para_stage = InsertP::Dont;
+                return Some(Event::Html(CowStr::Boxed(Box::from(
+                    format!("</{}>", style.wrap_tag).as_str(),
+                ))));
+            }
         }
         let event = self.iter.next()?;
+
+        // The markdown inside a stripped element is left out along with it,
+        // keeping the blocks it's in so the rest of the page isn't affected
+        if self.unsafe_html.is_some() && self.sanitize_mode() == Some(SanitizeHtml::Strip) {
+            match event {
+                Event::Start(Tag::Image(_, _, _)) => {
+                    for ev in self.iter.by_ref() {
+                        if let Event::End(Tag::Image(_, _, _)) = ev {
+                            break;
+                        }
+                    }
+                    return Some(Event::Text(CowStr::Borrowed("")));
+                }
+                Event::Start(_) | Event::End(_) | Event::Html(_) => {}
+                _ => return Some(Event::Text(CowStr::Borrowed(""))),
+            }
+        }
+
+        // Relative images in tutorials are processed and replaced with their
+        // own html, using the text inside the image tag as the alt text
+        if let Event::Start(Tag::Image(_, ref dest, ref title)) = event
+            && !dest.starts_with("/")
+            && let Some(ref ctx) = self.images
+        {
+            let mut alt = String::new();
+            for ev in self.iter.by_ref() {
+                match ev {
+                    Event::End(Tag::Image(_, _, _)) => break,
+                    Event::Text(t) | Event::Code(t) => alt.push_str(&t),
+                    _ => {}
+                }
+            }
+            return Some(Event::Html(CowStr::Boxed(Box::from(
+                fmt_image(self.builder, ctx, dest, &alt, title).as_str(),
+            ))));
+        }
+
+        // Consecutive code blocks with a tab name are grouped into tabs
+        if let Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(ref info))) = event
+            && let Some(tab) = parse_code_tab(info)
+        {
+            let mut tabs = vec![(tab, self.take_code_block())];
+            while let Some(Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))) =
+                self.iter.peek()
+                && let Some(tab) = parse_code_tab(info)
+            {
+                self.iter.next();
+                tabs.push((tab, self.take_code_block()));
+            }
+            return Some(Event::Html(CowStr::Boxed(Box::from(
+                fmt_code_tabs(tabs).as_str(),
+            ))));
+        }
+
+        // Links leaving the docs open in a new tab and are marked with an icon
+        if let Event::Start(Tag::Link(_, ref dest, ref title)) = event
+            && is_external_link(&self.builder.config, dest)
+        {
+            self.external_links.push(true);
+            let title = if title.is_empty() {
+                String::new()
+            } else {
+                format!(" title=\"{}\"", escape_attr(title))
+            };
+            return Some(Event::Html(CowStr::Boxed(Box::from(
+                format!(
+                    "<a class=\"external-link\" href=\"{}\"{title} target=\"_blank\" rel=\"noopener\">",
+                    escape_attr(dest)
+                )
+                .as_str(),
+            ))));
+        }
+        if let Event::End(Tag::Link(_, _, _)) = event
+            && self.external_links.pop() == Some(true)
+        {
+            return Some(Event::Html(CowStr::Boxed(Box::from(
+                format!("{}</a>", Html::feather("external-link").gen_html()).as_str(),
+            ))));
+        }
+
+        if let Event::Html(html) = event {
+            return Some(match self.sanitize_mode() {
+                Some(mode) => self.sanitize_html(html, mode),
+                None => Event::Html(html),
+            });
+        }
+
+        // Videos are embedded with shortcodes like {{youtube id}}
+        if let Event::Text(ref t) = event
+            && !self.inside_code_block
+            && let Some(html) = self.expand_embeds(t)
+        {
+            return Some(Event::Html(CowStr::Boxed(Box::from(html.as_str()))));
+        }
+
+        // Keyboard shortcuts are written like :kbd:`Ctrl+S`
+        if let Event::Text(ref t) = event
+            && !self.inside_code_block
+            && let Some(text) = t.strip_suffix(":kbd:")
+            && matches!(self.iter.peek(), Some(Event::Code(_)))
+        {
+            self.next_code_is_kbd = true;
+            if text.is_empty() {
+                return self.next();
+            }
+            return Some(Event::Text(CowStr::Boxed(Box::from(text))));
+        }
+        if let Event::Code(ref code) = event
+            && self.next_code_is_kbd
+        {
+            self.next_code_is_kbd = false;
+            return Some(Event::Html(CowStr::Boxed(Box::from(
+                fmt_kbd(code).as_str(),
+            ))));
+        }
+
         Some(match event {
             // Don't format emojis inside code blocks lol
             Event::Text(t) => {
                 if self.inside_code_block {
                     Event::Text(t)
                 } else {
-                    Event::Text(CowStr::Boxed(Box::from(fmt_emoji(&t).as_str())))
+                    match fmt_emoji(&t, self.builder.config.clone()) {
+                        (html, true) => Event::Html(CowStr::Boxed(Box::from(html.as_str()))),
+                        (text, false) => Event::Text(CowStr::Boxed(Box::from(text.as_str()))),
+                    }
                 }
             }
             Event::Start(tag) => Event::Start(match tag {
                 // Fix urls to point to root
                 Tag::Link(ty, ref dest, ref title) | Tag::Image(ty, ref dest, ref title) => {
+                    if matches!(tag, Tag::Link(_, _, _)) {
+                        self.external_links.push(false);
+                    }
                     let mut new_dest;
                     if ty == LinkType::Inline
                         && dest.starts_with("/")
                         && let Some(ref url_fixer) = self.url_fixer
                     {
-                        let url = UrlPath::new_with_path(
-                            dest.split("/").map(|s| s.to_string()).collect(),
-                        );
-                        if let Some(url) = url_fixer(url) {
+                        // Parsing keeps `#section` out of the file name
+                        if let Ok(url) = UrlPath::parse(dest)
+                            && let Some(url) = url_fixer(url)
+                        {
                             new_dest = url.to_unencoded_string();
                         } else {
                             new_dest = dest.to_string();
@@ -168,6 +583,16 @@ impl<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlPath>> Itera
                 }
                 // Add id to heading so they can be navigated to with url#header
                 Tag::Heading(lvl, mut frag, mut classes) => {
+                    // Ids given explicitly with `## Heading {#id}` are kept as is
+                    if let Some(ref id) = frag
+                        && !self.heading_ids.insert(id.to_string())
+                    {
+                        report(
+                            WarningKind::DuplicateHeadingId,
+                            ("", 0),
+                            format_args!("Heading id '{id}' is used more than once"),
+                        );
+                    }
                     if frag.is_none() && (lvl as usize) < 4 {
                         let mut buf = String::new();
                         for t in self.iter.lookahead() {
@@ -192,16 +617,17 @@ impl<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlPath>> Itera
                         }
                         // replace spaces with single hyphens
                         buf = buf.split_whitespace().collect::<Vec<_>>().join("-");
+                        buf = self.unique_heading_id(buf);
 
                         frag = Some(CowStr::Boxed(Box::from(buf)));
                     }
-                    if let Some(ref meta) = self.metadata
-                        && meta.style == Style::QnA
-                        && (lvl as usize) < 3
+                    if let Some(ref style) = self.style
+                        && let Some(ref class) = style.heading_class
+                        && style.heading_levels.contains(&(lvl as usize))
                     {
-                        classes.push(CowStr::Boxed(Box::from("qna-question")));
+                        classes.push(CowStr::Boxed(Box::from(class.as_str())));
                     }
-                    Tag::Heading(lvl, frag, classes)
+                    Tag::Heading(self.output_level(lvl), frag, classes)
                 }
                 Tag::CodeBlock(b) => {
                     self.inside_code_block = true;
@@ -211,13 +637,12 @@ impl<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlPath>> Itera
             }),
             Event::End(tag) => Event::End(match tag {
                 Tag::Heading(lvl, frag, classes) => {
-                    if let Some(ref meta) = self.metadata
-                        && meta.style == Style::QnA
-                        && (lvl as usize) == 2
+                    if let Some(ref style) = self.style
+                        && style.wrap_after == Some(lvl as usize)
                     {
                         self.insert_para_stage = InsertP::Start;
                     }
-                    Tag::Heading(lvl, frag, classes)
+                    Tag::Heading(self.output_level(lvl), frag, classes)
                 }
                 Tag::CodeBlock(b) => {
                     self.inside_code_block = false;
@@ -230,11 +655,122 @@ impl<'i, 'c, 'b, 'e, const SIZE: usize, F: Fn(UrlPath) -> Option<UrlPath>> Itera
     }
 }
 
+/// Format a YouTube video as its thumbnail, which is only replaced with the
+/// actual player once it's clicked so nothing else is loaded from YouTube
+/// before then
+fn fmt_youtube(builder: &Builder, id: &str) -> Option<String> {
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        warn!("Invalid YouTube video id '{id}'");
+        return None;
+    }
+    Some(
+        HtmlElement::new("div")
+            .with_class("video-embed")
+            .with_class("youtube")
+            .with_attr("data-youtube", id)
+            .with_child(
+                HtmlElement::new("button")
+                    .with_class("video-play")
+                    .with_attr("title", builder.config.tr("play_video"))
+                    .with_child(
+                        HtmlElement::new("img")
+                            .with_attr("src", format!("https://i.ytimg.com/vi/{id}/hqdefault.jpg"))
+                            .with_attr("alt", "")
+                            .with_attr("loading", "lazy")
+                            .with_attr("decoding", "async"),
+                    )
+                    .with_child(Html::feather("play-circle")),
+            )
+            .with_child(
+                HtmlElement::new("span")
+                    .with_class("video-notice")
+                    .with_text(builder.config.tr("video_notice")),
+            )
+            .gen_html(),
+    )
+}
+
+/// Format a keyboard shortcut like `Ctrl+Shift+S` as nested `<kbd>` elements,
+/// with `++` being the plus key
+fn fmt_kbd(shortcut: &str) -> String {
+    let mut keys = Vec::new();
+    let mut parts = shortcut.split('+').map(str::trim).peekable();
+    while let Some(part) = parts.next() {
+        if part.is_empty() {
+            if parts.peek() == Some(&"") {
+                parts.next();
+            }
+            keys.push("+");
+        } else {
+            keys.push(part);
+        }
+    }
+    if keys.len() == 1 {
+        return HtmlElement::new("kbd").with_text(keys[0]).gen_html();
+    }
+    HtmlElement::new("kbd")
+        .with_class("shortcut")
+        .with_children(
+            keys.into_iter()
+                .map(|key| Html::from(HtmlElement::new("kbd").with_text(key)))
+                .intersperse_with(|| HtmlText::new("+").into())
+                .collect(),
+        )
+        .gen_html()
+}
+
+/// Get the host of a link to another site, like `example.com`, without the
+/// port
+fn link_host(url: &str) -> Option<String> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .or_else(|| url.strip_prefix("//"))?;
+    let host = rest.split(['/', '?', '#']).next()?;
+    let host = host.rsplit('@').next()?;
+    Some(host.split(':').next()?.to_lowercase())
+}
+
+/// Check if a link leaves the docs, meaning it goes to another site than the
+/// one in `canonical-url` and isn't on one of the `external-links.allow`
+/// domains or their subdomains
+fn is_external_link(config: &Config, dest: &str) -> bool {
+    if !config.external_links.decorate {
+        return false;
+    }
+    let Some(host) = link_host(dest) else {
+        return false;
+    };
+    let matches = |domain: &str| {
+        let domain = domain.trim().to_lowercase();
+        host == domain || host.ends_with(&format!(".{domain}"))
+    };
+    !config
+        .canonical_url
+        .as_deref()
+        .and_then(link_host)
+        .is_some_and(|docs| matches(&docs))
+        && !config.external_links.allow.iter().any(|d| matches(d))
+}
+
 #[allow(clippy::ptr_arg)]
 pub fn fmt_markdown<F: Fn(UrlPath) -> Option<UrlPath>>(
     builder: &Builder,
     text: &str,
     url_fixer: Option<F>,
+) -> Html {
+    fmt_markdown_with_images(builder, text, url_fixer, None, false)
+}
+
+fn fmt_markdown_with_images<F: Fn(UrlPath) -> Option<UrlPath>>(
+    builder: &Builder,
+    text: &str,
+    url_fixer: Option<F>,
+    images: Option<ImageContext>,
+    shift_headings: bool,
 ) -> Html {
     // skip metadata
     let (text, meta) = parse_markdown_metadata(text);
@@ -244,11 +780,15 @@ pub fn fmt_markdown<F: Fn(UrlPath) -> Option<UrlPath>>(
     // provides for adding header links takes a &str and not an owned String, so
     // I have to do this to have Strings with the same lifetime as the input text
 
+    let style = page_style(builder, meta.as_ref());
+    let class = style.as_ref().and_then(|s| s.class.clone());
     let parser = MDStream::<5, F>::new(
         pulldown_cmark::Parser::new_ext(text, pulldown_cmark::Options::all()),
         url_fixer,
         builder,
-        meta,
+        style,
+        images,
+        shift_headings,
     );
 
     let mut content = String::new();
@@ -256,71 +796,587 @@ pub fn fmt_markdown<F: Fn(UrlPath) -> Option<UrlPath>>(
 
     HtmlElement::new("div")
         .with_class("text")
-        .with_child(Html::Raw(content))
+        .with_class_opt(class.as_deref())
+        .with_child(Html::Raw(RawHtml::trusted(content)))
         .into()
 }
 
+/// Format the site-wide announcement banner from the config, if there is one
+pub fn fmt_banner(builder: &Builder) -> Html {
+    let Some(ref banner) = builder.config.banner else {
+        return Html::empty();
+    };
+    HtmlElement::new("div")
+        .with_class("banner")
+        .with_attr("data-pagefind-ignore", "all")
+        .with_child(fmt_markdown(builder, &banner.content, None::<fn(_) -> _>))
+        .with_child_opt(banner.dismissible.then(|| {
+            // Dismissing is remembered per banner, so a new one shows up again
+            HtmlElement::new("button")
+                .with_class("banner-dismiss")
+                .with_attr("title", builder.config.tr("dismiss"))
+                .with_attr(
+                    "data-banner-id",
+                    banner.id.as_ref().unwrap_or(&banner.content),
+                )
+                .with_child(Html::feather("x"))
+        }))
+        .into()
+}
+
+/// Get the lines of a file selected by an include directive, which is either
+/// a line range like `10:30`, `10:` or `:30`, a single line like `10`, or the
+/// name of a region marked with `docs-start:name` and `docs-end:name`
+fn select_snippet(data: &str, spec: Option<&str>) -> Result<String, String> {
+    let lines = data.lines().collect::<Vec<_>>();
+    let is_marker = |line: &str| line.contains("docs-start:") || line.contains("docs-end:");
+
+    let selected = match spec {
+        None => lines,
+        Some(spec) if spec.chars().all(|c| c.is_ascii_digit() || c == ':') => {
+            let parse = |s: &str| s.parse::<usize>().ok();
+            let (start, end) = match spec.split_once(':') {
+                Some((start, end)) => (parse(start), parse(end)),
+                None => (parse(spec), parse(spec)),
+            };
+            let start = start.unwrap_or(1).max(1) - 1;
+            let end = end.unwrap_or(lines.len()).min(lines.len());
+            lines.get(start..end).unwrap_or_default().to_vec()
+        }
+        Some(region) => {
+            let start = lines
+                .iter()
+                .position(|l| l.trim_end().ends_with(&format!("docs-start:{region}")))
+                .ok_or(format!("Unable to find region '{region}'"))?;
+            let end = lines[start..]
+                .iter()
+                .position(|l| l.trim_end().ends_with(&format!("docs-end:{region}")))
+                .ok_or(format!("Region '{region}' is never ended"))?;
+            lines[start + 1..start + end].to_vec()
+        }
+    };
+
+    // Remove markers of other regions and any indentation shared by every line
+    let selected = selected
+        .into_iter()
+        .filter(|l| !is_marker(l))
+        .collect::<Vec<_>>();
+    let indent = selected
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    Ok(selected
+        .iter()
+        .map(|l| l.get(indent..).unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Replace `{{#include path}}` directives in a markdown document with the
+/// contents of the file, relative to the directory the document is in
+pub fn expand_includes(text: &str, dir: &Path) -> String {
+    let regex = Regex::new(r"\{\{#include\s+([^}:\s]+)(?::([^}\s]*))?\s*\}\}").unwrap();
+    regex
+        .replace_all(text, |caps: &Captures| {
+            let path = dir.join(&caps[1]);
+            fs::read_to_string(&path)
+                .map_err(|e| format!("Unable to read '{}': {e}", path.to_string_lossy()))
+                .and_then(|data| select_snippet(&data, caps.get(2).map(|m| m.as_str())))
+                .unwrap_or_else(|e| {
+                    warn!("Unable to include snippet: {e}");
+                    caps[0].to_string()
+                })
+        })
+        .into_owned()
+}
+
+/// Parse the attributes of a container shortcode, like
+/// `icon=book title="Getting started"`
+fn parse_container_attrs(attrs: &str) -> HashMap<String, String> {
+    let regex = Regex::new(r#"([\w-]+)=(?:"([^"]*)"|(\S+))"#).unwrap();
+    regex
+        .captures_iter(attrs)
+        .map(|caps| {
+            let value = caps.get(2).or(caps.get(3)).map_or("", |m| m.as_str());
+            (caps[1].to_owned(), value.to_owned())
+        })
+        .collect()
+}
+
+/// Format the start of a container shortcode, or `None` if it isn't one
+fn fmt_container_start(config: Arc<Config>, name: &str, attrs: &str) -> Option<String> {
+    let attrs = parse_container_attrs(attrs);
+    match name {
+        "cards" | "columns" => Some(format!("<div class=\"{name}\">")),
+        "column" => Some(String::from("<div class=\"column\">")),
+        "card" => {
+            let mut res = String::from("<div class=\"card\">");
+            if let Some(icon) = attrs.get("icon") {
+                res += &Html::feather(icon).gen_html();
+            }
+            if let Some(title) = attrs.get("title") {
+                let title = HtmlText::new(title).gen_html();
+                res += &match attrs.get("link") {
+                    Some(link) => {
+                        let link = match UrlPath::parse(link) {
+                            Ok(url) if link.starts_with('/') => url.to_absolute(config).to_string(),
+                            _ => link.clone(),
+                        };
+                        format!(
+                            "<p class=\"card-title\"><a href=\"{}\">{title}</a></p>",
+                            escape_attr(&link)
+                        )
+                    }
+                    None => format!("<p class=\"card-title\">{title}</p>"),
+                };
+            }
+            Some(res)
+        }
+        _ => None,
+    }
+}
+
+/// Replace container shortcodes in a tutorial with the HTML for them, for
+/// laying out landing pages. Containers start with `:::name attrs` on its own
+/// line and end with a line of as many colons, so containers nested in each
+/// other need fewer colons than their parents:
+///
+/// ```md
+/// ::::cards
+/// :::card icon=book title="Getting started" link=/tutorials/getting-started
+/// Set up your first project
+/// :::
+/// ::::
+/// ```
+///
+/// The contents are still markdown
+fn expand_containers(config: Arc<Config>, text: &str) -> String {
+    let start_regex = Regex::new(r"^(:{3,})\s*([\w-]+)\s*(.*)$").unwrap();
+    let end_regex = Regex::new(r"^:{3,}$").unwrap();
+    let mut open: Vec<usize> = Vec::new();
+    let mut in_code_block = false;
+    let mut res = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block {
+            res.push(line.to_owned());
+            continue;
+        }
+        if end_regex.is_match(trimmed) && open.last() == Some(&trimmed.len()) {
+            open.pop();
+            // Blank lines around the HTML keep the contents parsed as markdown
+            res.push(String::new());
+            res.push(String::from("</div>"));
+            res.push(String::new());
+            continue;
+        }
+        if let Some(caps) = start_regex.captures(trimmed) {
+            match fmt_container_start(config.clone(), &caps[2], &caps[3]) {
+                Some(html) => {
+                    open.push(caps[1].len());
+                    res.push(String::new());
+                    res.push(html);
+                    res.push(String::new());
+                }
+                None => {
+                    warn!("Unknown container shortcode '{}'", &caps[2]);
+                    res.push(line.to_owned());
+                }
+            }
+            continue;
+        }
+        res.push(line.to_owned());
+    }
+    if !open.is_empty() {
+        warn!("Container shortcode is never closed");
+        for _ in open {
+            res.push(String::from("\n</div>"));
+        }
+    }
+    res.join("\n")
+}
+
+/// Replace `[[Page]]` and `[[folder/page|label]]` wiki links in a tutorial
+/// with links to the tutorial or folder with that path or title. Links in
+/// code are left alone, since C++ attributes look the same
+fn expand_wiki_links(builder: &Builder, page: &UrlPath, text: &str) -> String {
+    let regex = Regex::new(r"\[\[([^\[\]|\n]+)(?:\|([^\[\]\n]+))?\]\]").unwrap();
+    let mut in_code_block = false;
+    text.lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_code_block = !in_code_block;
+            }
+            if in_code_block || !line.contains("[[") {
+                return line.to_owned();
+            }
+            regex
+                .replace_all(line, |caps: &Captures| {
+                    let link = caps.get(0).unwrap();
+                    // An odd number of backticks before means this is in
+                    // inline code
+                    if line[..link.start()].matches('`').count() % 2 == 1 {
+                        return link.as_str().to_owned();
+                    }
+                    let target = caps[1].trim();
+                    match builder.resolve_wiki_link(page, target) {
+                        Some((title, url)) => format!(
+                            "[{}]({url})",
+                            caps.get(2).map(|l| l.as_str().trim()).unwrap_or(&title)
+                        ),
+                        None => {
+                            report(
+                                WarningKind::DanglingWikiLink,
+                                ("", 0),
+                                format_args!(
+                                    "Wiki link {} in {page} doesn't match any tutorial",
+                                    link.as_str()
+                                ),
+                            );
+                            link.as_str().to_owned()
+                        }
+                    }
+                })
+                .into_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Get the C++ code blocks in a markdown document along with the byte offset
+/// they start at, skipping ones marked with `nocheck`
+pub fn extract_cpp_blocks(text: &str) -> Vec<(String, usize)> {
+    let mut res = Vec::new();
+    let mut current: Option<(String, usize)> = None;
+    for (event, range) in
+        pulldown_cmark::Parser::new_ext(text, pulldown_cmark::Options::all()).into_offset_iter()
+    {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                let mut words = info.split_whitespace();
+                if matches!(words.next(), Some("cpp" | "c++")) && !words.any(|w| w == "nocheck") {
+                    current = Some((String::new(), range.start));
+                }
+            }
+            Event::Text(t) => {
+                if let Some((ref mut code, _)) = current {
+                    code.push_str(&t);
+                }
+            }
+            Event::End(Tag::CodeBlock(_)) => res.extend(current.take()),
+            _ => {}
+        }
+    }
+    res
+}
+
+/// Get the content of a markdown document without its metadata
+pub fn strip_metadata_from_md(doc: &str) -> &str {
+    parse_markdown_metadata(doc).0
+}
+
+/// Get the metadata of a markdown document, with its title taken from its
+/// first heading if the metadata doesn't have one. Invalid metadata is
+/// reported as a warning about the file and skipped
 #[allow(clippy::ptr_arg)]
-pub fn extract_metadata_from_md(text: &String, default_title: Option<String>) -> Option<Metadata> {
-    let (text, metadata) = parse_markdown_metadata(text);
+pub fn extract_metadata_from_md(
+    text: &String,
+    default_title: Option<String>,
+    file: &Path,
+) -> Option<Metadata> {
+    let (text, metadata) = split_markdown_metadata(text);
+    let metadata = metadata.unwrap_or_else(|e| {
+        report(
+            WarningKind::InvalidMetadata,
+            (&file.to_string_lossy(), e.line),
+            format_args!("Invalid metadata, skipping it: {}", e.message),
+        );
+        None
+    });
 
     // if the metadata provided the title, no need to parse the doc for it
-    if metadata.is_some() && metadata.as_ref().unwrap().title.is_some() {
+    if metadata.as_ref().is_some_and(|m| m.title.is_some()) {
         return metadata;
     }
 
-    // otherwise parse doc and use first header as title
+    // otherwise use the first header of the doc as the title
+    let title = first_heading(text).or(default_title);
+    match metadata {
+        Some(mut metadata) => {
+            metadata.title = title;
+            Some(metadata)
+        }
+        None => title.map(Metadata::new_with_title),
+    }
+}
+
+/// Get the text of the heading a markdown document starts with, if any
+fn first_heading(text: &str) -> Option<String> {
     let mut parser = pulldown_cmark::Parser::new_ext(text, pulldown_cmark::Options::all());
 
-    let name = parser.next()?;
-    let Event::Start(tag) = name else { return None };
-    let Tag::Heading(_, _, _) = tag else {
+    let Some(Event::Start(Tag::Heading(_, _, _))) = parser.next() else {
         return None;
     };
 
     let mut res = String::new();
+    for ev in parser {
+        match ev {
+            Event::End(Tag::Heading(_, _, _)) => break,
+            Event::Text(text) => res.push_str(&text),
+            _ => {}
+        }
+    }
+    (!res.is_empty()).then_some(res)
+}
 
-    while match parser.next() {
-        Some(ev) => match ev {
-            Event::End(tag) => !matches!(tag, Tag::Heading(_, _, _)),
-            Event::Text(text) => {
-                res.push_str(&text);
-                true
-            }
-            _ => true,
-        },
-        None => false,
-    } {}
-
-    // if some metadata was found, set the title
-    if let Some(mut metadata) = metadata {
-        metadata.title = (!res.is_empty()).then_some(res).or(default_title);
-        Some(metadata)
-    }
-    // otherwise only return Some if a title was found
-    else if res.is_empty() {
-        default_title.map(Metadata::new_with_title)
+pub fn fmt_edit_link(url: Option<String>, label: &str) -> Html {
+    if let Some(url) = url {
+        HtmlElement::new("a")
+            .with_class("edit-link")
+            .with_attr("href", url)
+            .with_child(Html::feather("edit"))
+            .with_text(label)
+            .into()
     } else {
-        Some(Metadata::new_with_title(res))
+        Html::empty()
+    }
+}
+
+/// Links to the same tutorial page in the other languages it's translated to
+pub fn fmt_languages(builder: &Builder, url: &UrlPath, label: &str) -> Html {
+    let translations = builder.tutorial_translations(url);
+    if translations.len() < 2 {
+        return Html::empty();
     }
+    HtmlElement::new("nav")
+        .with_class("languages")
+        .with_attr("aria-label", label)
+        .with_child(Html::feather("globe"))
+        .with_children(
+            translations
+                .into_iter()
+                .map(|(name, target)| {
+                    HtmlElement::new("a")
+                        .with_text(name)
+                        .with_attr("href", target.to_absolute(builder.config.clone()))
+                        .with_attr_opt("aria-current", (target == *url).then_some("page"))
+                        .into()
+                })
+                .collect(),
+        )
+        .into()
+}
+
+/// Show which part of its series a tutorial is, like "Part 2 of 5 in Modding
+/// basics"
+fn fmt_series_label(builder: &Builder, url: &UrlPath) -> Html {
+    let Some((name, pos, parts)) = builder.tutorial_series(url) else {
+        return Html::empty();
+    };
+    HtmlElement::new("p")
+        .with_class("series-label")
+        .with_child(Html::feather("layers"))
+        .with_text(
+            builder
+                .config
+                .tr_in(builder.config.locale_of(url), "series_part")
+                .replace("{part}", &(pos + 1).to_string())
+                .replace("{count}", &parts.len().to_string())
+                .replace("{series}", &name),
+        )
+        .into()
+}
+
+/// Links to the previous and next parts of the series a tutorial is in
+pub fn fmt_series_nav(builder: &Builder, url: &UrlPath) -> Html {
+    let Some((_, pos, parts)) = builder.tutorial_series(url) else {
+        return Html::empty();
+    };
+    let locale = builder.config.locale_of(url);
+    let link = |part: Option<&SeriesPart>, class: &str, key: &str, icon: &str| {
+        part.map(|part| {
+            let label = HtmlElement::new("span")
+                .with_class("series-direction")
+                .with_text(builder.config.tr_in(locale, key));
+            let title = HtmlElement::new("span").with_text(&part.title);
+            HtmlElement::new("a")
+                .with_class(class)
+                .with_attr("href", part.url.to_absolute(builder.config.clone()))
+                .with_child(Html::feather(icon))
+                .with_child(HtmlElement::new("span").with_child(label).with_child(title))
+        })
+    };
+    HtmlElement::new("nav")
+        .with_class("series-nav")
+        .with_child_opt(link(
+            pos.checked_sub(1).and_then(|i| parts.get(i)),
+            "prev",
+            "previous_part",
+            "arrow-left",
+        ))
+        .with_child_opt(link(parts.get(pos + 1), "next", "next_part", "arrow-right"))
+        .into()
 }
 
 pub fn output_tutorial<'e, T: Entry<'e>>(
     entry: &T,
     builder: &Builder,
     content: &str,
+    source_dir: PathBuf,
+    metadata: Option<&Metadata>,
     links: Html,
+    edit_url: Option<String>,
 ) -> Vec<(&'static str, Html)> {
+    let locale = builder.config.locale_of(&entry.url());
+    let content = fmt_markdown_with_images(
+        builder,
+        &expand_wiki_links(
+            builder,
+            &entry.url(),
+            &expand_containers(builder.config.clone(), content),
+        ),
+        Some(|url: UrlPath| Some(url.remove_extension(".md"))),
+        Some(ImageContext {
+            source_dir,
+            page_url: entry.url(),
+        }),
+        metadata.and_then(|m| m.shift_headings).unwrap_or(
+            builder
+                .config
+                .tutorials
+                .as_ref()
+                .is_some_and(|t| t.shift_headings),
+        ),
+    );
     vec![
         ("title", HtmlText::new(entry.name()).into()),
         (
             "content",
-            fmt_markdown(
-                builder,
-                content,
-                Some(|url: UrlPath| Some(url.remove_extension(".md"))),
+            // Keep unlisted pages out of the Pagefind index
+            if metadata.is_some_and(|m| m.unlisted) {
+                HtmlElement::new("div")
+                    .with_attr("data-pagefind-ignore", "all")
+                    .with_child(content)
+                    .into()
+            } else {
+                content
+            },
+        ),
+        (
+            "tags",
+            fmt_tags(
+                builder.config.clone(),
+                metadata.map(|m| m.tags.as_slice()).unwrap_or_default(),
             ),
         ),
+        ("series", fmt_series_label(builder, &entry.url())),
         ("links", links),
+        (
+            "languages",
+            fmt_languages(
+                builder,
+                &entry.url(),
+                &builder.config.tr_in(locale, "languages"),
+            ),
+        ),
+        (
+            "edit_link",
+            fmt_edit_link(edit_url, &builder.config.tr_in(locale, "edit_this_page")),
+        ),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract(doc: &str) -> Option<Metadata> {
+        extract_metadata_from_md(
+            &doc.to_string(),
+            Some(String::from("default")),
+            Path::new("test.md"),
+        )
+    }
+
+    #[test]
+    fn reports_invalid_yaml() {
+        let (rest, metadata) = split_markdown_metadata("---\ntitle: [oops\n---\nText\n");
+        assert_eq!(rest, "\nText\n");
+        assert!(metadata.is_err());
+    }
+
+    #[test]
+    fn reports_invalid_toml() {
+        let (rest, metadata) = split_markdown_metadata("\n+++\ntitle = \n+++\nText\n");
+        assert_eq!(rest, "\nText\n");
+        assert!(metadata.is_err_and(|e| e.line == 3));
+    }
+
+    #[test]
+    fn parses_toml_metadata() {
+        let (rest, metadata) = split_markdown_metadata(
+            "+++\ntitle = \"Hello\"\norder = 3\ntags = [\"a\", \"b\"]\n+++\nText\n",
+        );
+        let metadata = metadata.ok().flatten().unwrap();
+        assert_eq!(rest, "\nText\n");
+        assert_eq!(metadata.title.as_deref(), Some("Hello"));
+        assert_eq!(metadata.order, Some(3));
+        assert_eq!(metadata.tags, ["a", "b"]);
+    }
+
+    #[test]
+    fn ends_metadata_on_its_own_line() {
+        let (rest, metadata) =
+            split_markdown_metadata("+++\ntitle = \"a +++ b\"\n+++\nText\n\n+++\n");
+        let metadata = metadata.ok().flatten().unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("a +++ b"));
+        assert_eq!(rest, "\nText\n\n+++\n");
+
+        let (rest, metadata) = split_markdown_metadata("---\ntitle: a --- b\n---  \nText\n");
+        let metadata = metadata.ok().flatten().unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("a --- b"));
+        assert_eq!(rest, "  \nText\n");
+    }
+
+    #[test]
+    fn ignores_horizontal_rules_without_metadata() {
+        let doc = "Text\n\n---\n\nMore text\n";
+        assert_eq!(strip_metadata_from_md(doc), doc);
+
+        let doc = "+++ not metadata\ntitle = \"x\"\n+++\n";
+        let (rest, metadata) = split_markdown_metadata(doc);
+        assert_eq!(rest, doc);
+        assert!(metadata.is_ok_and(|m| m.is_none()));
+    }
+
+    #[test]
+    fn falls_back_to_default_title_on_invalid_metadata() {
+        let metadata = extract("---\ntitle: [oops\n---\nSome text\n").unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("default"));
+
+        let metadata = extract("+++\ntitle = \n+++\nSome text\n").unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("default"));
+    }
+
+    #[test]
+    fn takes_title_from_heading_on_invalid_metadata() {
+        let metadata = extract("---\ntitle: [oops\n---\n# Heading\n").unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("Heading"));
+
+        let metadata = extract("+++\ntitle = \n+++\n# Heading\n").unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("Heading"));
+    }
+
+    #[test]
+    fn keeps_metadata_without_title_or_heading() {
+        let metadata = extract("---\norder: 2\n---\nSome text\n").unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("default"));
+        assert_eq!(metadata.order, Some(2));
+    }
+}
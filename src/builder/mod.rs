@@ -2,11 +2,23 @@
 pub mod builder;
 pub mod class;
 pub mod comment;
+pub mod feed;
 pub mod files;
 pub mod function;
+pub mod group;
+pub mod images;
+pub mod manifest;
 pub mod markdown;
 pub mod namespace;
+pub mod operators;
+pub mod search;
 pub mod shared;
+pub mod since;
+pub mod source;
 pub mod struct_;
+pub mod tags;
 pub mod traits;
 pub mod tutorial;
+pub mod undocumented;
+pub mod usage;
+pub mod variable;
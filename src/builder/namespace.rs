@@ -1,23 +1,57 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
-use clang::{Entity, EntityKind};
-use log::{debug, warn};
+use clang::{Accessibility, Entity, EntityKind};
+use log::debug;
 
-use crate::{config::Config, url::UrlPath};
+use crate::{
+    config::Config,
+    diagnostics::{WarningKind, report_for},
+    html::{Html, HtmlText},
+    url::UrlPath,
+};
 
 use super::{
     builder::Builder,
     class::Class,
+    comment::{GroupDef, JSDocComment, parse_group_commands},
     function::Function,
+    shared::{entity_breadcrumbs, fmt_entity_summary, fmt_enum, fmt_reexport, fmt_section},
     struct_::Struct,
-    traits::{ASTEntry, BuildResult, EntityMethods, Entry, NavItem},
+    traits::{ASTEntry, BuildResult, EntityMethods, Entry, NavItem, OutputEntry},
+    variable::Variable,
 };
 
+/// Get the documented entities a using-declaration brings into scope, which is
+/// every overload for functions
+fn using_targets<'e>(using: &Entity<'e>) -> Vec<Entity<'e>> {
+    let Some(target) = using.get_reference() else {
+        return Vec::new();
+    };
+    let targets = if target.get_kind() == EntityKind::OverloadedDeclRef {
+        target.get_overloaded_declarations().unwrap_or_default()
+    } else {
+        vec![target]
+    };
+    targets
+        .into_iter()
+        .map(|t| t.get_definition().unwrap_or(t))
+        .filter(|t| {
+            !matches!(CppItemKind::from(t), Some(CppItemKind::Namespace) | None)
+                && !t.full_name().first().is_some_and(|n| n == "std")
+        })
+        .collect()
+}
+
+#[derive(PartialEq)]
 pub enum CppItemKind {
     Namespace,
     Class,
     Struct,
     Function,
+    Variable,
 }
 
 impl CppItemKind {
@@ -29,17 +63,35 @@ impl CppItemKind {
             | EntityKind::ClassTemplatePartialSpecialization => Some(Self::Class),
             EntityKind::FunctionDecl | EntityKind::FunctionTemplate => Some(Self::Function),
             EntityKind::Namespace => Some(Self::Namespace),
+            // Only namespace-scope variables get their own pages
+            EntityKind::VarDecl
+                if entity.get_semantic_parent().is_some_and(|p| {
+                    matches!(
+                        p.get_kind(),
+                        EntityKind::Namespace | EntityKind::TranslationUnit
+                    )
+                }) =>
+            {
+                Some(Self::Variable)
+            }
             _ => None,
         }
     }
 
-    pub fn docs_category(&self) -> UrlPath {
-        UrlPath::part(match self {
+    /// Get the name of the category of API docs this kind is in, which is
+    /// used to look up its URL and display name in the config
+    pub fn category(&self) -> &'static str {
+        match self {
             Self::Namespace => "namespaces",
             Self::Class => "classes",
-            Self::Struct => "classes",
+            Self::Struct => "structs",
             Self::Function => "functions",
-        })
+            Self::Variable => "variables",
+        }
+    }
+
+    pub fn docs_category(&self, config: &Config) -> UrlPath {
+        config.category_url(self.category())
     }
 }
 
@@ -48,9 +100,49 @@ pub enum CppItem<'e> {
     Class(Class<'e>),
     Struct(Struct<'e>),
     Function(Function<'e>),
+    Variable(Variable<'e>),
+}
+
+/// Check if a child of a class is a public nested class or struct, which
+/// gets its own page
+pub fn is_nested_class(child: &Entity, config: Arc<Config>) -> bool {
+    matches!(
+        child.get_kind(),
+        EntityKind::ClassDecl | EntityKind::StructDecl | EntityKind::ClassTemplate
+    ) && child.is_definition()
+        && child
+            .get_name()
+            .is_some_and(|name| !name.contains("unnamed ") && !name.starts_with('('))
+        && !child.is_specialization()
+        && child.get_accessibility() == Some(Accessibility::Public)
+        && !child.is_hidden(config)
+}
+
+/// Load the nested classes and structs of a class
+pub fn load_nested<'e>(entity: &Entity<'e>, config: Arc<Config>) -> Vec<CppItem<'e>> {
+    entity
+        .get_children()
+        .into_iter()
+        .filter(|child| is_nested_class(child, config.clone()))
+        .map(|child| match CppItemKind::from(&child) {
+            Some(CppItemKind::Struct) => CppItem::Struct(Struct::new(child, config.clone())),
+            _ => CppItem::Class(Class::new(child, config.clone())),
+        })
+        .collect()
 }
 
 impl<'e> CppItem<'e> {
+    /// Get the entries inside this one, which are the contents of namespaces
+    /// and the nested classes of classes
+    pub fn children(&self) -> Vec<&CppItem<'e>> {
+        match self {
+            CppItem::Namespace(ns) => ns.entries.values().collect(),
+            CppItem::Class(cls) => cls.nested().iter().collect(),
+            CppItem::Struct(st) => st.nested().iter().collect(),
+            CppItem::Function(_) | CppItem::Variable(_) => Vec::new(),
+        }
+    }
+
     fn get(
         &'e self,
         matcher: &dyn Fn(&dyn ASTEntry<'e>) -> bool,
@@ -61,9 +153,6 @@ impl<'e> CppItem<'e> {
                 if matcher(ns) {
                     out.push(ns);
                 }
-                for entry in ns.entries.values() {
-                    entry.get(&matcher, out);
-                }
             }
             CppItem::Class(cls) => {
                 if matcher(cls) {
@@ -80,8 +169,62 @@ impl<'e> CppItem<'e> {
                     out.push(fun);
                 }
             }
+            CppItem::Variable(var) => {
+                if matcher(var) {
+                    out.push(var);
+                }
+            }
+        }
+        for entry in self.children() {
+            entry.get(&matcher, out);
         }
     }
+
+    fn collect_type_urls(&self, config: Arc<Config>, out: &mut HashMap<String, UrlPath>) {
+        match self {
+            CppItem::Namespace(ns) => return ns.collect_type_urls(out),
+            CppItem::Class(_) | CppItem::Struct(_) => {
+                out.insert(self.entity().full_name().join("::"), self.url());
+                for spec in self.specializations() {
+                    let mut name = spec.full_name();
+                    if let Some(last) = name.last_mut() {
+                        *last = spec.get_display_name().unwrap_or_default();
+                    }
+                    if let Some(url) = spec.rel_docs_url(config.clone()) {
+                        out.insert(name.join("::"), url);
+                    }
+                }
+            }
+            CppItem::Function(_) | CppItem::Variable(_) => {}
+        }
+        for entry in self.children() {
+            entry.collect_type_urls(config.clone(), out);
+        }
+    }
+
+    fn collect_docs_urls(&self, config: Arc<Config>, out: &mut Vec<UrlPath>) {
+        let url = match self {
+            // Overloads have their index appended, which can collide too
+            CppItem::Function(fun) => Some(fun.url()),
+            _ => self.entity().rel_docs_url(config.clone()),
+        };
+        if let Some(url) = url {
+            out.push(url);
+        }
+        for entry in self.children() {
+            entry.collect_docs_urls(config.clone(), out);
+        }
+    }
+
+    /// Count this entry and the ones inside it, not including namespaces
+    fn count(&self) -> usize {
+        let own = if matches!(self, CppItem::Namespace(_)) {
+            0
+        } else {
+            1
+        };
+        own + self.children().iter().map(|e| e.count()).sum::<usize>()
+    }
 }
 
 impl<'e> Entry<'e> for CppItem<'e> {
@@ -91,6 +234,7 @@ impl<'e> Entry<'e> for CppItem<'e> {
             CppItem::Class(cs) => cs.name(),
             CppItem::Struct(st) => st.name(),
             CppItem::Function(st) => st.name(),
+            CppItem::Variable(st) => st.name(),
         }
     }
 
@@ -100,6 +244,7 @@ impl<'e> Entry<'e> for CppItem<'e> {
             CppItem::Class(cs) => cs.url(),
             CppItem::Struct(st) => st.url(),
             CppItem::Function(st) => st.url(),
+            CppItem::Variable(st) => st.url(),
         }
     }
 
@@ -109,6 +254,7 @@ impl<'e> Entry<'e> for CppItem<'e> {
             CppItem::Class(cs) => cs.build(builder),
             CppItem::Struct(st) => st.build(builder),
             CppItem::Function(st) => st.build(builder),
+            CppItem::Variable(st) => st.build(builder),
         }
     }
 
@@ -118,6 +264,7 @@ impl<'e> Entry<'e> for CppItem<'e> {
             CppItem::Class(cs) => cs.nav(),
             CppItem::Struct(st) => st.nav(),
             CppItem::Function(st) => st.nav(),
+            CppItem::Variable(st) => st.nav(),
         }
     }
 }
@@ -129,6 +276,7 @@ impl<'e> ASTEntry<'e> for CppItem<'e> {
             CppItem::Function(c) => c.entity(),
             CppItem::Namespace(c) => c.entity(),
             CppItem::Struct(c) => c.entity(),
+            CppItem::Variable(c) => c.entity(),
         }
     }
 
@@ -138,22 +286,48 @@ impl<'e> ASTEntry<'e> for CppItem<'e> {
             CppItem::Class(cs) => cs.category(),
             CppItem::Struct(st) => st.category(),
             CppItem::Function(st) => st.category(),
+            CppItem::Variable(st) => st.category(),
+        }
+    }
+
+    fn specializations(&self) -> &[Entity<'e>] {
+        match self {
+            CppItem::Class(cs) => cs.specializations(),
+            CppItem::Struct(st) => st.specializations(),
+            _ => &[],
         }
     }
 }
 
 pub struct Namespace<'e> {
     entity: Entity<'e>,
+    config: Arc<Config>,
     is_root: bool,
     pub entries: HashMap<String, CppItem<'e>>,
+    /// Enums in this namespace, which are only listed on its page
+    enums: Vec<Entity<'e>>,
+    /// Type aliases in this namespace, which are only listed on its page
+    aliases: Vec<Entity<'e>>,
+    /// Entities brought into this namespace with using-declarations, which
+    /// are listed on its page and in the navbar but link to where they're
+    /// documented
+    reexports: Vec<Entity<'e>>,
+    /// Class template specializations found in this namespace, which are
+    /// moved to their primary template once everything has been loaded
+    specializations: Vec<Entity<'e>>,
 }
 
 impl<'e> Namespace<'e> {
     pub fn new(entity: Entity<'e>, config: Arc<Config>) -> Self {
         let mut ret = Self {
             entity,
+            config: config.clone(),
             is_root: false,
             entries: HashMap::new(),
+            enums: Vec::new(),
+            aliases: Vec::new(),
+            reexports: Vec::new(),
+            specializations: Vec::new(),
         };
         ret.load_entries(config);
         ret
@@ -162,16 +336,78 @@ impl<'e> Namespace<'e> {
     pub fn new_root(entity: Entity<'e>, config: Arc<Config>) -> Self {
         let mut ret = Self {
             entity,
+            config: config.clone(),
             is_root: true,
             entries: HashMap::new(),
+            enums: Vec::new(),
+            aliases: Vec::new(),
+            reexports: Vec::new(),
+            specializations: Vec::new(),
         };
         ret.load_entries(config);
+        for spec in ret.take_specializations() {
+            if !ret.attach_specialization(spec) {
+                debug!(
+                    "skipping specialization {} of undocumented template",
+                    spec.get_display_name().unwrap_or_default()
+                );
+            }
+        }
         ret.clean_empty_namespaces();
         ret
     }
 
+    /// Remove the specializations found in this namespace and all of its
+    /// nested namespaces
+    fn take_specializations(&mut self) -> Vec<Entity<'e>> {
+        let mut specs = std::mem::take(&mut self.specializations);
+        for entry in self.entries.values_mut() {
+            if let CppItem::Namespace(ns) = entry {
+                specs.extend(ns.take_specializations());
+            }
+        }
+        specs
+    }
+
+    /// Add a specialization to the page of its primary template, returning
+    /// false if that template isn't documented
+    fn attach_specialization(&mut self, spec: Entity<'e>) -> bool {
+        let Some(usr) = spec.get_template().and_then(|t| t.get_usr()) else {
+            return false;
+        };
+        for entry in self.entries.values_mut() {
+            match entry {
+                CppItem::Namespace(ns) => {
+                    if ns.attach_specialization(spec) {
+                        return true;
+                    }
+                }
+                CppItem::Class(cls) if cls.entity().get_usr().as_ref() == Some(&usr) => {
+                    cls.add_specialization(spec);
+                    return true;
+                }
+                CppItem::Struct(st) if st.entity().get_usr().as_ref() == Some(&usr) => {
+                    st.add_specialization(spec);
+                    return true;
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
     fn merge_with_namespace(&mut self, other: Namespace<'e>) {
-        assert_eq!(self.entity.get_name(), other.entity.get_name());
+        assert_eq!(self.name(), other.name());
+        self.merge_contents(other);
+    }
+
+    /// Move everything in another namespace into this one, merging the
+    /// namespaces they both have
+    fn merge_contents(&mut self, other: Namespace<'e>) {
+        self.enums.extend(other.enums);
+        self.aliases.extend(other.aliases);
+        self.reexports.extend(other.reexports);
+        self.specializations.extend(other.specializations);
         for (name, other_entry) in other.entries {
             if matches!(other_entry, CppItem::Namespace(_))
                 && let Some(CppItem::Namespace(ns)) = self.entries.get_mut(&name)
@@ -181,9 +417,69 @@ impl<'e> Namespace<'e> {
                 };
                 ns.merge_with_namespace(entry_ns);
             } else {
-                self.entries.insert(name, other_entry);
+                let (key, _) = self.free_key(name);
+                self.entries.insert(key, other_entry);
+            }
+        }
+    }
+
+    /// Add the entities from the docs for another platform that aren't
+    /// declared on the platforms merged so far
+    pub fn merge_platform(&mut self, other: Namespace<'e>) {
+        let has_usr = |list: &[Entity<'e>], entity: &Entity<'e>| {
+            entity
+                .get_usr()
+                .is_some_and(|usr| list.iter().any(|e| e.get_usr().as_ref() == Some(&usr)))
+        };
+        for entity in other.enums {
+            if !has_usr(&self.enums, &entity) {
+                self.enums.push(entity);
             }
         }
+        for entity in other.aliases {
+            if !has_usr(&self.aliases, &entity) {
+                self.aliases.push(entity);
+            }
+        }
+        for entity in other.reexports {
+            if !has_usr(&self.reexports, &entity) {
+                self.reexports.push(entity);
+            }
+        }
+        for (name, mut entry) in other.entries {
+            if let CppItem::Namespace(other_ns) = entry {
+                if let Some(CppItem::Namespace(ns)) = self.entries.get_mut(&name) {
+                    ns.merge_platform(other_ns);
+                } else {
+                    self.entries.insert(name, CppItem::Namespace(other_ns));
+                }
+                continue;
+            }
+            let usr = entry.entity().get_usr();
+            if usr.is_some() && self.entries.values().any(|e| e.entity().get_usr() == usr) {
+                continue;
+            }
+            // Overloads from different platforms need their own keys too
+            let (key, count) = self.free_key(entry.name());
+            if count > 0
+                && let CppItem::Function(fun) = &mut entry
+            {
+                fun.add_overload_index(count);
+            }
+            self.entries.insert(key, entry);
+        }
+    }
+
+    /// Find a key for an entry that doesn't replace another entry with the
+    /// same name, along with how many times it had to be suffixed
+    fn free_key(&self, base: String) -> (String, usize) {
+        let mut key = base.clone();
+        let mut count = 0;
+        while self.entries.contains_key(&key) {
+            count += 1;
+            key = format!("{base}{count}");
+        }
+        (key, count)
     }
 
     fn clean_empty_namespaces(&mut self) {
@@ -192,15 +488,23 @@ impl<'e> Namespace<'e> {
             let mut remove = false;
             if let Some(CppItem::Namespace(ns)) = self.entries.get_mut(&key) {
                 ns.clean_empty_namespaces();
-                if ns.entries.is_empty() {
+                if ns.entries.is_empty()
+                    && ns.enums.is_empty()
+                    && ns.aliases.is_empty()
+                    && ns.reexports.is_empty()
+                {
                     remove = true;
                 }
             }
             if remove {
                 if let Some(entry) = self.entries.get(&key) {
-                    warn!(
-                        "Removing empty namespace {}",
-                        entry.entity().full_name().join("::")
+                    report_for(
+                        WarningKind::EmptyNamespace,
+                        entry.entity(),
+                        format_args!(
+                            "Removing empty namespace {}",
+                            entry.entity().full_name().join("::")
+                        ),
                     );
                 }
                 self.entries.remove(&key);
@@ -237,7 +541,32 @@ impl<'e> Namespace<'e> {
                 .next()
                 .is_some_and(|c| "()<>[]".contains(c))
             {
-                warn!("{full_child_name:?} is probably an internal identifier, skipping");
+                report_for(
+                    WarningKind::InternalIdentifier,
+                    child,
+                    format_args!(
+                        "{full_child_name:?} is probably an internal identifier, skipping"
+                    ),
+                );
+                continue;
+            }
+
+            if child.is_hidden(config.clone()) {
+                debug!("skipping hidden {full_child_name}");
+                continue;
+            }
+
+            if matches!(
+                CppItemKind::from(child),
+                Some(
+                    CppItemKind::Class
+                        | CppItemKind::Struct
+                        | CppItemKind::Function
+                        | CppItemKind::Variable
+                )
+            ) && !child.is_exported(config.clone())
+            {
+                debug!("skipping unexported {full_child_name}");
                 continue;
             }
 
@@ -278,53 +607,234 @@ impl<'e> Namespace<'e> {
                 }
             }
 
+            if child.get_kind() == EntityKind::UsingDeclaration {
+                if config.namespaces.merge_using {
+                    for target in using_targets(child) {
+                        if !self
+                            .reexports
+                            .iter()
+                            .any(|e| e.full_name() == target.full_name())
+                        {
+                            self.reexports.push(target);
+                        }
+                    }
+                }
+                continue;
+            }
+
+            match child.get_kind() {
+                EntityKind::EnumDecl if child.is_definition() => self.enums.push(*child),
+                EntityKind::TypeAliasDecl
+                | EntityKind::TypeAliasTemplateDecl
+                | EntityKind::TypedefDecl => self.aliases.push(*child),
+                _ => {}
+            }
+
             if let Some(kind) = CppItemKind::from(child) {
                 match kind {
                     CppItemKind::Namespace => {
                         let entry = Namespace::new(*child, config.clone());
+                        // Collapsed namespaces have the same name as their
+                        // parent, and their contents are shown in it
+                        let own_name = if self.is_root {
+                            Vec::new()
+                        } else {
+                            self.entity.full_name()
+                        };
+                        if child.full_name() == own_name {
+                            self.merge_contents(entry);
+                        }
                         // if we have some namespace with the same name
-                        if let Some(CppItem::Namespace(ns)) = self.entries.get_mut(&entry.name()) {
+                        else if let Some(CppItem::Namespace(ns)) =
+                            self.entries.get_mut(&entry.name())
+                        {
                             ns.merge_with_namespace(entry);
                         } else {
                             // Insert new namespace
-                            self.entries.insert(entry.name(), CppItem::Namespace(entry));
+                            let (key, _) = self.free_key(entry.name());
+                            self.entries.insert(key, CppItem::Namespace(entry));
                         }
                     }
 
+                    CppItemKind::Struct | CppItemKind::Class
+                        if child.is_definition() && child.is_specialization() =>
+                    {
+                        self.specializations.push(*child);
+                    }
+
                     CppItemKind::Struct => {
                         if child.is_definition() {
-                            let entry = Struct::new(*child);
-                            self.entries.insert(entry.name(), CppItem::Struct(entry));
+                            let entry = Struct::new(*child, config.clone());
+                            let (key, _) = self.free_key(entry.name());
+                            self.entries.insert(key, CppItem::Struct(entry));
                         }
                     }
 
                     CppItemKind::Class => {
                         if child.is_definition() {
-                            let entry = Class::new(*child);
-                            self.entries.insert(entry.name(), CppItem::Class(entry));
+                            let entry = Class::new(*child, config.clone());
+                            let (key, _) = self.free_key(entry.name());
+                            self.entries.insert(key, CppItem::Class(entry));
                         }
                     }
 
                     CppItemKind::Function => {
-                        let mut entry = Function::new(*child);
-                        let entry_name = entry.name();
-                        let mut final_entry_name = entry_name.clone();
+                        let mut entry = Function::new(*child, config.clone());
                         // if we have some function overloads with the same name
-                        let mut count = 0;
-                        while let Some(CppItem::Function(_)) = self.entries.get(&final_entry_name) {
-                            count += 1;
-                            final_entry_name = format!("{entry_name}{count}");
-                        }
+                        let (key, count) = self.free_key(entry.name());
                         if count > 0 {
                             entry.add_overload_index(count);
                         }
-                        self.entries.insert(final_entry_name, CppItem::Function(entry));
+                        self.entries.insert(key, CppItem::Function(entry));
+                    }
+
+                    CppItemKind::Variable => {
+                        // Only constants are part of the API, mutable
+                        // globals are usually implementation details
+                        if child.is_constant_variable() {
+                            let entry = Variable::new(*child, config.clone());
+                            let (key, _) = self.free_key(entry.name());
+                            self.entries.insert(key, CppItem::Variable(entry));
+                        }
                     }
                 }
             }
         }
     }
 
+    pub fn collect_type_urls(&self, out: &mut HashMap<String, UrlPath>) {
+        for entry in self.entries.values() {
+            entry.collect_type_urls(self.config.clone(), out);
+        }
+    }
+
+    /// Collect the docs URLs of this namespace's entities and its children's,
+    /// including the indices of overloaded functions
+    pub fn collect_docs_urls(&self, out: &mut Vec<UrlPath>) {
+        for entry in self.entries.values() {
+            entry.collect_docs_urls(self.config.clone(), out);
+        }
+    }
+
+    /// Count all the documented entities in this namespace and its children,
+    /// not including the namespaces themselves
+    pub fn count(&self) -> usize {
+        self.entries.values().map(|entry| entry.count()).sum()
+    }
+
+    /// Find the groups defined in doc comments and the entities added to them
+    pub fn collect_groups(
+        &self,
+        defs: &mut Vec<GroupDef>,
+        members: &mut HashMap<String, Vec<Entity<'e>>>,
+    ) {
+        for entry in self.entries.values() {
+            if let CppItem::Namespace(ns) = entry {
+                ns.collect_groups(defs, members);
+            }
+            let Some(comment) = entry.entity().get_comment() else {
+                continue;
+            };
+            let (found_defs, groups) = parse_group_commands(&comment);
+            defs.extend(found_defs);
+            if !matches!(entry, CppItem::Namespace(_)) {
+                for group in groups {
+                    members.entry(group).or_default().push(*entry.entity());
+                }
+            }
+        }
+    }
+
+    /// Format the entries of some kind as a section of one-line summaries
+    fn fmt_entries(&self, kind: CppItemKind, builder: &'e Builder<'e>) -> Html {
+        let title = builder.config.category_name(kind.category());
+        let mut entries = self
+            .entries
+            .values()
+            .filter(|e| CppItemKind::from(e.entity()).is_some_and(|k| k == kind))
+            .map(|e| (e.name(), e.entity()))
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        fmt_section(
+            &title,
+            entries
+                .into_iter()
+                .map(|(_, entity)| fmt_entity_summary(entity, builder))
+                .collect(),
+        )
+    }
+
+    /// Get the navbar items of the entities in this namespace and the
+    /// namespaces in it that match a filter, leaving out namespaces without
+    /// any
+    fn nav_filtered(&self, filter: &dyn Fn(&Entity<'e>) -> bool) -> Vec<NavItem> {
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|p| (!matches!(p.1, CppItem::Namespace(_)), p.0));
+        let mut items = Vec::new();
+        for (_, entry) in entries {
+            if let CppItem::Namespace(ns) = entry {
+                let ns_items = ns.nav_filtered(filter);
+                if !ns_items.is_empty() {
+                    items.push(NavItem::new_dir(&ns.name(), ns_items, None));
+                }
+            } else if filter(entry.entity()) {
+                items.push(entry.nav());
+            }
+        }
+        items.extend(self.reexport_nav(filter));
+        items
+    }
+
+    /// Get links to the pages of the entities brought into this namespace
+    /// with using-declarations that match a filter
+    fn reexport_nav(&self, filter: &dyn Fn(&Entity<'e>) -> bool) -> Vec<NavItem> {
+        let mut reexports = self.reexports.clone();
+        reexports.retain(|e| filter(e));
+        reexports.sort_by_key(|e| e.get_name());
+        reexports
+            .iter()
+            .filter_map(|entity| {
+                Some(NavItem::new_link(
+                    &entity.get_name()?,
+                    entity.rel_docs_url(self.config.clone())?,
+                    Some(("corner-down-right", false)),
+                    Vec::new(),
+                ))
+            })
+            .collect()
+    }
+
+    fn fmt_others(
+        &self,
+        title: &str,
+        others: &[Entity<'e>],
+        fmt: fn(&Entity, &Builder) -> Html,
+        builder: &'e Builder<'e>,
+    ) -> Html {
+        let mut others = others.to_vec();
+        others.sort_by_key(|e| e.get_name());
+        fmt_section(
+            title,
+            others.iter().map(|entity| fmt(entity, builder)).collect(),
+        )
+    }
+
+    /// Get the enums in this namespace and the namespaces in it, along with
+    /// the URL of the namespace page they're listed on
+    pub fn all_enums(&self) -> Vec<(Entity<'e>, UrlPath)> {
+        let mut res = if self.is_root {
+            Vec::new()
+        } else {
+            self.enums.iter().map(|e| (*e, self.url())).collect()
+        };
+        for entry in self.entries.values() {
+            if let CppItem::Namespace(ns) = entry {
+                res.extend(ns.all_enums());
+            }
+        }
+        res
+    }
+
     // so apparently if you make this a <M: Fn(&dyn ASTEntry<'e>) -> bool>
     // rustc crashes
     pub fn get(&'e self, matcher: &dyn Fn(&dyn ASTEntry<'e>) -> bool) -> Vec<&'e dyn ASTEntry<'e>> {
@@ -339,6 +849,9 @@ impl<'e> Namespace<'e> {
 impl<'e> Entry<'e> for Namespace<'e> {
     fn build(&self, builder: &Builder<'e>) -> BuildResult {
         let mut handles = Vec::new();
+        if !self.is_root {
+            handles.extend(builder.create_output_for(self)?);
+        }
         for entry in self.entries.values() {
             handles.extend(entry.build(builder)?);
         }
@@ -351,20 +864,74 @@ impl<'e> Entry<'e> for Namespace<'e> {
         // Namespaces first in sorted order, everything else after in sorted order
         entries.sort_by_key(|p| (!matches!(p.1, CppItem::Namespace(_)), p.0));
 
-        if self.is_root {
-            NavItem::new_root(None, entries.iter().map(|e| e.1.nav()).collect())
+        if self.is_root && self.config.is_c() {
+            // C has no namespaces, so entities are grouped by their header
+            let mut headers = BTreeMap::<String, Vec<NavItem>>::new();
+            for (_, entry) in entries {
+                let header = entry
+                    .entity()
+                    .include_path(self.config.clone())
+                    .map(|p| p.to_raw_string())
+                    .unwrap_or_default();
+                headers.entry(header).or_default().push(entry.nav());
+            }
+            NavItem::new_root(
+                None,
+                headers
+                    .into_iter()
+                    .flat_map(|(header, items)| {
+                        if header.is_empty() {
+                            items
+                        } else {
+                            vec![NavItem::new_dir(&header, items, Some(("file", false)))]
+                        }
+                    })
+                    .collect(),
+            )
+        } else if self.is_root && self.config.sources.iter().any(|s| s.nav_title.is_some()) {
+            // Entities are grouped by the source they're from, with the ones
+            // that aren't from any source after them
+            let source_of = |entity: &Entity| entity.config_source(self.config.clone());
+            let mut items = self
+                .config
+                .sources
+                .iter()
+                .filter_map(|source| {
+                    let items = self.nav_filtered(&|entity| {
+                        source_of(entity).is_some_and(|s| s.name == source.name)
+                    });
+                    (!items.is_empty()).then(|| NavItem::new_section(source, items))
+                })
+                .collect::<Vec<_>>();
+            items.extend(self.nav_filtered(&|entity| source_of(entity).is_none()));
+            NavItem::new_root(None, items)
+        } else if self.is_root {
+            NavItem::new_root(
+                None,
+                entries
+                    .iter()
+                    .map(|e| e.1.nav())
+                    .chain(self.reexport_nav(&|_| true))
+                    .collect(),
+            )
         } else {
             NavItem::new_dir(
                 &self.name(),
-                entries.iter().map(|e| e.1.nav()).collect(),
+                entries
+                    .iter()
+                    .map(|e| e.1.nav())
+                    .chain(self.reexport_nav(&|_| true))
+                    .collect(),
                 None,
             )
         }
     }
 
     fn name(&self) -> String {
+        // Renamed namespaces are listed under their new name
         self.entity
             .get_name()
+            .and_then(|_| self.entity.full_name().pop())
             .unwrap_or("<Anonymous namespace>".into())
     }
 
@@ -373,7 +940,7 @@ impl<'e> Entry<'e> for Namespace<'e> {
             UrlPath::new()
         } else {
             self.entity
-                .rel_docs_url()
+                .rel_docs_url(self.config.clone())
                 .expect("Unable to get namespace URL")
         }
     }
@@ -388,3 +955,73 @@ impl<'e> ASTEntry<'e> for Namespace<'e> {
         "namespace"
     }
 }
+
+impl<'e> OutputEntry<'e> for Namespace<'e> {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+        (
+            builder.config.templates.namespace.clone(),
+            vec![
+                ("name", HtmlText::new(self.name()).into()),
+                (
+                    "description",
+                    self.entity
+                        .get_comment()
+                        .map(|s| JSDocComment::parse(s, builder).to_html(false))
+                        .unwrap_or(Html::p("")),
+                ),
+                (
+                    "namespaces",
+                    self.fmt_entries(CppItemKind::Namespace, builder),
+                ),
+                ("classes", self.fmt_entries(CppItemKind::Class, builder)),
+                ("structs", self.fmt_entries(CppItemKind::Struct, builder)),
+                (
+                    "functions",
+                    self.fmt_entries(CppItemKind::Function, builder),
+                ),
+                (
+                    "enums",
+                    self.fmt_others(&builder.config.tr("enums"), &self.enums, fmt_enum, builder),
+                ),
+                (
+                    "aliases",
+                    self.fmt_others(
+                        &builder.config.tr("aliases"),
+                        &self.aliases,
+                        fmt_entity_summary,
+                        builder,
+                    ),
+                ),
+                (
+                    "variables",
+                    self.fmt_entries(CppItemKind::Variable, builder),
+                ),
+                (
+                    "reexports",
+                    self.fmt_others(
+                        &builder.config.tr("reexports"),
+                        &self.reexports,
+                        fmt_reexport,
+                        builder,
+                    ),
+                ),
+            ],
+        )
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        self.output_description(builder)
+    }
+
+    fn breadcrumbs(&self, builder: &'e Builder<'e>) -> Vec<(String, Option<UrlPath>)> {
+        entity_breadcrumbs(builder.config.clone(), self)
+    }
+
+    fn schema_type(&self) -> &'static str {
+        "SoftwareSourceCode"
+    }
+
+    fn source(&self) -> Option<String> {
+        self.entity().get_usr().map(|usr| usr.0)
+    }
+}
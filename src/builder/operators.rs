@@ -0,0 +1,99 @@
+/// Overloadable operators, along with a URL-safe slug and a short description
+/// of what they are usually used for
+const OPERATORS: &[(&str, &str, &str)] = &[
+    ("+", "plus", "addition"),
+    ("-", "minus", "subtraction"),
+    ("*", "star", "multiplication / dereference"),
+    ("/", "slash", "division"),
+    ("%", "percent", "modulo"),
+    ("^", "xor", "bitwise xor"),
+    ("&", "amp", "bitwise and / address-of"),
+    ("|", "pipe", "bitwise or"),
+    ("~", "tilde", "bitwise not"),
+    ("!", "not", "logical not"),
+    ("=", "assign", "assignment"),
+    ("<", "lt", "less than"),
+    (">", "gt", "greater than"),
+    ("+=", "plus-assign", "addition assignment"),
+    ("-=", "minus-assign", "subtraction assignment"),
+    ("*=", "star-assign", "multiplication assignment"),
+    ("/=", "slash-assign", "division assignment"),
+    ("%=", "percent-assign", "modulo assignment"),
+    ("^=", "xor-assign", "bitwise xor assignment"),
+    ("&=", "amp-assign", "bitwise and assignment"),
+    ("|=", "pipe-assign", "bitwise or assignment"),
+    ("<<", "lshift", "stream insertion"),
+    (">>", "rshift", "stream extraction"),
+    ("<<=", "lshift-assign", "left shift assignment"),
+    (">>=", "rshift-assign", "right shift assignment"),
+    ("==", "eq", "equality"),
+    ("!=", "ne", "inequality"),
+    ("<=", "le", "less than or equal"),
+    (">=", "ge", "greater than or equal"),
+    ("<=>", "spaceship", "three-way comparison"),
+    ("&&", "and", "logical and"),
+    ("||", "or", "logical or"),
+    ("++", "increment", "increment"),
+    ("--", "decrement", "decrement"),
+    (",", "comma", "comma"),
+    ("->*", "arrow-star", "pointer to member access"),
+    ("->", "arrow", "member access"),
+    ("()", "call", "function call"),
+    ("[]", "subscript", "subscript"),
+    ("new", "new", "allocation"),
+    ("new[]", "new-array", "array allocation"),
+    ("delete", "delete", "deallocation"),
+    ("delete[]", "delete-array", "array deallocation"),
+    ("co_await", "co-await", "coroutine await"),
+];
+
+/// Get the part after `operator` in an operator function's name, like `<<` or
+/// `bool`
+fn operator_symbol(name: &str) -> Option<&str> {
+    let rest = name.strip_prefix("operator")?;
+    // Something like operatorName is just a regular identifier
+    if rest
+        .chars()
+        .next()
+        .is_none_or(|c| c.is_alphanumeric() || c == '_')
+    {
+        return None;
+    }
+    Some(rest.trim())
+}
+
+/// Get a URL-safe name for an operator function, like `operator-lshift` for
+/// `operator<<`. Returns None if the name is not an operator
+pub fn operator_slug(name: &str) -> Option<String> {
+    let symbol = operator_symbol(name)?;
+    if let Some((_, slug, _)) = OPERATORS.iter().find(|(op, _, _)| *op == symbol) {
+        return Some(format!("operator-{slug}"));
+    }
+    // User-defined literals and conversion operators
+    let symbol = match symbol.strip_prefix("\"\"") {
+        Some(suffix) => format!("literal {suffix}"),
+        None => symbol.replace('*', " ptr ").replace('&', " ref "),
+    };
+    Some(format!(
+        "operator-{}",
+        symbol
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("-")
+    ))
+}
+
+/// Get a human-readable title for an operator function, like
+/// `operator<< (stream insertion)`. Returns None if the name is not an operator
+pub fn operator_title(name: &str) -> Option<String> {
+    let symbol = operator_symbol(name)?;
+    let desc = if let Some((_, _, desc)) = OPERATORS.iter().find(|(op, _, _)| *op == symbol) {
+        *desc
+    } else if symbol.starts_with("\"\"") {
+        "user-defined literal"
+    } else {
+        "conversion"
+    };
+    Some(format!("{name} ({desc})"))
+}
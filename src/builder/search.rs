@@ -0,0 +1,303 @@
+use clang::{Accessibility, Availability, Entity, EntityKind};
+use serde_json::json;
+
+use crate::{config::SearchRankingConfig, url::UrlPath};
+
+use super::{
+    builder::Builder,
+    namespace::CppItemKind,
+    shared::{enumerator_anchor, member_fun_link},
+    traits::{Access, EntityMethods, Include},
+};
+
+/// Something that can be searched for in the navbar, either an entity with its
+/// own page or a member that links to its anchor on the page it's on
+pub struct SearchEntry {
+    /// Fully qualified name, like `geode::Mod::get`
+    pub name: String,
+    /// Fully qualified name of the class or enum it's in, if it's a member
+    pub parent: Option<String>,
+    pub kind: &'static str,
+    pub url: UrlPath,
+    pub deprecated: bool,
+}
+
+impl SearchEntry {
+    fn new(entity: &Entity, parent: Option<String>, kind: &'static str, url: UrlPath) -> Self {
+        Self {
+            name: entity.full_name().join("::"),
+            parent,
+            kind,
+            url,
+            deprecated: is_deprecated(entity),
+        }
+    }
+
+    pub fn is_member(&self) -> bool {
+        self.parent.is_some()
+    }
+
+    /// How much the score of a match against this entry is changed by, which
+    /// doesn't depend on the query. Exact and prefix matches are boosted in
+    /// the navbar script since they do
+    pub fn boost(&self, ranking: &SearchRankingConfig) -> f64 {
+        let mut boost = 0.0;
+        if self.is_member() {
+            boost -= ranking.member_penalty;
+        }
+        if self.deprecated {
+            boost -= ranking.deprecated_penalty;
+        }
+        boost
+    }
+}
+
+/// Split a fully qualified name into the parts the navbar matches a query
+/// against, like `geode`, `Mod` and `get`. Template arguments are kept in the
+/// part they belong to, except in operator names where `<` and `>` aren't
+/// brackets
+fn name_parts(name: &str) -> Vec<&str> {
+    let mut res = Vec::new();
+    let mut start = 0;
+    let mut depth = 0usize;
+    let mut chars = name.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let in_operator = name[start..].starts_with("operator");
+        match c {
+            '<' if !in_operator => depth += 1,
+            '>' if !in_operator => depth = depth.saturating_sub(1),
+            ':' if depth == 0 && chars.peek().is_some_and(|(_, next)| *next == ':') => {
+                res.push(&name[start..i]);
+                chars.next();
+                start = i + 2;
+            }
+            _ => {}
+        }
+    }
+    res.push(&name[start..]);
+    res
+}
+
+/// Check if an entity is marked `[[deprecated]]` or has `@deprecated` in its
+/// doc comment
+fn is_deprecated(entity: &Entity) -> bool {
+    entity.get_availability() == Availability::Deprecated
+        || entity
+            .get_comment()
+            .is_some_and(|c| c.lines().any(|line| line.contains("@deprecated")))
+}
+
+/// Get every public method, field and enumerator so they can be searched
+/// for on their own, with links to their anchors on the page they're on
+pub fn search_members(builder: &Builder) -> Vec<SearchEntry> {
+    let config = builder.config.clone();
+    let mut res = Vec::new();
+    let entries = builder.root.get(&|entry| {
+        matches!(
+            CppItemKind::from(entry.entity()),
+            Some(CppItemKind::Class | CppItemKind::Struct)
+        )
+    });
+    for entry in entries {
+        let entity = entry.entity();
+        let parent = entity.full_name().join("::");
+        for method in entity
+            .get_member_functions(Access::Public, Include::All)
+            .into_iter()
+            .filter(|m| !m.is_hidden(config.clone()))
+        {
+            let url = entry
+                .url()
+                .with_fragment(&member_fun_link(&method).unwrap_or_default());
+            res.push(SearchEntry::new(
+                &method,
+                Some(parent.clone()),
+                "method",
+                url,
+            ));
+        }
+        for field in entity.get_children().into_iter().filter(|child| {
+            child.get_kind() == EntityKind::FieldDecl
+                && child.get_accessibility() == Some(Accessibility::Public)
+                && !child.is_hidden(config.clone())
+        }) {
+            let url = entry
+                .url()
+                .with_fragment(&field.get_name().unwrap_or_default());
+            res.push(SearchEntry::new(&field, Some(parent.clone()), "field", url));
+        }
+    }
+    for (entity, url) in builder.root.all_enums() {
+        let parent = entity.full_name().join("::");
+        for constant in entity
+            .get_children()
+            .into_iter()
+            .filter(|c| c.get_kind() == EntityKind::EnumConstantDecl)
+        {
+            let mut item = SearchEntry::new(
+                &constant,
+                Some(parent.clone()),
+                "enumerator",
+                url.clone()
+                    .with_fragment(&enumerator_anchor(&entity, &constant)),
+            );
+            // Unscoped enumerators are named as if they were outside the enum
+            item.name = format!("{parent}::{}", constant.get_name().unwrap_or_default());
+            res.push(item);
+        }
+    }
+    dedup_overloads(&mut res);
+    res
+}
+
+/// Merge the entries of overloads, which all link to the same anchor
+fn dedup_overloads(entries: &mut Vec<SearchEntry>) {
+    entries.sort_by(|a, b| (&a.name, a.url.to_string()).cmp(&(&b.name, b.url.to_string())));
+    entries.dedup_by(|a, b| {
+        if a.name == b.name && a.url == b.url {
+            // Only penalize the anchor if every overload is deprecated
+            b.deprecated &= a.deprecated;
+            true
+        } else {
+            false
+        }
+    });
+}
+
+/// Get every entity with its own page, so the navbar script can rank the
+/// links to them
+fn search_entities(builder: &Builder) -> Vec<SearchEntry> {
+    builder
+        .root
+        .get(&|entry| {
+            !matches!(
+                CppItemKind::from(entry.entity()),
+                Some(CppItemKind::Namespace) | None
+            )
+        })
+        .into_iter()
+        .map(|entry| SearchEntry::new(entry.entity(), None, entry.category(), entry.url()))
+        .collect()
+}
+
+/// Build `search.json`, which has the ranking settings for the navbar search
+/// and every entry it ranks. Entities are only listed if their score is
+/// changed, as their links are already in the navbar
+pub fn build_search_index(builder: &Builder) -> serde_json::Value {
+    search_index(
+        &builder.config.search_ranking,
+        search_entities(builder),
+        search_members(builder),
+    )
+}
+
+fn search_index(
+    ranking: &SearchRankingConfig,
+    entities: Vec<SearchEntry>,
+    members: Vec<SearchEntry>,
+) -> serde_json::Value {
+    let entries = entities
+        .into_iter()
+        .filter(|entry| entry.boost(ranking) != 0.0)
+        .chain(members)
+        .map(|entry| {
+            json!({
+                "name": entry.name,
+                "parts": name_parts(&entry.name),
+                "kind": entry.kind,
+                "url": entry.url.to_string(),
+                "member": entry.is_member(),
+                "boost": entry.boost(ranking),
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "ranking": {
+            "fuzzy": ranking.fuzzy,
+            "exact": ranking.exact_match,
+            "prefix": ranking.prefix_match,
+        },
+        "entries": entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, parent: Option<&str>, url: &str, deprecated: bool) -> SearchEntry {
+        SearchEntry {
+            name: name.into(),
+            parent: parent.map(|p| p.into()),
+            kind: "method",
+            url: UrlPath::parse(url).unwrap(),
+            deprecated,
+        }
+    }
+
+    #[test]
+    fn splits_names() {
+        assert_eq!(name_parts("geode::Mod::get"), ["geode", "Mod", "get"]);
+        assert_eq!(name_parts("get"), ["get"]);
+        assert_eq!(
+            name_parts("ns::Map<ns::Key, Vec<a::b>>::find"),
+            ["ns", "Map<ns::Key, Vec<a::b>>", "find"]
+        );
+    }
+
+    #[test]
+    fn splits_operator_names() {
+        assert_eq!(name_parts("ns::Foo::operator<"), ["ns", "Foo", "operator<"]);
+        assert_eq!(name_parts("ns::operator<<"), ["ns", "operator<<"]);
+        assert_eq!(
+            name_parts("ns::Ptr::operator->"),
+            ["ns", "Ptr", "operator->"]
+        );
+    }
+
+    #[test]
+    fn merges_overloads() {
+        let mut entries = vec![
+            entry("ns::Foo::get", Some("ns::Foo"), "classes/ns/Foo#get", true),
+            entry("ns::Foo::bar", Some("ns::Foo"), "classes/ns/Foo#bar", false),
+            entry("ns::Foo::get", Some("ns::Foo"), "classes/ns/Foo#get", false),
+            entry("ns::Foo::bar", Some("ns::Foo"), "classes/ns/Foo#bar", false),
+        ];
+        dedup_overloads(&mut entries);
+        let names = entries.iter().map(|e| e.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["ns::Foo::bar", "ns::Foo::get"]);
+        assert!(entries.iter().all(|e| !e.deprecated));
+    }
+
+    #[test]
+    fn builds_index() {
+        let ranking = SearchRankingConfig::default();
+        let index = search_index(
+            &ranking,
+            vec![
+                entry("ns::Foo", None, "classes/ns/Foo", false),
+                entry("ns::Old", None, "classes/ns/Old", true),
+            ],
+            vec![entry(
+                "ns::Foo::get",
+                Some("ns::Foo"),
+                "classes/ns/Foo#get",
+                false,
+            )],
+        );
+        assert_eq!(index["ranking"]["fuzzy"], ranking.fuzzy);
+        assert_eq!(index["ranking"]["exact"], ranking.exact_match);
+
+        // Entities are only listed if their score changes
+        let entries = index["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["name"], "ns::Old");
+        assert_eq!(entries[0]["member"], false);
+        assert_eq!(entries[0]["boost"], -ranking.deprecated_penalty);
+
+        assert_eq!(entries[1]["parts"], json!(["ns", "Foo", "get"]));
+        assert_eq!(entries[1]["url"], "/classes/ns/Foo#get");
+        assert_eq!(entries[1]["member"], true);
+        assert_eq!(entries[1]["boost"], -ranking.member_penalty);
+    }
+}
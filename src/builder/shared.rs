@@ -1,15 +1,22 @@
 use super::builder::Builder;
 use super::comment::JSDocComment;
-use super::namespace::CppItem;
-use super::traits::{ASTEntry, Access, EntityMethods, Entry, Include};
+use super::markdown::fmt_markdown;
+use super::namespace::{CppItem, CppItemKind, is_nested_class};
+use super::since::fmt_since;
+use super::source::SourceFile;
+use super::traits::{
+    ASTEntry, Access, EntityMethods, Entry, Include, entity_platforms, specialization_anchor,
+};
 use crate::annotation::Annotations;
 use crate::config::Config;
-use crate::html::{Html, HtmlElement, HtmlList, HtmlText};
-use clang::{Accessibility, Entity, EntityKind, Type, TypeKind};
+use crate::html::{GenHtml, Html, HtmlElement, HtmlList, HtmlText};
+use crate::url::UrlPath;
+use clang::{Accessibility, Entity, EntityKind, EvaluationResult, StorageClass, Type, TypeKind};
 use multipeek::{IteratorExt, MultiPeek};
 use pulldown_cmark::CowStr;
+use regex_lite::Regex;
 use std::str::Chars;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 
 trait Surround<T> {
     fn surround(self, start: T, end: T) -> Self;
@@ -42,6 +49,95 @@ impl<T, Sep: Fn() -> T> InsertBetween<T, Sep> for Vec<T> {
     }
 }
 
+fn builtin_type_name(ty: &Type) -> Option<&'static str> {
+    Some(match ty.get_kind() {
+        TypeKind::Void => "void",
+        TypeKind::Bool => "bool",
+        TypeKind::Long => "long",
+        TypeKind::Auto => "auto",
+        TypeKind::Int => "int",
+        TypeKind::Short => "short",
+        TypeKind::SChar | TypeKind::CharS => "char",
+        TypeKind::UChar | TypeKind::CharU => "uchar",
+        TypeKind::Float => "float",
+        TypeKind::Double => "double",
+        TypeKind::UInt => "uint",
+        TypeKind::LongLong => "long long",
+        _ => return None,
+    })
+}
+
+/// Find the docs URL of a type by its name, which may be unqualified as long
+/// as it's not ambiguous
+fn type_url(builder: &Builder, name: &str) -> Option<UrlPath> {
+    builder
+        .type_urls
+        .get(name)
+        .or_else(|| builder.type_suffixes.get(name)?.as_ref())
+        .cloned()
+}
+
+/// Resolve a reference like `Class::method`, `ns::function` or `Class` from a
+/// doc comment to the docs URL of what it refers to
+pub fn resolve_reference(builder: &Builder, reference: &str) -> Option<String> {
+    let name = reference.split('(').next()?.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    // Entities with their own pages, qualified just enough to be unambiguous
+    let suffix = format!("::{name}");
+    let found = builder.root.get(&|entry| {
+        let full = entry.entity().full_name().join("::");
+        full == name || full.ends_with(&suffix)
+    });
+    if let [entry] = found.as_slice() {
+        return Some(entry.url().to_absolute(builder.config.clone()).to_string());
+    }
+
+    // Members link to their anchor on the page of their class
+    let (owner, member) = name.rsplit_once("::")?;
+    type_url(builder, owner).map(|url| {
+        url.to_absolute(builder.config.clone())
+            .with_fragment(member)
+            .to_string()
+    })
+}
+
+/// Possibly qualified names in the spelling of a type
+static TYPE_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:[A-Za-z_]\w*::)*[A-Za-z_]\w*").unwrap());
+
+/// Link all known type names in the spelling of a type, for types that
+/// LibClang can't resolve to a declaration (like dependent types)
+fn fmt_type_spelling(spelling: &str, builder: &Builder) -> Html {
+    let mut list: Vec<Html> = Vec::new();
+    let mut last = 0;
+    for m in TYPE_NAME.find_iter(spelling) {
+        list.push(HtmlText::new(&spelling[last..m.start()]).into());
+        last = m.end();
+
+        let name = m.as_str();
+        let link = type_url(builder, name)
+            .map(|url| url.to_absolute(builder.config.clone()).to_string())
+            .or_else(|| {
+                name.starts_with("std::")
+                    .then(|| builder.config.cppreference_url(name).to_string())
+            });
+
+        list.push(match link {
+            Some(link) => HtmlElement::new("a")
+                .with_class("type-link")
+                .with_attr("href", link)
+                .with_text(name)
+                .into(),
+            None => HtmlText::new(name).into(),
+        });
+    }
+    list.push(HtmlText::new(&spelling[last..]).into());
+    HtmlList::new(list).into()
+}
+
 fn fmt_type(entity: &Type, builder: &Builder) -> Html {
     let base = entity.get_pointee_type().unwrap_or(entity.to_owned());
     let decl = base.get_declaration();
@@ -52,12 +148,24 @@ fn fmt_type(entity: &Type, builder: &Builder) -> Html {
 
     let name: Html = decl
         .map(|decl| {
+            // Renamed or collapsed namespaces can make the name shorter than
+            // the list of ancestors, but classes are always at the end of both
+            let ancestors = decl.ancestorage();
+            let names = decl.full_name();
+            let kind_of = |i: usize| {
+                ancestors
+                    .len()
+                    .checked_sub(names.len() - i)
+                    .map(|j| ancestors[j].get_kind())
+                    .unwrap_or(EntityKind::Namespace)
+            };
             HtmlList::new(
-                decl.ancestorage()
+                names
                     .iter()
-                    .map(|e| {
+                    .enumerate()
+                    .map(|(i, name)| {
                         HtmlElement::new("span")
-                            .with_class(match e.get_kind() {
+                            .with_class(match kind_of(i) {
                                 EntityKind::Namespace => "namespace",
                                 EntityKind::ClassDecl => "class",
                                 EntityKind::ClassTemplate => "class",
@@ -70,7 +178,7 @@ fn fmt_type(entity: &Type, builder: &Builder) -> Html {
                                 _ => "type",
                             })
                             .with_class("name")
-                            .with_child(HtmlText::new(e.get_name().unwrap_or("_".into())))
+                            .with_child(HtmlText::new(name))
                             .into()
                     })
                     .collect::<Vec<_>>()
@@ -86,34 +194,22 @@ fn fmt_type(entity: &Type, builder: &Builder) -> Html {
                     "keyword"
                 })
                 .with_class("name")
-                .with_child(HtmlText::new(match base.get_kind() {
-                    TypeKind::Void => "void".into(),
-                    TypeKind::Bool => "bool".into(),
-                    TypeKind::Long => "long".into(),
-                    TypeKind::Auto => "auto".into(),
-                    TypeKind::Int => "int".into(),
-                    TypeKind::Short => "short".into(),
-                    TypeKind::SChar | TypeKind::CharS => "char".into(),
-                    TypeKind::UChar | TypeKind::CharU => "uchar".into(),
-                    TypeKind::Float => "float".into(),
-                    TypeKind::Double => "double".into(),
-                    TypeKind::UInt => "uint".into(),
-                    TypeKind::LongLong => "long long".into(),
-                    _ => base.get_display_name(),
-                }))
+                .with_child(match builtin_type_name(&base) {
+                    Some(name) => HtmlText::new(name).into(),
+                    None => fmt_type_spelling(&base.get_display_name(), builder),
+                })
                 .into()
         });
 
-    HtmlElement::new("a")
+    // Links can't be nested, and the spelling of an undeclared type may have
+    // links in it
+    HtmlElement::new(if link.is_some() { "a" } else { "span" })
         .with_class("entity")
         .with_class("type")
         .with_class_opt(entity.is_pod().then_some("keyword"))
         .with_class_opt(link.is_none().then_some("disabled"))
-        .with_attr_opt("href", link.clone())
-        .with_attr_opt(
-            "onclick",
-            link.map(|link| format!("return navigate('{link}')")),
-        )
+        .with_bool_attr("data-navigate", link.is_some())
+        .with_attr_opt("href", link)
         .with_child(name)
         .with_child_opt(match kind {
             EntityKind::TypeAliasDecl | EntityKind::TypedefDecl => None,
@@ -150,6 +246,214 @@ fn fmt_type(entity: &Type, builder: &Builder) -> Html {
         .into()
 }
 
+/// Get the default argument of a function parameter as written, since
+/// LibClang doesn't expose it other than through the tokens
+fn param_default(param: &Entity) -> Option<String> {
+    if param.get_kind() != EntityKind::ParmDecl {
+        return None;
+    }
+    let tokens = param
+        .get_range()?
+        .tokenize()
+        .into_iter()
+        .map(|t| t.get_spelling())
+        .collect::<Vec<_>>();
+    // Skip over any `=` inside of the type, like in array sizes or function
+    // pointer parameters
+    let mut depth = 0;
+    let start = tokens.iter().position(|t| {
+        match t.as_str() {
+            "(" | "[" | "{" | "<" => depth += 1,
+            ")" | "]" | "}" | ">" => depth -= 1,
+            ">>" => depth -= 2,
+            "=" if depth <= 0 => return true,
+            _ => {}
+        }
+        false
+    })?;
+    let value = join_tokens(&tokens[start + 1..]);
+    (!value.is_empty()).then_some(value)
+}
+
+/// Join tokens back into source code, only keeping spaces between words like
+/// in `unsigned int(5)` and around logical and comparison operators
+fn join_tokens(tokens: &[String]) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut res = String::new();
+    for token in tokens {
+        if matches!(token.as_str(), "&&" | "||" | "==" | "!=" | "<=" | ">=") {
+            res.push_str(&format!(" {token} "));
+            continue;
+        }
+        if res.ends_with(is_word) && token.starts_with(is_word) {
+            res.push(' ');
+        }
+        res.push_str(token);
+        if token == "," {
+            res.push(' ');
+        }
+    }
+    res
+}
+
+/// Get the index after the bracket that closes the one at `start`
+fn skip_balanced(tokens: &[String], start: usize) -> usize {
+    let (open, close) = match tokens[start].as_str() {
+        "(" => ("(", ")"),
+        "[" => ("[", "]"),
+        "{" => ("{", "}"),
+        _ => ("<", ">"),
+    };
+    let mut depth = 0;
+    let mut parens = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        match token.as_str() {
+            // Comparisons inside parentheses don't close template arguments
+            "(" if open == "<" => parens += 1,
+            ")" if open == "<" => parens -= 1,
+            ">>" if open == "<" && parens == 0 => depth -= 2,
+            t if parens == 0 && t == open => depth += 1,
+            t if parens == 0 && t == close => depth -= 1,
+            _ => {}
+        }
+        if depth <= 0 {
+            return i + 1;
+        }
+    }
+    tokens.len()
+}
+
+/// Get the index after the constraint expression of a `requires` clause
+/// starting at `start`, like `std::integral<T> && (sizeof(T) > 2)`
+fn skip_constraint(tokens: &[String], start: usize) -> usize {
+    let mut i = start;
+    while i < tokens.len() {
+        match tokens[i].as_str() {
+            "(" => i = skip_balanced(tokens, i),
+            "!" => {
+                i += 1;
+                continue;
+            }
+            // A requires expression, like `requires(T t) { t.foo(); }`
+            "requires" => {
+                i += 1;
+                if tokens.get(i).is_some_and(|t| t == "(") {
+                    i = skip_balanced(tokens, i);
+                }
+                if tokens.get(i).is_some_and(|t| t == "{") {
+                    i = skip_balanced(tokens, i);
+                }
+            }
+            _ => {
+                while tokens.get(i).is_some_and(|t| {
+                    t == "::" || t.starts_with(|c: char| c.is_alphanumeric() || c == '_')
+                }) {
+                    i += 1;
+                    if tokens.get(i).is_some_and(|t| t == "<") {
+                        i = skip_balanced(tokens, i);
+                    }
+                }
+            }
+        }
+        if tokens.get(i).is_some_and(|t| t == "&&" || t == "||") {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    i
+}
+
+/// Get the constraints on a template from its `requires` clauses and any
+/// `std::enable_if` in its declaration
+fn template_constraints(entity: &Entity) -> Vec<String> {
+    let Some(range) = entity.get_range() else {
+        return Vec::new();
+    };
+    let tokens = range
+        .tokenize()
+        .into_iter()
+        .map(|t| t.get_spelling())
+        .collect::<Vec<_>>();
+
+    let mut res = Vec::new();
+    let mut parens = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        match tokens[i].as_str() {
+            "requires" => {
+                let end = skip_constraint(&tokens, i + 1);
+                if end > i + 1 {
+                    res.push(join_tokens(&tokens[i + 1..end]));
+                }
+                i = end;
+                continue;
+            }
+            // The condition is the first argument of `enable_if`
+            "enable_if" | "enable_if_t" if tokens.get(i + 1).is_some_and(|t| t == "<") => {
+                let end = skip_balanced(&tokens, i + 1);
+                let mut args = tokens[i + 2..end.saturating_sub(1).max(i + 2)].to_vec();
+                // `>>` closes both the last argument and `enable_if`
+                if tokens.get(end - 1).is_some_and(|t| t == ">>") {
+                    args.push(">".into());
+                }
+                let mut depth = 0;
+                let cond = args
+                    .into_iter()
+                    .take_while(|t| {
+                        match t.as_str() {
+                            "(" | "<" | "[" => depth += 1,
+                            ")" | ">" | "]" => depth -= 1,
+                            ">>" => depth -= 2,
+                            "," if depth == 0 => return false,
+                            _ => {}
+                        }
+                        true
+                    })
+                    .collect::<Vec<_>>();
+                if !cond.is_empty() {
+                    res.push(join_tokens(&cond));
+                }
+                i = end;
+                continue;
+            }
+            "(" => parens += 1,
+            ")" => parens -= 1,
+            // Stop at the body, since it could have requires expressions of
+            // its own
+            "{" | ";" if parens == 0 => break,
+            _ => {}
+        }
+        i += 1;
+    }
+    res
+}
+
+/// Format the constraints on a template as a single `requires` clause
+fn fmt_constraints(entity: &Entity) -> Option<Html> {
+    let constraints = template_constraints(entity);
+    (!constraints.is_empty()).then(|| {
+        HtmlElement::new("span")
+            .with_class("constraints")
+            .with_child(Html::span(&["keyword", "space-before"], "requires"))
+            .with_child(Html::span(
+                &["space-before", "literal"],
+                &constraints
+                    .iter()
+                    .map(|c| {
+                        if constraints.len() > 1 && (c.contains("&&") || c.contains("||")) {
+                            format!("({c})")
+                        } else {
+                            c.clone()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" && "),
+            ))
+            .into()
+    })
+}
+
 fn fmt_param(param: &Entity, builder: &Builder) -> Html {
     HtmlElement::new("div")
         .with_classes(&["entity", "var"])
@@ -159,6 +463,12 @@ fn fmt_param(param: &Entity, builder: &Builder) -> Html {
                 .get_display_name()
                 .map(|name| Html::span(&["name", "space-before"], &name)),
         )
+        .with_child_opt(param_default(param).map(|value| {
+            HtmlList::new(vec![
+                Html::span(&["space-before"], "="),
+                Html::span(&["space-before", "literal"], &value),
+            ])
+        }))
         .into()
 }
 
@@ -203,6 +513,7 @@ fn fmt_template_args(entity: &Entity, _builder: &Builder) -> Option<Html> {
 pub fn fmt_field(field: &Entity, builder: &Builder) -> Html {
     HtmlElement::new("details")
         .with_class("entity-desc")
+        .with_attr_opt("id", field.get_name())
         .with_child(
             HtmlElement::new("summary")
                 .with_classes(&["entity", "var"])
@@ -214,12 +525,138 @@ pub fn fmt_field(field: &Entity, builder: &Builder) -> Html {
                 field
                     .get_comment()
                     .map(|s| JSDocComment::parse(s, builder).to_html(true))
-                    .unwrap_or(Html::span(&["no-desc"], "No description provided")),
+                    .unwrap_or(Html::span(
+                        &["no-desc"],
+                        &builder.config.tr("no_description"),
+                    )),
             ),
         )
         .into()
 }
 
+/// Get the qualifiers and attributes of a function that are worth pointing out,
+/// like `constexpr` or `[[nodiscard]]`
+/// Check if a constructor or conversion operator lets the compiler convert
+/// between types implicitly
+fn is_implicit_conversion(fun: &Entity, tokens: &[String]) -> bool {
+    if tokens.iter().any(|t| t == "explicit") {
+        return false;
+    }
+    match fun.get_kind() {
+        EntityKind::ConversionFunction => true,
+        EntityKind::Constructor => {
+            fun.is_converting_constructor()
+                && !fun.is_copy_constructor()
+                && !fun.is_move_constructor()
+        }
+        _ => false,
+    }
+}
+
+fn fun_qualifiers(fun: &Entity) -> Vec<&'static str> {
+    // Keywords like these aren't exposed by LibClang, so look for them in the
+    // declaration's tokens
+    let tokens = fun.declaration_tokens();
+    let has_token = |name: &str| tokens.iter().any(|t| t == name);
+    let has_attr = |kind: EntityKind| fun.get_children().iter().any(|c| c.get_kind() == kind);
+
+    [
+        (
+            fun.is_static_method() || fun.get_storage_class() == Some(StorageClass::Static),
+            "static",
+        ),
+        (fun.is_virtual_method(), "virtual"),
+        (fun.is_const_method(), "const"),
+        (has_token("explicit"), "explicit"),
+        (is_implicit_conversion(fun, &tokens), "implicit"),
+        (has_token("constexpr"), "constexpr"),
+        (has_token("consteval"), "consteval"),
+        (has_token("noexcept"), "noexcept"),
+        (has_attr(EntityKind::OverrideAttr), "override"),
+        (has_attr(EntityKind::FinalAttr), "final"),
+        (has_token("nodiscard"), "[[nodiscard]]"),
+    ]
+    .into_iter()
+    .filter_map(|(has, name)| has.then_some(name))
+    .collect()
+}
+
+/// Get whether a function is declared `= delete` or `= default`
+fn fun_definition(fun: &Entity) -> Option<&'static str> {
+    let tokens = fun.declaration_tokens();
+    if tokens.windows(2).any(|w| w[0] == "=" && w[1] == "delete") {
+        Some("delete")
+    } else if fun.is_defaulted() {
+        Some("default")
+    } else {
+        None
+    }
+}
+
+/// Format a badge with the platforms an entity is declared on, if it isn't
+/// declared on all of the ones in `analysis.platforms`
+pub fn fmt_platforms(entity: &Entity, builder: &Builder) -> Option<Html> {
+    let platforms = entity_platforms(entity)?;
+    Some(Html::span(
+        &["badge", "platforms"],
+        &builder
+            .config
+            .tr("platform_only")
+            .replace("{}", &platforms.join(", ")),
+    ))
+}
+
+/// Format the list of example files an entity is used in, linking to the line
+/// it's first used on in each
+pub fn fmt_example_usage(entity: &Entity, builder: &Builder) -> Html {
+    let Some(index) = &builder.example_usage else {
+        return Html::empty();
+    };
+    let files = index.files_using(entity);
+    if files.is_empty() {
+        return Html::empty();
+    }
+    HtmlElement::new("p")
+        .with_class("example-usage")
+        .with_child(Html::span(
+            &["space-after"],
+            &builder.config.tr("used_in_examples"),
+        ))
+        .with_children(
+            files
+                .into_iter()
+                .map(|(file, line)| {
+                    HtmlElement::new("a")
+                        .with_attr(
+                            "href",
+                            file.url()
+                                .with_fragment(&format!("L{line}"))
+                                .to_absolute(builder.config.clone()),
+                        )
+                        .with_text(file.name())
+                        .into()
+                })
+                .collect::<Vec<_>>()
+                .insert_between(|| Html::span(&["space-after"], ",")),
+        )
+        .into()
+}
+
+fn fmt_qualifiers(fun: &Entity) -> Option<Html> {
+    let qualifiers = fun_qualifiers(fun);
+    (!qualifiers.is_empty()).then(|| {
+        HtmlElement::new("span")
+            .with_class("qualifiers")
+            .with_children(
+                qualifiers
+                    .into_iter()
+                    .map(|q| Html::span(&["badge", "qualifier"], q))
+                    .collect(),
+            )
+            .into()
+    })
+}
+
 fn fmt_fun_signature(fun: &Entity, builder: &Builder) -> Html {
     HtmlElement::new("summary")
         .with_classes(&["entity", "fun"])
@@ -235,7 +672,18 @@ fn fmt_fun_signature(fun: &Entity, builder: &Builder) -> Html {
                     fun.is_virtual_method()
                         .then_some(Html::span(&["keyword", "space-after"], "virtual")),
                 )
-                .with_child_opt(fun.get_result_type().map(|t| fmt_type(&t, builder)))
+                // The type of constructors and conversion operators is
+                // already in their name
+                .with_child_opt(
+                    fun.get_result_type()
+                        .filter(|_| {
+                            !matches!(
+                                fun.get_kind(),
+                                EntityKind::Constructor | EntityKind::ConversionFunction
+                            ) && fun.get_template_kind() != Some(EntityKind::Constructor)
+                        })
+                        .map(|t| fmt_type(&t, builder)),
+                )
                 .with_child(Html::span(
                     &["name", "space-before"],
                     &fun.get_name().unwrap_or("_anon".into()),
@@ -265,8 +713,17 @@ fn fmt_fun_signature(fun: &Entity, builder: &Builder) -> Html {
                         ])
                         .into(),
                     ),
-                ),
+                )
+                .with_child_opt(fun_definition(fun).map(|def| {
+                    HtmlList::new(vec![
+                        Html::span(&["space-before"], "="),
+                        Html::span(&["keyword", "space-before"], def),
+                    ])
+                }))
+                .with_child_opt(fmt_constraints(fun)),
         )
+        .with_child_opt(fmt_qualifiers(fun))
+        .with_child_opt(fmt_platforms(fun, builder))
         .into()
 }
 
@@ -279,12 +736,292 @@ pub fn fmt_class_method(fun: &Entity, builder: &Builder) -> Html {
             HtmlElement::new("div").with_child(
                 fun.get_comment()
                     .map(|s| JSDocComment::parse(s, builder).to_html(true))
-                    .unwrap_or(Html::span(&["no-desc"], "No description provided")),
+                    .unwrap_or(Html::span(
+                        &["no-desc"],
+                        &builder.config.tr("no_description"),
+                    )),
             ),
         )
         .into()
 }
 
+/// Format the name of an entity as a link to its docs page
+fn fmt_entity_name(entity: &Entity, builder: &Builder) -> Html {
+    let name = entity.get_name().unwrap_or("_anon".into());
+    match entity.abs_docs_url(builder.config.clone()) {
+        Some(link) => HtmlElement::new("a")
+            .with_class("name")
+            .with_attr("href", link)
+            .with_bool_attr("data-navigate", true)
+            .with_text(name)
+            .into(),
+        None => Html::span(&["name"], &name),
+    }
+}
+
+/// Format an entity as a single line in the contents of a namespace, with the
+/// first sentence of its docs
+pub fn fmt_entity_summary(entity: &Entity, builder: &Builder) -> Html {
+    let keyword = match entity.get_kind() {
+        EntityKind::Namespace => Some("namespace"),
+        EntityKind::ClassDecl | EntityKind::ClassTemplate => Some("class"),
+        EntityKind::StructDecl => Some("struct"),
+        EntityKind::EnumDecl if entity.is_scoped() => Some("enum class"),
+        EntityKind::EnumDecl => Some("enum"),
+        EntityKind::TypeAliasDecl | EntityKind::TypeAliasTemplateDecl | EntityKind::TypedefDecl => {
+            Some("using")
+        }
+        _ => None,
+    };
+    // Alias templates keep the aliased type in a child declaration
+    let aliased = match entity.get_kind() {
+        EntityKind::TypeAliasTemplateDecl => entity
+            .get_children()
+            .into_iter()
+            .find(|c| c.get_kind() == EntityKind::TypeAliasDecl)
+            .and_then(|c| c.get_typedef_underlying_type()),
+        EntityKind::TypeAliasDecl | EntityKind::TypedefDecl => entity.get_typedef_underlying_type(),
+        _ => None,
+    };
+
+    HtmlElement::new("div")
+        .with_class("entity-summary")
+        .with_child(
+            HtmlElement::new("div")
+                .with_class("entity")
+                .with_child_opt(keyword.map(|k| Html::span(&["keyword", "space-after"], k)))
+                .with_child(fmt_entity_name(entity, builder))
+                .with_child_opt(fmt_platforms(entity, builder))
+                .with_child_opt(aliased.map(|ty| {
+                    HtmlList::new(vec![
+                        Html::span(&["space-before", "space-after"], "="),
+                        fmt_type(&ty, builder),
+                    ])
+                })),
+        )
+        .with_child(
+            entity
+                .get_comment()
+                .and_then(|c| JSDocComment::parse(c, builder).brief())
+                .map(|brief| {
+                    fmt_markdown(builder, &fmt_autolinks(builder, &brief), None::<fn(_) -> _>)
+                })
+                .unwrap_or(Html::span(
+                    &["no-desc"],
+                    &builder.config.tr("no_description"),
+                )),
+        )
+        .into()
+}
+
+/// Format the summary of an entity brought into a namespace with a
+/// using-declaration, noting which namespace it was originally defined in
+pub fn fmt_reexport(entity: &Entity, builder: &Builder) -> Html {
+    let parent = entity
+        .get_semantic_parent()
+        .filter(|p| p.get_kind() == EntityKind::Namespace);
+    let parent_name = parent
+        .map(|p| p.full_name().join("::"))
+        .unwrap_or(String::from("::"));
+    let text = builder.config.tr("originally_defined_in");
+    let (before, after) = text
+        .split_once("{namespace}")
+        .unwrap_or((text.as_str(), ""));
+    HtmlElement::new("div")
+        .with_class("reexport")
+        .with_child(fmt_entity_summary(entity, builder))
+        .with_child(
+            HtmlElement::new("p")
+                .with_class("reexport-note")
+                .with_text(before)
+                .with_child(
+                    match parent.and_then(|p| p.abs_docs_url(builder.config.clone())) {
+                        Some(url) => HtmlElement::new("a")
+                            .with_attr("href", url)
+                            .with_child(Html::span(&["namespace", "name"], &parent_name))
+                            .into(),
+                        None => Html::span(&["namespace", "name"], &parent_name),
+                    },
+                )
+                .with_text(after),
+        )
+        .into()
+}
+
+/// Check if the values of an enum look like bit flags, which is when there are
+/// a few powers of two that aren't just the first few values in order and the
+/// rest are combinations of them
+fn looks_like_flags(values: &[u64]) -> bool {
+    let bits = values
+        .iter()
+        .filter(|v| v.is_power_of_two())
+        .fold(0, |acc, v| acc | v);
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    let consecutive = sorted.windows(2).all(|w| w[1] == w[0] + 1);
+    bits.count_ones() >= 3 && !consecutive && values.iter().all(|v| v & !bits == 0)
+}
+
+/// Get the id of the row of an enumerator in its enum's value table, like
+/// `Color::Red`
+pub fn enumerator_anchor(entity: &Entity, constant: &Entity) -> String {
+    format!(
+        "{}::{}",
+        entity.get_name().unwrap_or_default(),
+        constant.get_name().unwrap_or_default()
+    )
+}
+
+/// Format an enum with a table of its values. Flag sets, either marked with
+/// `@flags` or detected from their values, also get the values in hex, the
+/// bit of each flag, and what combined values are made of
+pub fn fmt_enum(entity: &Entity, builder: &Builder) -> Html {
+    let comment = entity
+        .get_comment()
+        .map(|c| JSDocComment::parse(c, builder));
+    // Mask negative values to the size of the enum for showing them in hex
+    let mask = match entity
+        .get_enum_underlying_type()
+        .and_then(|t| t.get_sizeof().ok())
+    {
+        Some(size) if size < 8 => (1u64 << (size * 8)) - 1,
+        _ => u64::MAX,
+    };
+    let values = entity
+        .get_children()
+        .into_iter()
+        .filter(|c| c.get_kind() == EntityKind::EnumConstantDecl)
+        .filter_map(|c| c.get_enum_constant_value().map(|(s, u)| (c, s, u & mask)))
+        .collect::<Vec<_>>();
+    let flags = comment.as_ref().is_some_and(|c| c.is_flags())
+        || looks_like_flags(&values.iter().map(|(_, _, u)| *u).collect::<Vec<_>>());
+
+    let prefix = if entity.is_scoped() {
+        format!("{}::", entity.get_name().unwrap_or_default())
+    } else {
+        String::new()
+    };
+    let bit_names = values
+        .iter()
+        .filter(|(_, _, u)| u.is_power_of_two())
+        .map(|(c, _, u)| (*u, format!("{prefix}{}", c.get_name().unwrap_or_default())))
+        .collect::<Vec<_>>();
+    let combination = |value: u64| {
+        bit_names
+            .iter()
+            .filter(|(bit, _)| value & bit != 0)
+            .map(|(_, name)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(" | ")
+    };
+
+    let headers = if flags {
+        vec!["name", "value", "hex", "bit"]
+    } else {
+        vec!["name", "value"]
+    };
+    let rows = values.iter().map(|(constant, signed, value)| {
+        HtmlElement::new("tr")
+            .with_attr("id", enumerator_anchor(entity, constant))
+            .with_child(HtmlElement::new("td").with_text(constant.get_name().unwrap_or_default()))
+            .with_child(HtmlElement::new("td").with_text(if *signed < 0 {
+                signed.to_string()
+            } else {
+                value.to_string()
+            }))
+            .with_child_opt(flags.then(|| HtmlElement::new("td").with_text(format!("{value:#x}"))))
+            .with_child_opt(flags.then(|| {
+                HtmlElement::new("td").with_text(if value.is_power_of_two() {
+                    value.trailing_zeros().to_string()
+                } else {
+                    combination(*value)
+                })
+            }))
+            .with_child(
+                HtmlElement::new("td").with_child_opt(
+                    constant
+                        .get_comment()
+                        .and_then(|c| JSDocComment::parse(c, builder).brief())
+                        .map(|brief| {
+                            fmt_markdown(
+                                builder,
+                                &fmt_autolinks(builder, &brief),
+                                None::<fn(_) -> _>,
+                            )
+                        }),
+                ),
+            )
+            .into()
+    });
+
+    HtmlElement::new("details")
+        .with_classes(&["entity-desc", "enum"])
+        .with_attr_opt("id", entity.get_name())
+        .with_child(
+            HtmlElement::new("summary")
+                .with_classes(&["entity", "enum"])
+                .with_child(Html::span(
+                    &["keyword", "space-after"],
+                    if entity.is_scoped() {
+                        "enum class"
+                    } else {
+                        "enum"
+                    },
+                ))
+                .with_child(Html::span(
+                    &["name"],
+                    &entity.get_name().unwrap_or_default(),
+                ))
+                .with_child_opt(
+                    flags.then(|| {
+                        Html::span(&["badge", "space-before"], &builder.config.tr("flags"))
+                    }),
+                ),
+        )
+        .with_child(
+            HtmlElement::new("div")
+                .with_child(
+                    comment
+                        .as_ref()
+                        .map(|c| c.to_html(true))
+                        .unwrap_or(Html::span(
+                            &["no-desc"],
+                            &builder.config.tr("no_description"),
+                        )),
+                )
+                .with_child_opt((!values.is_empty()).then(|| {
+                    HtmlElement::new("table")
+                        .with_classes(&["layout", "enum-values"])
+                        .with_child(
+                            HtmlElement::new("tr").with_children(
+                                headers
+                                    .into_iter()
+                                    .map(|key| {
+                                        HtmlElement::new("th")
+                                            .with_text(builder.config.tr(key))
+                                            .into()
+                                    })
+                                    .collect(),
+                            ),
+                        )
+                        .with_children(rows.collect())
+                }))
+                // Show how the flags are combined with the first two of them
+                .with_child_opt((flags && bit_names.len() >= 2).then(|| {
+                    HtmlElement::new("p")
+                        .with_text(builder.config.tr("flags_example"))
+                        .with_child(HtmlElement::new("code").with_text(format!(
+                            "{} | {} == {:#x}",
+                            bit_names[0].1,
+                            bit_names[1].1,
+                            bit_names[0].0 | bit_names[1].0
+                        )))
+                })),
+        )
+        .into()
+}
+
 pub fn fmt_classlike_decl(class: &Entity, keyword: &str, builder: &Builder) -> Html {
     HtmlElement::new("details")
         .with_class("entity-desc")
@@ -292,11 +1029,9 @@ pub fn fmt_classlike_decl(class: &Entity, keyword: &str, builder: &Builder) -> H
             HtmlElement::new("summary")
                 .with_classes(&["entity", keyword])
                 .with_child(Html::span(&["keyword", "space-after"], keyword))
-                .with_child(Html::span(
-                    &["name"],
-                    &class.get_name().unwrap_or("_anon".into()),
-                ))
+                .with_child(fmt_entity_name(class, builder))
                 .with_child_opt(fmt_template_args(class, builder))
+                .with_child_opt(fmt_constraints(class))
                 .with_child(HtmlText::new(";")),
         )
         .with_child(
@@ -304,15 +1039,154 @@ pub fn fmt_classlike_decl(class: &Entity, keyword: &str, builder: &Builder) -> H
                 class
                     .get_comment()
                     .map(|s| JSDocComment::parse(s, builder).to_html(true))
-                    .unwrap_or(Html::span(&["no-desc"], "No description provided")),
+                    .unwrap_or(Html::span(
+                        &["no-desc"],
+                        &builder.config.tr("no_description"),
+                    )),
             ),
         )
         .into()
 }
 
+/// Format a specialization of a class template, which is documented on the
+/// primary template's page under its own anchor
+fn fmt_specialization(spec: &Entity, builder: &Builder) -> Html {
+    let keyword = if spec.get_kind() == EntityKind::StructDecl {
+        "struct"
+    } else {
+        "class"
+    };
+    HtmlElement::new("details")
+        .with_class("entity-desc")
+        .with_attr("id", specialization_anchor(spec))
+        .with_child(
+            HtmlElement::new("summary")
+                .with_classes(&["entity", keyword])
+                .with_child_opt(fmt_template_args(spec, builder))
+                .with_child(Html::span(&["keyword", "space-after"], keyword))
+                .with_child(Html::span(
+                    &["name"],
+                    &spec.get_display_name().unwrap_or_default(),
+                ))
+                .with_child(HtmlText::new(";")),
+        )
+        .with_child(
+            HtmlElement::new("div")
+                .with_child(
+                    spec.get_comment()
+                        .map(|s| JSDocComment::parse(s, builder).to_html(true))
+                        .unwrap_or(Html::span(
+                            &["no-desc"],
+                            &builder.config.tr("no_description"),
+                        )),
+                )
+                .with_children(
+                    spec.get_member_functions(Access::Public, Include::All)
+                        .into_iter()
+                        .filter(|e| !e.is_hidden(builder.config.clone()))
+                        .map(|e| fmt_class_method(&e, builder))
+                        .collect(),
+                ),
+        )
+        .into()
+}
+
+/// Format a friend declaration, linking to the befriended class or function if
+/// it has docs
+pub fn fmt_friend(friend: &Entity, builder: &Builder) -> Option<Html> {
+    let target = friend.get_children().into_iter().next()?;
+    match target.get_kind() {
+        EntityKind::FunctionDecl | EntityKind::FunctionTemplate => {
+            // Friend functions only have docs if they are also declared in
+            // the enclosing namespace
+            let full_name = target.full_name();
+            let link = builder
+                .root
+                .get(&|entry| {
+                    matches!(
+                        CppItemKind::from(entry.entity()),
+                        Some(CppItemKind::Function)
+                    ) && entry.entity().full_name() == full_name
+                })
+                .first()
+                .map(|entry| entry.url().to_absolute(builder.config.clone()));
+            Some(
+                HtmlElement::new("details")
+                    .with_class("entity-desc")
+                    .with_child(fmt_fun_signature(&target, builder))
+                    .with_child(
+                        HtmlElement::new("div")
+                            .with_child(
+                                target
+                                    .get_comment()
+                                    .or(friend.get_comment())
+                                    .map(|s| JSDocComment::parse(s, builder).to_html(true))
+                                    .unwrap_or(Html::span(
+                                        &["no-desc"],
+                                        &builder.config.tr("no_description"),
+                                    )),
+                            )
+                            .with_child_opt(link.map(|link| {
+                                HtmlElement::new("a")
+                                    .with_attr("href", link)
+                                    .with_bool_attr("data-navigate", true)
+                                    .with_child(HtmlText::new(
+                                        builder.config.tr("view_documentation"),
+                                    ))
+                            })),
+                    )
+                    .into(),
+            )
+        }
+        _ => {
+            // Friend classes show up as either a reference to the class or a
+            // declaration of it
+            let decl = target.get_reference().unwrap_or(target);
+            let keyword = match decl.get_kind() {
+                EntityKind::StructDecl => "struct",
+                _ => "class",
+            };
+            Some(
+                HtmlElement::new("details")
+                    .with_class("entity-desc")
+                    .with_child(
+                        HtmlElement::new("summary")
+                            .with_classes(&["entity", keyword])
+                            .with_child(Html::span(&["keyword", "space-after"], "friend"))
+                            .with_child(Html::span(&["keyword", "space-after"], keyword))
+                            .with_child(fmt_type(&decl.get_type()?, builder))
+                            .with_child(HtmlText::new(";")),
+                    )
+                    .with_child(
+                        HtmlElement::new("div").with_child(
+                            friend
+                                .get_comment()
+                                .or(decl.get_comment())
+                                .map(|s| JSDocComment::parse(s, builder).to_html(true))
+                                .unwrap_or(Html::span(
+                                    &["no-desc"],
+                                    &builder.config.tr("no_description"),
+                                )),
+                        ),
+                    )
+                    .into(),
+            )
+        }
+    }
+}
+
 pub fn fmt_section(title: &str, data: Vec<Html>) -> Html {
+    fmt_section_impl(title, data, true)
+}
+
+/// Format a section that starts out collapsed
+pub fn fmt_collapsed_section(title: &str, data: Vec<Html>) -> Html {
+    fmt_section_impl(title, data, false)
+}
+
+fn fmt_section_impl(title: &str, data: Vec<Html>, open: bool) -> Html {
     HtmlElement::new("details")
-        .with_attr("open", "")
+        .with_bool_attr("open", open)
         .with_class("section")
         .with_child(
             HtmlElement::new("summary").with_child(
@@ -326,6 +1200,216 @@ pub fn fmt_section(title: &str, data: Vec<Html>) -> Html {
         .into()
 }
 
+pub fn fmt_layout(class: &Entity, builder: &Builder) -> Html {
+    if !builder.config.analysis.show_layout {
+        return Html::empty();
+    }
+    // Templates and incomplete types don't have a layout
+    let Some(ty) = class.get_type() else {
+        return Html::empty();
+    };
+    let (Ok(size), Ok(align)) = (ty.get_sizeof(), ty.get_alignof()) else {
+        return Html::empty();
+    };
+
+    let fields = class
+        .get_children()
+        .into_iter()
+        .filter(|child| child.get_kind() == EntityKind::FieldDecl)
+        .filter_map(|field| {
+            // Offsets are given in bits
+            let offset = field.get_offset_of_field().ok()?;
+            let size = field.get_type().and_then(|t| t.get_sizeof().ok());
+            Some(
+                HtmlElement::new("tr")
+                    .with_child(HtmlElement::new("td").with_text(if offset % 8 == 0 {
+                        format!("{:#x}", offset / 8)
+                    } else {
+                        format!("{:#x}:{}", offset / 8, offset % 8)
+                    }))
+                    .with_child(
+                        HtmlElement::new("td")
+                            .with_text(size.map(|s| s.to_string()).unwrap_or("?".into())),
+                    )
+                    .with_child(HtmlElement::new("td").with_child(fmt_param(&field, builder)))
+                    .into(),
+            )
+        })
+        .collect::<Vec<Html>>();
+
+    HtmlElement::new("details")
+        .with_bool_attr("open", true)
+        .with_class("section")
+        .with_child(
+            HtmlElement::new("summary").with_child(
+                HtmlElement::new("span")
+                    .with_child(Html::feather("chevron-right"))
+                    .with_child(HtmlText::new(builder.config.tr("layout"))),
+            ),
+        )
+        .with_child(
+            HtmlElement::new("div")
+                .with_child(Html::p(format!(
+                    "Size: {size} bytes ({size:#x}), alignment: {align} bytes"
+                )))
+                .with_child_opt((!fields.is_empty()).then(|| {
+                    HtmlElement::new("table")
+                        .with_class("layout")
+                        .with_child(
+                            HtmlElement::new("tr").with_children(
+                                ["offset", "size", "field"]
+                                    .into_iter()
+                                    .map(|key| {
+                                        HtmlElement::new("th")
+                                            .with_text(builder.config.tr(key))
+                                            .into()
+                                    })
+                                    .collect(),
+                            ),
+                        )
+                        .with_children(fields)
+                })),
+        )
+        .into()
+}
+
+/// Check if a method is an assignment operator taking the class it's in by
+/// lvalue (copy) or rvalue (move) reference
+fn is_assignment(fun: &Entity, class: &Entity, kind: TypeKind) -> bool {
+    fun.get_name().is_some_and(|n| n == "operator=")
+        && fun.get_arguments().is_some_and(|args| {
+            args.len() == 1
+                && args[0].get_type().is_some_and(|ty| {
+                    ty.get_kind() == kind
+                        && ty
+                            .get_pointee_type()
+                            .and_then(|p| p.get_declaration())
+                            .and_then(|d| d.get_name())
+                            == class.get_name()
+                })
+        })
+}
+
+/// Get a summary of the special member functions of a class, like whether it
+/// can be copied or moved, based on what it declares. The implicit ones are
+/// assumed to be usable even though members could still make them deleted
+fn special_members(class: &Entity) -> Vec<(&'static str, &'static str)> {
+    let children = class.get_children();
+    let ctors = children
+        .iter()
+        .filter(|c| c.get_kind() == EntityKind::Constructor)
+        .collect::<Vec<_>>();
+    let methods = children
+        .iter()
+        .filter(|c| c.get_kind() == EntityKind::Method)
+        .collect::<Vec<_>>();
+
+    let default_ctor = ctors.iter().find(|c| c.is_default_constructor());
+    let copy_ctor = ctors.iter().find(|c| c.is_copy_constructor());
+    let move_ctor = ctors.iter().find(|c| c.is_move_constructor());
+    let copy_assign = methods
+        .iter()
+        .find(|m| is_assignment(m, class, TypeKind::LValueReference));
+    let move_assign = methods
+        .iter()
+        .find(|m| is_assignment(m, class, TypeKind::RValueReference));
+    let dtor = children
+        .iter()
+        .find(|c| c.get_kind() == EntityKind::Destructor);
+
+    let status = |fun: &Entity| match fun_definition(fun) {
+        Some("delete") => "deleted",
+        Some(_) => "defaulted",
+        None => "user_provided",
+    };
+    // Declaring any copy operation, move operation, or destructor stops the
+    // move operations from being declared implicitly, and declaring a move
+    // operation deletes the implicit copy operations
+    let has_move = move_ctor.is_some() || move_assign.is_some();
+    let blocks_move = copy_ctor.is_some() || copy_assign.is_some() || dtor.is_some();
+
+    vec![
+        (
+            "default_constructor",
+            match default_ctor {
+                Some(c) => status(c),
+                None if !ctors.is_empty() => "not_declared",
+                None => "implicit",
+            },
+        ),
+        (
+            "copy_constructor",
+            match copy_ctor {
+                Some(c) => status(c),
+                None if has_move => "deleted",
+                None => "implicit",
+            },
+        ),
+        (
+            "move_constructor",
+            match move_ctor {
+                Some(c) => status(c),
+                None if blocks_move || move_assign.is_some() => "not_declared",
+                None => "implicit",
+            },
+        ),
+        (
+            "copy_assignment",
+            match copy_assign {
+                Some(c) => status(c),
+                None if has_move => "deleted",
+                None => "implicit",
+            },
+        ),
+        (
+            "move_assignment",
+            match move_assign {
+                Some(c) => status(c),
+                None if blocks_move || move_ctor.is_some() => "not_declared",
+                None => "implicit",
+            },
+        ),
+        ("destructor", dtor.map(status).unwrap_or("implicit")),
+    ]
+}
+
+pub fn fmt_special_members(class: &Entity, builder: &Builder) -> Html {
+    HtmlElement::new("details")
+        .with_bool_attr("open", true)
+        .with_class("section")
+        .with_child(
+            HtmlElement::new("summary").with_child(
+                HtmlElement::new("span")
+                    .with_child(Html::feather("chevron-right"))
+                    .with_child(HtmlText::new(builder.config.tr("special_members"))),
+            ),
+        )
+        .with_child(
+            HtmlElement::new("div").with_child(
+                HtmlElement::new("table")
+                    .with_class("layout")
+                    .with_children(
+                        special_members(class)
+                            .into_iter()
+                            .map(|(member, status)| {
+                                HtmlElement::new("tr")
+                                    .with_child(
+                                        HtmlElement::new("th").with_text(builder.config.tr(member)),
+                                    )
+                                    .with_child(
+                                        HtmlElement::new("td")
+                                            .with_class(status)
+                                            .with_text(builder.config.tr(status)),
+                                    )
+                                    .into()
+                            })
+                            .collect(),
+                    ),
+            ),
+        )
+        .into()
+}
+
 pub fn fmt_header_link(entity: &Entity, config: Arc<Config>) -> Html {
     if let Some(link) = entity.github_url(config.clone())
         && let Some(path) = entity.include_path(config.clone())
@@ -344,12 +1428,75 @@ pub fn fmt_header_link(entity: &Entity, config: Arc<Config>) -> Html {
                     .with_class("header-link")
                     .with_children(vec![
                         Html::span(&["keyword"], "#include "),
-                        Html::span(&["url"], &format!("&lt;{}&gt;", path.to_raw_string())),
+                        Html::span(&["url"], &format!("<{}>", path.to_raw_string())),
                     ]),
             )
             .into()
     } else {
-        Html::p("&lt;Not available online&gt;")
+        Html::p("<Not available online>")
+    }
+}
+
+/// Format a ready to copy block with the include for an entity, followed by
+/// the CMake line for linking the library of its source if it has one
+pub fn fmt_include_snippet(entity: &Entity, config: Arc<Config>) -> Html {
+    let Some(path) = entity.include_path(config.clone()) else {
+        return Html::empty();
+    };
+    let library = entity
+        .config_source(config.clone())
+        .and_then(|s| s.link_library.clone());
+    HtmlElement::new("div")
+        .with_class("include-snippet")
+        .with_child(
+            HtmlElement::new("pre").with_child(
+                HtmlElement::new("code")
+                    .with_class("language-cpp")
+                    .with_text(format!("#include <{}>", path.to_raw_string())),
+            ),
+        )
+        .with_child_opt(library.map(|lib| {
+            HtmlElement::new("pre").with_child(
+                HtmlElement::new("code")
+                    .with_class("language-cmake")
+                    .with_text(format!("target_link_libraries(${{PROJECT_NAME}} {lib})")),
+            )
+        }))
+        .into()
+}
+
+/// Link to the line an entity is declared at, in the source browser if it is
+/// enabled or otherwise online
+pub fn fmt_source_link(entity: &Entity, config: Arc<Config>) -> Html {
+    let link = entity.get_location().and_then(|loc| {
+        let line = loc.get_file_location().line;
+        let link = if config.source_browser && entity.config_source(config.clone()).is_some() {
+            let header = UrlPath::try_from(&entity.header(config.clone())?).ok()?;
+            Some(
+                SourceFile::url_for(&header)
+                    .to_absolute(config.clone())
+                    .with_fragment(&format!("L{line}"))
+                    .to_string(),
+            )
+        } else if entity.get_allowed_external_lib(config.clone()).is_none()
+            && entity
+                .config_source(config.clone())
+                .is_some_and(|s| s.exists_online)
+        {
+            entity.github_url(config.clone())
+        } else {
+            None
+        };
+        link.map(|link| (link, line))
+    });
+    match link {
+        Some((link, line)) => HtmlElement::new("a")
+            .with_class("source-link")
+            .with_attr("href", link)
+            .with_child(Html::feather("code"))
+            .with_text(format!("Defined at line {line}"))
+            .into(),
+        None => Html::empty(),
     }
 }
 
@@ -372,6 +1519,7 @@ pub fn fmt_base_classes<'e, T: ASTEntry<'e>>(entry: &T, kw: &str, builder: &Buil
                     &["name"],
                     entry.entity().get_name().unwrap_or("_".into()).as_str(),
                 ))
+                .with_child_opt(fmt_constraints(entry.entity()))
                 .with_child_opt(
                     (!bases.is_empty())
                         .then_some(Html::span(&["space-before", "space-after"], ":")),
@@ -424,16 +1572,39 @@ pub fn output_entity<'e, T: ASTEntry<'e>>(
                 .entity()
                 .get_comment()
                 .map(|s| JSDocComment::parse(s, builder).to_html(false))
-                .unwrap_or(Html::span(&["no-desc"], "No description provided")),
+                .unwrap_or(Html::span(
+                    &["no-desc"],
+                    &builder.config.tr("no_description"),
+                )),
         ),
         (
             "header_link",
             fmt_header_link(entry.entity(), builder.config.clone()),
         ),
+        (
+            "include_snippet",
+            fmt_include_snippet(entry.entity(), builder.config.clone()),
+        ),
+        (
+            "source_link",
+            fmt_source_link(entry.entity(), builder.config.clone()),
+        ),
+        (
+            "platforms",
+            fmt_platforms(entry.entity(), builder).unwrap_or(Html::empty()),
+        ),
+        (
+            "since",
+            fmt_since(entry.entity(), builder).unwrap_or(Html::empty()),
+        ),
+        (
+            "used_in_examples",
+            fmt_example_usage(entry.entity(), builder),
+        ),
         (
             "examples",
             fmt_section(
-                "Examples",
+                &builder.config.tr("examples"),
                 entry
                     .entity()
                     .get_comment()
@@ -450,24 +1621,82 @@ pub fn output_entity<'e, T: ASTEntry<'e>>(
     ]
 }
 
+/// Format the public classes, enums, and aliases declared inside a class.
+/// Nested classes link to their own pages, and the rest are shown inline
+fn fmt_member_types(class: &Entity, builder: &Builder) -> Html {
+    fmt_section(
+        &builder.config.tr("member_types"),
+        class
+            .get_children()
+            .into_iter()
+            .filter(|child| {
+                child.get_accessibility() == Some(Accessibility::Public)
+                    && !child.is_hidden(builder.config.clone())
+            })
+            .filter_map(|child| match child.get_kind() {
+                EntityKind::EnumDecl if child.is_definition() => Some(fmt_enum(&child, builder)),
+                EntityKind::TypeAliasDecl
+                | EntityKind::TypeAliasTemplateDecl
+                | EntityKind::TypedefDecl => Some(fmt_entity_summary(&child, builder)),
+                _ if is_nested_class(&child, builder.config.clone()) => {
+                    Some(fmt_entity_summary(&child, builder))
+                }
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
+/// Format a section for non-public members, which are only shown (collapsed)
+/// if enabled in the config
+fn fmt_access_section(shown: bool, title: &str, data: Vec<Html>) -> Html {
+    if shown {
+        fmt_collapsed_section(title, data)
+    } else {
+        Html::empty()
+    }
+}
+
 pub fn output_classlike<'e, T: ASTEntry<'e>>(
     entry: &T,
     builder: &Builder,
 ) -> Vec<(&'static str, Html)> {
+    let show_protected = builder.config.analysis.show_protected;
+    let show_private = builder.config.analysis.show_private;
     let mut ent = output_entity(entry, builder);
     ent.extend(vec![
         (
             "base_classes",
             fmt_base_classes(entry, entry.category(), builder),
         ),
+        ("layout", fmt_layout(entry.entity(), builder)),
+        ("member_types", fmt_member_types(entry.entity(), builder)),
+        (
+            "special_members",
+            fmt_special_members(entry.entity(), builder),
+        ),
+        (
+            "constructors",
+            fmt_section(
+                &builder.config.tr("constructors"),
+                entry
+                    .entity()
+                    .get_constructors(Access::Public)
+                    .into_iter()
+                    .filter(|e| !e.is_hidden(builder.config.clone()))
+                    .map(|e| fmt_class_method(&e, builder))
+                    .collect::<Vec<_>>(),
+            ),
+        ),
         (
             "public_static_functions",
             fmt_section(
-                "Public static methods",
+                &builder.config.tr("public_static_methods"),
                 entry
                     .entity()
                     .get_member_functions(Access::Public, Include::Statics)
                     .into_iter()
+                    .filter(|e| !e.is_hidden(builder.config.clone()))
                     .map(|e| fmt_class_method(&e, builder))
                     .collect::<Vec<_>>(),
             ),
@@ -475,11 +1704,12 @@ pub fn output_classlike<'e, T: ASTEntry<'e>>(
         (
             "public_member_functions",
             fmt_section(
-                "Public member functions",
+                &builder.config.tr("public_member_functions"),
                 entry
                     .entity()
                     .get_member_functions(Access::Public, Include::Members)
                     .into_iter()
+                    .filter(|e| !e.is_hidden(builder.config.clone()))
                     .map(|e| fmt_class_method(&e, builder))
                     .collect::<Vec<_>>(),
             ),
@@ -487,12 +1717,28 @@ pub fn output_classlike<'e, T: ASTEntry<'e>>(
         (
             // todo: hide if final class
             "protected_member_functions",
-            fmt_section(
-                "Protected member functions",
+            fmt_access_section(
+                show_protected,
+                &builder.config.tr("protected_member_functions"),
                 entry
                     .entity()
                     .get_member_functions(Access::Protected, Include::Members)
                     .into_iter()
+                    .filter(|e| !e.is_hidden(builder.config.clone()))
+                    .map(|e| fmt_class_method(&e, builder))
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+        (
+            "private_member_functions",
+            fmt_access_section(
+                show_private,
+                &builder.config.tr("private_member_functions"),
+                entry
+                    .entity()
+                    .get_member_functions(Access::Private, Include::Members)
+                    .into_iter()
+                    .filter(|e| !e.is_hidden(builder.config.clone()))
                     .map(|e| fmt_class_method(&e, builder))
                     .collect::<Vec<_>>(),
             ),
@@ -500,7 +1746,7 @@ pub fn output_classlike<'e, T: ASTEntry<'e>>(
         (
             "public_members",
             fmt_section(
-                "Fields",
+                &builder.config.tr("fields"),
                 entry
                     .entity()
                     .get_children()
@@ -508,6 +1754,7 @@ pub fn output_classlike<'e, T: ASTEntry<'e>>(
                     .filter(|child| {
                         child.get_kind() == EntityKind::FieldDecl
                             && child.get_accessibility() == Some(Accessibility::Public)
+                            && !child.is_hidden(builder.config.clone())
                     })
                     .map(|e| fmt_field(e, builder))
                     .collect::<Vec<_>>(),
@@ -515,8 +1762,9 @@ pub fn output_classlike<'e, T: ASTEntry<'e>>(
         ),
         (
             "protected_members",
-            fmt_section(
-                "Protected fields",
+            fmt_access_section(
+                show_protected,
+                &builder.config.tr("protected_fields"),
                 entry
                     .entity()
                     .get_children()
@@ -524,11 +1772,54 @@ pub fn output_classlike<'e, T: ASTEntry<'e>>(
                     .filter(|child| {
                         child.get_kind() == EntityKind::FieldDecl
                             && child.get_accessibility() == Some(Accessibility::Protected)
+                            && !child.is_hidden(builder.config.clone())
+                    })
+                    .map(|e| fmt_field(e, builder))
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+        (
+            "private_members",
+            fmt_access_section(
+                show_private,
+                &builder.config.tr("private_fields"),
+                entry
+                    .entity()
+                    .get_children()
+                    .iter()
+                    .filter(|child| {
+                        child.get_kind() == EntityKind::FieldDecl
+                            && child.get_accessibility() == Some(Accessibility::Private)
+                            && !child.is_hidden(builder.config.clone())
                     })
                     .map(|e| fmt_field(e, builder))
                     .collect::<Vec<_>>(),
             ),
         ),
+        (
+            "friends",
+            fmt_section(
+                &builder.config.tr("friends"),
+                entry
+                    .entity()
+                    .get_children()
+                    .iter()
+                    .filter(|child| child.get_kind() == EntityKind::FriendDecl)
+                    .filter_map(|e| fmt_friend(e, builder))
+                    .collect::<Vec<_>>(),
+            ),
+        ),
+        (
+            "specializations",
+            fmt_section(
+                &builder.config.tr("specializations"),
+                entry
+                    .specializations()
+                    .iter()
+                    .map(|e| fmt_specialization(e, builder))
+                    .collect::<Vec<_>>(),
+            ),
+        ),
     ]);
     ent
 }
@@ -545,6 +1836,201 @@ pub fn output_function<'e, T: ASTEntry<'e>>(
     ent
 }
 
+/// Get the text of a doc comment without the comment markers
+fn comment_text(raw: &str) -> String {
+    raw.lines()
+        .map(|line| {
+            let line = line.trim();
+            let line = line
+                .trim_start_matches("/**")
+                .trim_start_matches("/*!")
+                .trim_start_matches("///")
+                .trim_start_matches("//!")
+                .trim_end_matches("*/")
+                .trim_start_matches('*');
+            line.strip_prefix(' ').unwrap_or(line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_owned()
+}
+
+pub fn declaration_text(entity: &Entity) -> String {
+    format!(
+        "{};",
+        entity
+            .declaration_tokens()
+            .join(" ")
+            .trim_end_matches(';')
+            .trim_end()
+    )
+}
+
+/// Get the breadcrumbs leading to an entity, with links to the namespaces and
+/// classes it is nested in
+pub fn entity_breadcrumbs<'e, T: ASTEntry<'e>>(
+    config: Arc<Config>,
+    entry: &T,
+) -> Vec<(String, Option<UrlPath>)> {
+    let mut ancestors = entry.entity().ancestorage();
+    ancestors.pop();
+    let mut res = Vec::new();
+    let mut last_name = Vec::new();
+    for ancestor in ancestors {
+        let name = ancestor.full_name();
+        // Collapsed namespaces are shown as part of their parent
+        if name.is_empty() || name == last_name {
+            continue;
+        }
+        res.push((
+            name.last().cloned().unwrap_or_default(),
+            ancestor.rel_docs_url(config.clone()),
+        ));
+        last_name = name;
+    }
+    res
+}
+
+/// Format the breadcrumbs at the top of a page, ending with the page itself
+pub fn fmt_breadcrumbs(
+    config: Arc<Config>,
+    crumbs: Vec<(String, Option<UrlPath>)>,
+    current: String,
+) -> Html {
+    if crumbs.is_empty() {
+        return Html::empty();
+    }
+    HtmlElement::new("nav")
+        .with_class("breadcrumbs")
+        .with_attr("aria-label", config.tr("breadcrumbs"))
+        .with_children(
+            crumbs
+                .into_iter()
+                .map(|(name, url)| match url {
+                    Some(url) => HtmlElement::new("a")
+                        .with_attr("href", url.to_absolute(config.clone()))
+                        .with_text(name)
+                        .into(),
+                    None => Html::span(&[], &name),
+                })
+                .chain([Html::span(&["current"], &current)])
+                .collect::<Vec<_>>()
+                .insert_between(|| Html::feather("chevron-right")),
+        )
+        .into()
+}
+
+/// Format an entity as plain markdown for LLM-based tools
+pub fn output_entity_markdown<'e, T: ASTEntry<'e>>(entry: &T, builder: &Builder) -> String {
+    let entity = entry.entity();
+    let mut md = format!("# {}\n\n```cpp\n", entry.name());
+    if let Some(path) = entity.include_path(builder.config.clone()) {
+        md.push_str(&format!("#include <{}>\n\n", path.to_raw_string()));
+    }
+    md.push_str(&declaration_text(entity));
+    md.push_str("\n```\n\n");
+    md.push_str(
+        &entity
+            .get_comment()
+            .map(|c| comment_text(&c))
+            .unwrap_or(builder.config.tr("no_description")),
+    );
+    md.push('\n');
+
+    if matches!(
+        CppItemKind::from(entity),
+        Some(CppItemKind::Class | CppItemKind::Struct)
+    ) {
+        let funs = entity.get_member_functions(Access::Public, Include::All);
+        if !funs.is_empty() {
+            md.push_str("\n## Public member functions\n\n");
+            for fun in funs.iter().filter(|f| !f.is_hidden(builder.config.clone())) {
+                md.push_str(&format!("### `{}`\n\n", declaration_text(fun)));
+                if let Some(comment) = fun.get_comment() {
+                    md.push_str(&comment_text(&comment));
+                    md.push_str("\n\n");
+                }
+            }
+        }
+    }
+    md
+}
+
+/// Get the value a variable is initialized with, evaluated if possible
+fn var_value(var: &Entity) -> Option<String> {
+    match var.evaluate() {
+        Some(EvaluationResult::SignedInteger(v)) => Some(v.to_string()),
+        Some(EvaluationResult::UnsignedInteger(v)) => Some(v.to_string()),
+        Some(EvaluationResult::Float(v)) => Some(v.to_string()),
+        Some(EvaluationResult::String(s)) => Some(format!("{:?}", s.to_string_lossy())),
+        // Otherwise just show the initializer as written
+        _ => var
+            .extract_source_string_cleaned()?
+            .split_once('=')
+            .map(|(_, value)| value.trim().trim_end_matches(';').trim().to_owned()),
+    }
+}
+
+fn fmt_var_signature(var: &Entity, builder: &Builder) -> Html {
+    let tokens = var.declaration_tokens();
+    let has_token = |name: &str| tokens.iter().take_while(|t| *t != "=").any(|t| t == name);
+    HtmlElement::new("div")
+        .with_classes(&["entity", "var"])
+        .with_child_opt(
+            has_token("inline").then_some(Html::span(&["keyword", "space-after"], "inline")),
+        )
+        .with_child_opt(
+            has_token("constexpr").then_some(Html::span(&["keyword", "space-after"], "constexpr")),
+        )
+        .with_child_opt(var.get_type().map(|t| fmt_type(&t, builder)))
+        .with_child(Html::span(
+            &["name", "space-before"],
+            &var.get_name().unwrap_or("_anon".into()),
+        ))
+        .with_child_opt(var_value(var).map(|value| {
+            HtmlList::new(vec![
+                Html::span(&["space-before"], "="),
+                Html::span(&["space-before", "literal"], &value),
+            ])
+        }))
+        .with_child(HtmlText::new(";"))
+        .into()
+}
+
+pub fn fmt_variable(var: &Entity, builder: &Builder) -> Html {
+    HtmlElement::new("details")
+        .with_class("entity-desc")
+        .with_child(
+            HtmlElement::new("summary")
+                .with_classes(&["entity", "var"])
+                .with_child(fmt_var_signature(var, builder)),
+        )
+        .with_child(
+            HtmlElement::new("div").with_child(
+                var.get_comment()
+                    .map(|s| JSDocComment::parse(s, builder).to_html(true))
+                    .unwrap_or(Html::span(
+                        &["no-desc"],
+                        &builder.config.tr("no_description"),
+                    )),
+            ),
+        )
+        .into()
+}
+
+pub fn output_variable<'e, T: ASTEntry<'e>>(
+    entry: &T,
+    builder: &Builder,
+) -> Vec<(&'static str, Html)> {
+    let mut ent = output_entity(entry, builder);
+    ent.extend(vec![(
+        "variable_signature",
+        fmt_var_signature(entry.entity(), builder),
+    )]);
+    ent
+}
+
 fn fmt_autolinks_recursive(
     entity: &CppItem,
     config: Arc<Config>,
@@ -561,10 +2047,8 @@ fn fmt_autolinks_recursive(
         }
     }
 
-    if let CppItem::Namespace(ns) = entity {
-        for v in ns.entries.values() {
-            fmt_autolinks_recursive(v, config.clone(), annotations);
-        }
+    for v in entity.children() {
+        fmt_autolinks_recursive(v, config.clone(), annotations);
     }
 }
 
@@ -576,46 +2060,83 @@ pub fn fmt_autolinks(builder: &Builder, text: &str) -> String {
     annotations.into_result()
 }
 
-pub fn fmt_emoji(text: &CowStr) -> String {
-    fn eat_emoji<'e>(iter: &mut MultiPeek<Chars>) -> Option<&'e str> {
+/// Replace `:shortcode:` emojis in text, with custom emojis from the config
+/// taking priority. Custom emojis can be images, in which case the text is
+/// returned as html and the second value is true
+pub fn fmt_emoji(text: &CowStr, config: Arc<Config>) -> (String, bool) {
+    fn eat_shortcode(iter: &mut MultiPeek<Chars>) -> Option<(String, usize)> {
         let mut buffer = String::new();
         let mut i = 0;
         while let Some(d) = iter.peek_nth(i) {
-            if d.is_alphanumeric() || *d == '_' {
+            if d.is_alphanumeric() || *d == '_' || *d == '-' {
                 buffer.push(*d);
             } else if *d == ':' {
-                break;
+                return Some((buffer, i));
             } else {
                 return None;
             }
             i += 1;
         }
-        if let Some(emoji) = emojis::get_by_shortcode(&buffer) {
-            #[allow(clippy::match_single_binding)]
-            match iter.advance_by(i + 1) {
-                _ => {}
-            }
-            Some(emoji.as_str())
-        } else {
-            None
-        }
+        None
     }
 
+    let custom = config.markdown.as_ref().map(|m| &m.emoji);
     let mut res = String::new();
     res.reserve(text.len());
+    let mut parts: Vec<Html> = Vec::new();
 
     let mut iter = text.chars().multipeek();
     while let Some(c) = iter.next() {
-        if c == ':'
-            && let Some(emoji) = eat_emoji(&mut iter)
+        let emoji = if c == ':'
+            && let Some((code, len)) = eat_shortcode(&mut iter)
         {
-            res.push_str(emoji);
+            custom
+                .and_then(|c| c.get(&code))
+                .map(|e| e.as_str())
+                .or(emojis::get_by_shortcode(&code).map(|e| e.as_str()))
+                .map(|e| (code, e, len))
         } else {
+            None
+        };
+        let Some((code, emoji, len)) = emoji else {
             res.push(c);
+            continue;
+        };
+        #[allow(clippy::match_single_binding)]
+        match iter.advance_by(len + 1) {
+            _ => {}
+        }
+
+        // Anything that looks like a path or a URL is an image
+        if emoji.contains('/') || emoji.contains('.') {
+            parts.push(HtmlText::new(std::mem::take(&mut res)).into());
+            parts.push(
+                HtmlElement::new("img")
+                    .with_class("emoji")
+                    .with_attr(
+                        "src",
+                        match UrlPath::parse(emoji) {
+                            Ok(url) if emoji.starts_with('/') => {
+                                url.to_absolute(config.clone()).to_string()
+                            }
+                            _ => emoji.to_string(),
+                        },
+                    )
+                    .with_attr("alt", format!(":{code}:"))
+                    .with_attr("title", format!(":{code}:"))
+                    .into(),
+            );
+        } else {
+            res.push_str(emoji);
         }
     }
 
-    res
+    if parts.is_empty() {
+        (res, false)
+    } else {
+        parts.push(HtmlText::new(res).into());
+        (HtmlList::new(parts).gen_html(), true)
+    }
 }
 
 pub fn member_fun_link(entity: &Entity) -> Option<String> {
@@ -0,0 +1,271 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    process::Command,
+    sync::{Arc, OnceLock},
+};
+
+use clang::Entity;
+use log::info;
+
+use crate::{
+    config::Config,
+    html::{Html, HtmlElement, HtmlText},
+    url::UrlPath,
+};
+
+use super::{
+    builder::Builder,
+    comment::JSDocComment,
+    namespace::CppItemKind,
+    shared::{fmt_section, member_fun_link},
+    traits::{Access, BuildResult, EntityMethods, Entry, Include, NavItem, OutputEntry},
+};
+
+/// The first git tag that the name of each identifier in the headers shows up
+/// in, for entities without `@since`
+static INFERRED: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Find the first tag each identifier in the project's headers shows up in,
+/// going through the tags of the repository in version order. This matches by
+/// name, so it's only used for entities with their own pages
+pub fn infer_from_tags(config: Arc<Config>) -> Result<(), String> {
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&config.input_dir)
+            .output()
+            .map_err(|e| format!("Unable to run git: {e}"))?;
+        // git grep fails if nothing matched, which is fine
+        Ok::<_, String>(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    let tags = git(&["tag", "--list", "--sort=v:refname"])?;
+    let mut found = HashMap::new();
+    for tag in tags.lines().map(str::trim).filter(|t| !t.is_empty()) {
+        let idents = git(&[
+            "grep",
+            "-I",
+            "-h",
+            "-o",
+            "-w",
+            "-E",
+            "[A-Za-z_][A-Za-z0-9_]*",
+            tag,
+            "--",
+            "*.h",
+            "*.hpp",
+            "*.hh",
+            "*.hxx",
+        ])?;
+        for ident in idents.lines() {
+            if !found.contains_key(ident) {
+                found.insert(ident.to_owned(), tag.to_owned());
+            }
+        }
+    }
+    info!(
+        "Inferred versions for {} identifiers from git tags",
+        found.len()
+    );
+    let _ = INFERRED.set(found);
+    Ok(())
+}
+
+/// Get the version an entity was added in, from `@since` in its doc comment
+/// or, if enabled, the git tags of the project
+pub fn entity_since(entity: &Entity, builder: &Builder, infer: bool) -> Option<String> {
+    entity
+        .get_comment()
+        .and_then(|c| JSDocComment::parse(c, builder).since().cloned())
+        .or_else(|| {
+            infer
+                .then(|| INFERRED.get()?.get(&entity.get_name()?).cloned())
+                .flatten()
+        })
+}
+
+/// Compare versions like `v2.0.0` and `v2.0.0-beta.1`, treating numbers as
+/// numbers so `v2.10.0` comes after `v2.9.0`
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |v: &str| {
+        v.trim_start_matches(['v', 'V'])
+            .split(|c: char| !c.is_alphanumeric())
+            .map(|part| part.parse::<u64>().map_err(|_| part.to_owned()))
+            .collect::<Vec<_>>()
+    };
+    let (a, b) = (parts(a), parts(b));
+    for (a, b) in a.iter().zip(&b) {
+        let ord = match (a, b) {
+            (Ok(a), Ok(b)) => a.cmp(b),
+            // Numbers come after prerelease tags like beta
+            (Ok(_), Err(_)) => Ordering::Greater,
+            (Err(_), Ok(_)) => Ordering::Less,
+            (Err(a), Err(b)) => a.cmp(b),
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    // A prerelease comes before the release it's for
+    match (a.len() > b.len(), b.len() > a.len()) {
+        (true, _) if a[b.len()].is_err() => Ordering::Less,
+        (_, true) if b[a.len()].is_err() => Ordering::Greater,
+        _ => a.len().cmp(&b.len()),
+    }
+}
+
+/// Get the id of a version's section on the versions page
+pub fn version_anchor(version: &str) -> String {
+    version.replace(|c: char| !c.is_alphanumeric() && c != '.' && c != '-', "-")
+}
+
+/// Format the badge shown on the page of an entity with the version it was
+/// added in, linking to the version's section on the versions page
+pub fn fmt_since(entity: &Entity, builder: &Builder) -> Option<Html> {
+    let since = entity_since(entity, builder, builder.config.since.infer_from_tags)?;
+    let text = builder.config.tr("since_version").replace("{}", &since);
+    Some(if builder.config.since.version_index {
+        HtmlElement::new("a")
+            .with_classes(&["badge", "since"])
+            .with_attr(
+                "href",
+                SinceIndex::page_url()
+                    .with_fragment(&version_anchor(&since))
+                    .to_absolute(builder.config.clone()),
+            )
+            .with_text(text)
+            .into()
+    } else {
+        Html::span(&["badge", "since"], &text)
+    })
+}
+
+/// An entity or member that was added in a version
+struct Added {
+    name: String,
+    url: UrlPath,
+}
+
+/// A page listing the API added in each version, newest first
+pub struct SinceIndex {
+    versions: Vec<(String, Vec<Added>)>,
+}
+
+impl SinceIndex {
+    pub fn new(builder: &Builder) -> Self {
+        let config = builder.config.clone();
+        let mut found = BTreeMap::<String, Vec<Added>>::new();
+        let entries = builder.root.get(&|entry| {
+            !matches!(
+                CppItemKind::from(entry.entity()),
+                Some(CppItemKind::Namespace) | None
+            )
+        });
+        for entry in entries {
+            let entity = entry.entity();
+            if let Some(since) = entity_since(entity, builder, config.since.infer_from_tags) {
+                found.entry(since).or_default().push(Added {
+                    name: entity.full_name().join("::"),
+                    url: entry.url(),
+                });
+            }
+            for member in entity
+                .get_constructors(Access::Public)
+                .into_iter()
+                .chain(entity.get_member_functions(Access::Public, Include::All))
+                .filter(|m| !m.is_hidden(config.clone()))
+            {
+                if let Some(since) = entity_since(&member, builder, false) {
+                    found.entry(since).or_default().push(Added {
+                        name: member.full_name().join("::"),
+                        url: entry
+                            .url()
+                            .with_fragment(&member_fun_link(&member).unwrap_or_default()),
+                    });
+                }
+            }
+        }
+        let mut versions = found.into_iter().collect::<Vec<_>>();
+        versions.sort_by(|a, b| compare_versions(&b.0, &a.0));
+        for (_, items) in &mut versions {
+            items.sort_by(|a, b| a.name.cmp(&b.name));
+            items.dedup_by(|a, b| a.name == b.name && a.url == b.url);
+        }
+        Self { versions }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.versions.is_empty()
+    }
+
+    fn page_url() -> UrlPath {
+        UrlPath::parse("versions").unwrap()
+    }
+}
+
+impl<'e> Entry<'e> for SinceIndex {
+    fn name(&self) -> String {
+        String::from("Versions")
+    }
+
+    fn url(&self) -> UrlPath {
+        Self::page_url()
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("clock", false)), Vec::new())
+    }
+}
+
+impl<'e> OutputEntry<'e> for SinceIndex {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+        let config = builder.config.clone();
+        (
+            builder.config.templates.tutorial_index.clone(),
+            vec![
+                ("title", HtmlText::new(config.tr("api_versions")).into()),
+                (
+                    "links",
+                    HtmlElement::new("div")
+                        .with_children(
+                            self.versions
+                                .iter()
+                                .map(|(version, items)| {
+                                    HtmlElement::new("div")
+                                        .with_attr("id", version_anchor(version))
+                                        .with_child(fmt_section(
+                                            version,
+                                            items
+                                                .iter()
+                                                .map(|item| {
+                                                    HtmlElement::new("a")
+                                                        .with_attr(
+                                                            "href",
+                                                            item.url.to_absolute(config.clone()),
+                                                        )
+                                                        .with_text(&item.name)
+                                                        .into()
+                                                })
+                                                .collect(),
+                                        ))
+                                        .into()
+                                })
+                                .collect(),
+                        )
+                        .into(),
+                ),
+            ],
+        )
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!(
+            "The API added in each version of {}",
+            builder.config.project.name
+        )
+    }
+}
@@ -0,0 +1,291 @@
+use std::{fs, sync::Arc};
+
+use crate::{
+    config::Source,
+    html::{Html, HtmlElement, HtmlList, HtmlText},
+    url::UrlPath,
+};
+
+use super::{
+    builder::Builder,
+    files::dir_breadcrumbs,
+    traits::{BuildResult, Entry, NavItem, OutputEntry},
+};
+
+const KEYWORDS: &[&str] = &[
+    "alignas",
+    "alignof",
+    "auto",
+    "bool",
+    "break",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "consteval",
+    "constexpr",
+    "constinit",
+    "const_cast",
+    "continue",
+    "co_await",
+    "co_return",
+    "co_yield",
+    "decltype",
+    "default",
+    "delete",
+    "do",
+    "double",
+    "dynamic_cast",
+    "else",
+    "enum",
+    "explicit",
+    "export",
+    "extern",
+    "false",
+    "final",
+    "float",
+    "for",
+    "friend",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "mutable",
+    "namespace",
+    "new",
+    "noexcept",
+    "nullptr",
+    "operator",
+    "override",
+    "private",
+    "protected",
+    "public",
+    "register",
+    "reinterpret_cast",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "static_assert",
+    "static_cast",
+    "struct",
+    "switch",
+    "template",
+    "this",
+    "thread_local",
+    "throw",
+    "true",
+    "try",
+    "typedef",
+    "typeid",
+    "typename",
+    "union",
+    "unsigned",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "while",
+];
+
+/// A syntax-highlighted copy of a header, with anchors for every line so
+/// entity pages can link to where they are defined
+pub struct SourceFile {
+    source: Arc<Source>,
+    path: UrlPath,
+}
+
+impl SourceFile {
+    pub fn new(source: Arc<Source>, path: UrlPath) -> Self {
+        Self { source, path }
+    }
+
+    /// Get the URL of the source browser page for a header, given its path
+    /// relative to the input directory
+    pub fn url_for(header: &UrlPath) -> UrlPath {
+        UrlPath::parse("source").unwrap().join(header)
+    }
+}
+
+impl<'e> Entry<'e> for SourceFile {
+    fn name(&self) -> String {
+        self.path.raw_file_name().unwrap()
+    }
+
+    fn url(&self) -> UrlPath {
+        Self::url_for(&self.source.dir.join(&self.path))
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("code", false)), Vec::new())
+    }
+}
+
+impl<'e> OutputEntry<'e> for SourceFile {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+        let path = self.source.dir.join(&self.path);
+        let data = fs::read_to_string(builder.config.input_dir.join(path.to_raw_string()))
+            .unwrap_or_default();
+
+        (
+            builder.config.templates.source.clone(),
+            vec![
+                ("name", HtmlText::new(self.name()).into()),
+                ("file_path", HtmlText::new(path.to_raw_string()).into()),
+                (
+                    "file_url",
+                    HtmlText::new(builder.config.source_url(&path, None).unwrap_or_default())
+                        .into(),
+                ),
+                ("source", fmt_source_lines(&data)),
+            ],
+        )
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!(
+            "Source code of {} in {}",
+            self.path, builder.config.project.name
+        )
+    }
+
+    fn breadcrumbs(&self, _builder: &'e Builder<'e>) -> Vec<(String, Option<UrlPath>)> {
+        let mut crumbs = dir_breadcrumbs(&self.path);
+        crumbs.push((
+            self.name(),
+            Some(UrlPath::parse("files").unwrap().join(&self.path)),
+        ));
+        crumbs
+    }
+
+    fn schema_type(&self) -> &'static str {
+        "SoftwareSourceCode"
+    }
+
+    fn source(&self) -> Option<String> {
+        Some(self.source.dir.join(&self.path).to_raw_string())
+    }
+}
+
+/// Format a file of C++ as highlighted lines, each with an anchor like `#L12`
+pub fn fmt_source_lines(data: &str) -> Html {
+    let mut in_comment = false;
+    let lines = data
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let num = i + 1;
+            HtmlElement::new("span")
+                .with_class("source-line")
+                .with_attr("id", format!("L{num}"))
+                .with_child(
+                    HtmlElement::new("a")
+                        .with_class("line-number")
+                        .with_attr("href", format!("#L{num}"))
+                        .with_text(num.to_string()),
+                )
+                .with_children(highlight_line(line, &mut in_comment))
+                .with_text("\n")
+                .into()
+        })
+        .collect::<Vec<Html>>();
+    HtmlElement::new("pre")
+        .with_class("source-browser")
+        .with_child(HtmlElement::new("code").with_child(HtmlList::new(lines)))
+        .into()
+}
+
+/// Split a line of C++ into highlighted spans using the same classes as Prism,
+/// keeping track of whether a block comment continues onto the next line
+fn highlight_line(line: &str, in_comment: &mut bool) -> Vec<Html> {
+    let mut res = Vec::new();
+    let chars = line.char_indices().collect::<Vec<_>>();
+    let at = |i: usize| chars.get(i).map(|c| c.1);
+    let slice = |from: usize, to: usize| {
+        let start = chars.get(from).map(|c| c.0).unwrap_or(line.len());
+        let end = chars.get(to).map(|c| c.0).unwrap_or(line.len());
+        &line[start..end]
+    };
+    let token = |class: &str, text: &str| -> Html {
+        HtmlElement::new("span")
+            .with_classes(&["token", class])
+            .with_text(text)
+            .into()
+    };
+
+    // Preprocessor directives are highlighted as a whole
+    if !*in_comment && line.trim_start().starts_with('#') {
+        return vec![token("macro", line)];
+    }
+
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i].1;
+        if *in_comment {
+            while i < chars.len() && !(at(i) == Some('*') && at(i + 1) == Some('/')) {
+                i += 1;
+            }
+            if i < chars.len() {
+                i += 2;
+                *in_comment = false;
+            }
+            res.push(token("comment", slice(start, i)));
+        } else if c == '/' && at(i + 1) == Some('/') {
+            res.push(token("comment", slice(start, chars.len())));
+            break;
+        } else if c == '/' && at(i + 1) == Some('*') {
+            *in_comment = true;
+            i += 2;
+            while i < chars.len() && !(at(i) == Some('*') && at(i + 1) == Some('/')) {
+                i += 1;
+            }
+            if i < chars.len() {
+                i += 2;
+                *in_comment = false;
+            }
+            res.push(token("comment", slice(start, i)));
+        } else if c == '"' || c == '\'' {
+            i += 1;
+            while i < chars.len() && chars[i].1 != c {
+                if chars[i].1 == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i = (i + 1).min(chars.len());
+            res.push(token("string", slice(start, i)));
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].1.is_ascii_alphanumeric() || chars[i].1 == '.') {
+                i += 1;
+            }
+            res.push(token("number", slice(start, i)));
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].1.is_alphanumeric() || chars[i].1 == '_') {
+                i += 1;
+            }
+            let word = slice(start, i);
+            if KEYWORDS.contains(&word) {
+                res.push(token("keyword", word));
+            } else {
+                res.push(HtmlText::new(word).into());
+            }
+        } else if c.is_whitespace() {
+            while i < chars.len() && chars[i].1.is_whitespace() {
+                i += 1;
+            }
+            res.push(HtmlText::new(slice(start, i)).into());
+        } else {
+            i += 1;
+            res.push(token("punctuation", slice(start, i)));
+        }
+    }
+    res
+}
@@ -0,0 +1,193 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::{
+    config::Config,
+    html::{Html, HtmlElement, HtmlText},
+    url::UrlPath,
+};
+
+use super::{
+    builder::Builder,
+    shared::fmt_section,
+    traits::{BuildResult, Entry, NavItem, OutputEntry},
+    tutorial::TutorialFolder,
+};
+
+/// A listed tutorial with the tags from its metadata
+pub struct TaggedTutorial {
+    pub title: String,
+    pub url: UrlPath,
+    pub tags: Vec<String>,
+}
+
+fn tag_url(tag: &str) -> UrlPath {
+    UrlPath::parse("tags").unwrap().join(UrlPath::part(tag))
+}
+
+fn fmt_tag_link(config: Arc<Config>, tag: &str) -> HtmlElement {
+    HtmlElement::new("a")
+        .with_class("tag")
+        .with_attr("href", tag_url(tag).to_absolute(config))
+        .with_attr("data-pagefind-filter", "tag")
+        .with_text(tag)
+}
+
+/// Format the tags of a tutorial as links to their pages
+pub fn fmt_tags(config: Arc<Config>, tags: &[String]) -> Html {
+    if tags.is_empty() {
+        return Html::empty();
+    }
+    HtmlElement::new("div")
+        .with_class("tags")
+        .with_child(Html::feather("tag"))
+        .with_children(
+            tags.iter()
+                .map(|tag| fmt_tag_link(config.clone(), tag).into())
+                .collect(),
+        )
+        .into()
+}
+
+/// Format links to every tag, sized by how many tutorials have it
+pub fn fmt_tag_cloud(config: Arc<Config>, tutorials: &[TaggedTutorial]) -> Html {
+    let mut counts = BTreeMap::<&str, usize>::new();
+    for tag in tutorials.iter().flat_map(|t| t.tags.iter()) {
+        *counts.entry(tag).or_default() += 1;
+    }
+    if counts.is_empty() {
+        return Html::empty();
+    }
+    let max = counts.values().copied().max().unwrap_or(1);
+
+    HtmlElement::new("div")
+        .with_class("section")
+        .with_child(HtmlElement::new("h2").with_text(config.tr("tags")))
+        .with_child(
+            HtmlElement::new("div")
+                .with_class("tag-cloud")
+                .with_children(
+                    counts
+                        .into_iter()
+                        .map(|(tag, count)| {
+                            // Classes instead of inline styles, which strict
+                            // content security policies block
+                            let size = 1 + 4 * (count - 1) / (max - 1).max(1);
+                            fmt_tag_link(config.clone(), tag)
+                                .with_class(&format!("size-{size}"))
+                                .with_child(Html::span(&["badge"], &count.to_string()))
+                                .into()
+                        })
+                        .collect(),
+                ),
+        )
+        .into()
+}
+
+/// A page listing all tutorials with a tag
+pub struct Tag {
+    name: String,
+    tutorials: Vec<(String, UrlPath)>,
+}
+
+impl<'e> Entry<'e> for Tag {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn url(&self) -> UrlPath {
+        tag_url(&self.name)
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("tag", false)), Vec::new())
+    }
+}
+
+impl<'e> OutputEntry<'e> for Tag {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+        (
+            builder.config.templates.tutorial_index.clone(),
+            vec![
+                ("title", HtmlText::new(format!("Tag: {}", self.name)).into()),
+                (
+                    "links",
+                    fmt_section(
+                        &builder.config.tr("pages"),
+                        self.tutorials
+                            .iter()
+                            .map(|(title, url)| {
+                                HtmlElement::new("ul")
+                                    .with_child(HtmlElement::new("li").with_child(
+                                        HtmlElement::new("a").with_text(title).with_attr(
+                                            "href",
+                                            url.to_absolute(builder.config.clone()),
+                                        ),
+                                    ))
+                                    .into()
+                            })
+                            .collect(),
+                    ),
+                ),
+            ],
+        )
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!(
+            "Tutorials tagged {} in {}",
+            self.name, builder.config.project.name
+        )
+    }
+}
+
+pub struct Tags {
+    tags: Vec<Tag>,
+}
+
+impl Tags {
+    pub fn new(tutorials: &TutorialFolder) -> Self {
+        let mut tags = BTreeMap::<String, Vec<(String, UrlPath)>>::new();
+        for tut in tutorials.tagged_tutorials() {
+            for tag in tut.tags {
+                tags.entry(tag)
+                    .or_default()
+                    .push((tut.title.clone(), tut.url.clone()));
+            }
+        }
+        Self {
+            tags: tags
+                .into_iter()
+                .map(|(name, mut tutorials)| {
+                    tutorials.sort_by(|a, b| a.0.cmp(&b.0));
+                    Tag { name, tutorials }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<'e> Entry<'e> for Tags {
+    fn name(&self) -> String {
+        String::from("Tags")
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::parse("tags").unwrap()
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        let mut handles = Vec::new();
+        for tag in &self.tags {
+            handles.extend(tag.build(builder)?);
+        }
+        Ok(handles)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_root(None, self.tags.iter().map(|t| t.nav()).collect())
+    }
+}
@@ -1,17 +1,157 @@
-use clang::{Accessibility, Entity, EntityKind};
+use clang::{Accessibility, Entity, EntityKind, EntityVisitResult};
 use serde_json::json;
 
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+};
 
 use tokio::task::JoinHandle;
 
 use crate::{
     config::{Config, ExternalLib, Source},
+    diagnostics::{WarningKind, report},
     html::Html,
-    url::UrlPath,
+    url::{UrlPath, strip_path_prefix},
 };
 
-use super::{builder::Builder, namespace::CppItemKind};
+use super::{builder::Builder, namespace::CppItemKind, operators::operator_slug};
+
+/// The platforms that entities which aren't declared on every platform in
+/// `analysis.platforms` are declared on, by USR
+static PLATFORMS: OnceLock<HashMap<String, Vec<String>>> = OnceLock::new();
+
+/// Namespaces shown under other names, and namespaces whose contents are shown
+/// as if they were in their parent, from the `namespaces` config
+static NAMESPACE_ALIASES: OnceLock<(HashMap<String, String>, Vec<String>)> = OnceLock::new();
+
+/// Set how namespaces are renamed and collapsed in names, URLs and the navbar
+pub fn set_namespace_aliases(rename: HashMap<String, String>, collapse: Vec<String>) {
+    let _ = NAMESPACE_ALIASES.set((rename, collapse));
+}
+
+/// Collect the USRs of the entities in a namespace and of the members of its
+/// classes, for finding out which platforms they are declared on
+pub fn collect_usrs(entity: &Entity, out: &mut HashSet<String>) {
+    entity.visit_children(|child, _| {
+        if child.is_in_system_header() {
+            return EntityVisitResult::Continue;
+        }
+        if let Some(usr) = child.get_usr() {
+            out.insert(usr.0);
+        }
+        match child.get_kind() {
+            EntityKind::Namespace
+            | EntityKind::ClassDecl
+            | EntityKind::StructDecl
+            | EntityKind::ClassTemplate
+            | EntityKind::ClassTemplatePartialSpecialization
+            | EntityKind::EnumDecl => EntityVisitResult::Recurse,
+            _ => EntityVisitResult::Continue,
+        }
+    });
+}
+
+/// Remember which of the platforms each entity is declared on, given the
+/// USRs found when parsing for each of them
+pub fn set_platforms(platforms: Vec<(String, HashSet<String>)>) {
+    let mut res: HashMap<String, Vec<String>> = HashMap::new();
+    for (name, usrs) in &platforms {
+        for usr in usrs {
+            res.entry(usr.clone()).or_default().push(name.clone());
+        }
+    }
+    res.retain(|_, found| found.len() < platforms.len());
+    PLATFORMS.set(res).ok();
+}
+
+/// Get the platforms an entity is declared on, if it isn't on all of them
+pub fn entity_platforms(entity: &Entity) -> Option<&'static [String]> {
+    PLATFORMS
+        .get()?
+        .get(&entity.get_usr()?.0)
+        .map(|found| found.as_slice())
+}
+
+/// Give docs URLs that only differ in case a numbered suffix, like
+/// `classes/myclass-2`, since they would be written to the same files on
+/// case-insensitive filesystems like the ones on Windows and macOS. The first
+/// URL in sorted order keeps its path, so the result doesn't depend on the
+/// order entities were found in
+pub fn resolve_url_collisions(config: &Config, urls: Vec<UrlPath>) {
+    let mut by_folded = BTreeMap::<String, Vec<UrlPath>>::new();
+    for url in urls {
+        by_folded
+            .entry(url.to_raw_string().to_lowercase())
+            .or_default()
+            .push(url);
+    }
+    let mut overrides = HashMap::new();
+    for mut group in by_folded.into_values() {
+        group.sort_unstable_by_key(|url| url.to_raw_string());
+        group.dedup();
+        for (i, url) in group.iter().enumerate().skip(1) {
+            let renamed = url.clone().append_to_last(&format!("-{}", i + 1));
+            report(
+                WarningKind::UrlCollision,
+                ("", 0),
+                format_args!(
+                    "Output path {url} collides with {} on case-insensitive filesystems, \
+                    using {renamed} instead",
+                    group[0]
+                ),
+            );
+            overrides.insert(url.to_raw_string(), renamed);
+        }
+    }
+    let _ = config.url_overrides.set(overrides);
+}
+
+/// Get the URL a docs page is actually output to, which is different if it
+/// collided with another one
+pub fn resolve_docs_url(config: &Config, url: UrlPath) -> UrlPath {
+    config
+        .url_overrides
+        .get()
+        .and_then(|o| o.get(&url.to_raw_string()))
+        .cloned()
+        .unwrap_or(url)
+}
+
+/// Get the docs URL of an entity before collisions are resolved
+pub fn unresolved_docs_url(entity: &Entity, config: &Config) -> Option<UrlPath> {
+    let mut name = entity.full_name();
+    // Operators like operator<< make for awkward URLs
+    if let Some(last) = name.last_mut()
+        && let Some(slug) = operator_slug(last)
+    {
+        *last = slug;
+    }
+    if config.lowercase_urls {
+        name = name.iter().map(|part| lowercase_slug(part)).collect();
+    }
+    Some(
+        CppItemKind::from(entity)?
+            .docs_category(config)
+            .join(UrlPath::new_with_path(name)),
+    )
+}
+
+/// Lowercase a part of a docs URL, adding a short hash of the original if
+/// that changed it so names that only differ in case still get their own pages
+fn lowercase_slug(part: &str) -> String {
+    let lower = part.to_lowercase();
+    if lower == part {
+        return lower;
+    }
+    // FNV-1a, since the hash is part of the URL and has to be the same with
+    // every build and toolchain
+    let hash = part.bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{lower}-{}", &format!("{hash:016x}")[..8])
+}
 
 pub trait EntityMethods<'e> {
     /// Get the config source for this entity
@@ -23,13 +163,17 @@ pub trait EntityMethods<'e> {
     /// Get a relative path to this file's header, if applicable
     fn header(&self, config: Arc<Config>) -> Option<PathBuf>;
 
+    /// Get the file and line this entity is declared at, for diagnostics
+    fn source_location(&self) -> (String, u32);
+
     /// Get the relative for this entity
-    fn rel_docs_url(&self) -> Option<UrlPath>;
+    fn rel_docs_url(&self, config: Arc<Config>) -> Option<UrlPath>;
 
     /// Get the full URL for this entity, valid for links
     fn abs_docs_url(&self, config: Arc<Config>) -> Option<UrlPath>;
 
-    /// Get the full online URL of this entity
+    /// Get the full online URL of this entity, pointing to the line it is
+    /// declared at
     fn github_url(&self, config: Arc<Config>) -> Option<String>;
 
     /// Get the include path for this entity
@@ -45,6 +189,10 @@ pub trait EntityMethods<'e> {
     fn get_member_functions(&self, visibility: Access, include_statics: Include)
         -> Vec<Entity<'e>>;
 
+    /// Gets the constructors from this entity, including templated ones,
+    /// assuming it is a class-like entity
+    fn get_constructors(&self, visibility: Access) -> Vec<Entity<'e>>;
+
     /// Gets the function arguments for this method, including templated ones
     fn get_function_arguments(&self) -> Option<Vec<Entity<'e>>>;
 
@@ -57,6 +205,25 @@ pub trait EntityMethods<'e> {
 
     /// Checks if the entitiy is in one of the allowed external libraries
     fn get_allowed_external_lib(&self, config: Arc<Config>) -> Option<Arc<ExternalLib>>;
+
+    /// Get the spelling of the tokens that make up this entity's declaration,
+    /// stopping at the body if there is one
+    fn declaration_tokens(&self) -> Vec<String>;
+
+    /// Checks if this is a `const`, `constexpr`, or `inline` variable
+    fn is_constant_variable(&self) -> bool;
+
+    /// Check if this entity should be left out of the docs, either because
+    /// it is marked `@internal` or because it has one of the attributes or
+    /// macros listed in `analysis.hide-attributes`
+    fn is_hidden(&self, config: Arc<Config>) -> bool;
+
+    /// Check if this entity is declared with the project's export macro or
+    /// one of the attributes in `analysis.require-attributes`, if any are set
+    fn is_exported(&self, config: Arc<Config>) -> bool;
+
+    /// Check if this is a full or partial specialization of a class template
+    fn is_specialization(&self) -> bool;
 }
 
 impl<'e> EntityMethods<'e> for Entity<'e> {
@@ -81,33 +248,41 @@ impl<'e> EntityMethods<'e> for Entity<'e> {
             .into()
     }
 
+    fn source_location(&self) -> (String, u32) {
+        self.get_location()
+            .map(|loc| loc.get_file_location())
+            .and_then(|loc| Some((loc.file?.get_path().to_string_lossy().to_string(), loc.line)))
+            .unwrap_or_default()
+    }
+
     fn header(&self, config: Arc<Config>) -> Option<PathBuf> {
         let path = self.definition_file()?;
-        path.strip_prefix(&config.input_dir)
-            .unwrap_or(&path)
-            .to_path_buf()
+        strip_path_prefix(&path, &config.input_dir)
+            .unwrap_or(path)
             .into()
     }
 
-    fn rel_docs_url(&self) -> Option<UrlPath> {
-        Some(
-            CppItemKind::from(self)?
-                .docs_category()
-                .join(UrlPath::new_with_path(self.full_name())),
-        )
+    fn rel_docs_url(&self, config: Arc<Config>) -> Option<UrlPath> {
+        // Specializations are documented on the primary template's page
+        if self.is_specialization() {
+            return Some(
+                self.get_template()?
+                    .rel_docs_url(config)?
+                    .with_fragment(&specialization_anchor(self)),
+            );
+        }
+        Some(resolve_docs_url(
+            &config,
+            unresolved_docs_url(self, &config)?,
+        ))
     }
 
     fn abs_docs_url(&self, config: Arc<Config>) -> Option<UrlPath> {
         // If this is an std item, redirect to cppreference instead
         if self.full_name().first().is_some_and(|n| n == "std") {
-            UrlPath::parse(&format!(
-                "en.cppreference.com/w/cpp/{}/{}",
-                self.definition_file()?.file_name()?.to_str()?,
-                self.get_name()?
-            ))
-            .ok()
+            Some(config.cppreference_url(&self.full_name().join("::")))
         } else {
-            Some(self.rel_docs_url()?.to_absolute(config))
+            Some(self.rel_docs_url(config.clone())?.to_absolute(config))
         }
     }
 
@@ -118,15 +293,16 @@ impl<'e> EntityMethods<'e> for Entity<'e> {
                 self.get_name()
             )
         } else if let Some(lib) = self.get_allowed_external_lib(config.clone()) {
-            Some(lib.repository.clone())
+            let location = self.get_location()?.get_file_location();
+            Some(lib.url_for(
+                &self.full_name().join("::"),
+                &location.file?.get_path().to_string_lossy(),
+                location.line,
+            ))
         } else {
-            Some(
-                config.project.tree.clone()?
-                    + UrlPath::try_from(&self.header(config)?)
-                        .ok()?
-                        .to_string()
-                        .as_str(),
-            )
+            let path = UrlPath::try_from(&self.header(config.clone())?).ok()?;
+            let line = self.get_location().map(|loc| loc.get_file_location().line);
+            config.source_url(&path, line)
         }
     }
 
@@ -147,10 +323,39 @@ impl<'e> EntityMethods<'e> for Entity<'e> {
     }
 
     fn full_name(&self) -> Vec<String> {
-        self.ancestorage()
-            .iter()
-            .map(|a| a.get_name().unwrap_or("_anon".into()))
-            .collect()
+        let Some((rename, collapse)) = NAMESPACE_ALIASES.get() else {
+            return self
+                .ancestorage()
+                .iter()
+                .map(|a| a.get_name().unwrap_or("_anon".into()))
+                .collect();
+        };
+        // Rules match the original path of a namespace, even if one of its
+        // parents was renamed or collapsed
+        let mut path = Vec::new();
+        let mut res = Vec::new();
+        for ancestor in self.ancestorage() {
+            let name = ancestor.get_name().unwrap_or("_anon".into());
+            path.push(name.clone());
+            if ancestor.get_kind() != EntityKind::Namespace {
+                res.push(name);
+                continue;
+            }
+            let original = path.join("::");
+            if collapse.iter().any(|c| c == &original || c == &name) {
+                continue;
+            }
+            if let Some(to) = rename.get(&original) {
+                res = to
+                    .split("::")
+                    .filter(|part| !part.is_empty())
+                    .map(String::from)
+                    .collect();
+                continue;
+            }
+            res.push(name);
+        }
+        res
     }
 
     fn ancestorage(&self) -> Vec<Entity<'e>> {
@@ -186,22 +391,28 @@ impl<'e> EntityMethods<'e> for Entity<'e> {
         self.get_children()
             .into_iter()
             .filter(|child| {
-                (child.get_kind() == EntityKind::Method
-                    || child.get_kind() == EntityKind::FunctionTemplate)
+                (matches!(
+                    child.get_kind(),
+                    EntityKind::Method | EntityKind::ConversionFunction
+                ) || (child.get_kind() == EntityKind::FunctionTemplate
+                    && child.get_template_kind() != Some(EntityKind::Constructor)))
                     && match include_statics {
                         Include::Members => !child.is_static_method(),
                         Include::Statics => child.is_static_method(),
                         Include::All => true,
                     }
-                    && match child.get_accessibility() {
-                        Some(Accessibility::Protected) => {
-                            matches!(visibility, Access::All | Access::Protected)
-                        }
-                        Some(Accessibility::Public) => {
-                            matches!(visibility, Access::All | Access::Public)
-                        }
-                        _ => false,
-                    }
+                    && has_access(child, &visibility)
+            })
+            .collect()
+    }
+
+    fn get_constructors(&self, visibility: Access) -> Vec<Entity<'e>> {
+        self.get_children()
+            .into_iter()
+            .filter(|child| {
+                (child.get_kind() == EntityKind::Constructor
+                    || child.get_template_kind() == Some(EntityKind::Constructor))
+                    && has_access(child, &visibility)
             })
             .collect()
     }
@@ -209,7 +420,11 @@ impl<'e> EntityMethods<'e> for Entity<'e> {
     fn get_function_arguments(&self) -> Option<Vec<Entity<'e>>> {
         if !matches!(
             self.get_kind(),
-            EntityKind::FunctionTemplate | EntityKind::FunctionDecl | EntityKind::Method
+            EntityKind::FunctionTemplate
+                | EntityKind::FunctionDecl
+                | EntityKind::Method
+                | EntityKind::Constructor
+                | EntityKind::ConversionFunction
         ) {
             return None;
         }
@@ -262,6 +477,122 @@ impl<'e> EntityMethods<'e> for Entity<'e> {
             })
             .cloned()
     }
+
+    fn declaration_tokens(&self) -> Vec<String> {
+        self.get_range()
+            .map(|range| {
+                range
+                    .tokenize()
+                    .into_iter()
+                    .map(|t| t.get_spelling())
+                    .take_while(|t| t != "{")
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn is_hidden(&self, config: Arc<Config>) -> bool {
+        if self.get_comment().is_some_and(|c| {
+            c.split_whitespace()
+                .any(|word| word == "@internal" || word == "\\internal")
+        }) {
+            return true;
+        }
+        has_any_attribute(self, &config.analysis.hide_attributes)
+    }
+
+    fn is_exported(&self, config: Arc<Config>) -> bool {
+        let required = config
+            .analysis
+            .require_macro
+            .iter()
+            .chain(&config.analysis.require_attributes)
+            .cloned()
+            .collect::<Vec<_>>();
+        if required.is_empty() {
+            return true;
+        }
+        // Templates are instantiated in the user's code, so they never need
+        // to be exported
+        matches!(
+            self.get_kind(),
+            EntityKind::ClassTemplate
+                | EntityKind::ClassTemplatePartialSpecialization
+                | EntityKind::FunctionTemplate
+        ) || has_any_attribute(self, &required)
+    }
+
+    fn is_constant_variable(&self) -> bool {
+        self.get_kind() == EntityKind::VarDecl
+            && (self.get_type().is_some_and(|t| t.is_const_qualified())
+                || self
+                    .declaration_tokens()
+                    .iter()
+                    .take_while(|t| *t != "=")
+                    .any(|t| t == "constexpr" || t == "inline"))
+    }
+
+    fn is_specialization(&self) -> bool {
+        match self.get_kind() {
+            EntityKind::ClassTemplatePartialSpecialization => true,
+            EntityKind::ClassDecl | EntityKind::StructDecl => self.get_template().is_some(),
+            _ => false,
+        }
+    }
+}
+
+/// Check if an entity has one of some attributes or macros
+fn has_any_attribute(entity: &Entity, attrs: &[String]) -> bool {
+    if attrs.is_empty() {
+        return false;
+    }
+    // Macros are gone by the time LibClang sees the attributes, so check
+    // the tokens as written as well
+    entity
+        .declaration_tokens()
+        .iter()
+        .any(|t| attrs.contains(t))
+        || entity.get_children().iter().any(|child| {
+            child.is_attribute()
+                && child
+                    .get_display_name()
+                    .is_some_and(|name| attrs.contains(&name))
+        })
+}
+
+fn has_access(entity: &Entity, visibility: &Access) -> bool {
+    match entity.get_accessibility() {
+        Some(Accessibility::Protected) => matches!(visibility, Access::All | Access::Protected),
+        Some(Accessibility::Public) => matches!(visibility, Access::All | Access::Public),
+        Some(Accessibility::Private) => matches!(visibility, Access::Private),
+        _ => false,
+    }
+}
+
+/// Get the anchor of a template specialization on its primary template's
+/// page, like `vec-int` for `Vec<int>` or `vec-t-ptr` for `Vec<T*>`
+pub fn specialization_anchor(entity: &Entity) -> String {
+    let name = entity.get_display_name().unwrap_or_default();
+    let mut anchor = String::new();
+    for c in name.chars() {
+        let part = match c {
+            '*' => "ptr",
+            '&' => "ref",
+            c if c.is_alphanumeric() => {
+                anchor.extend(c.to_lowercase());
+                continue;
+            }
+            _ => "",
+        };
+        if !anchor.is_empty() && !anchor.ends_with('-') {
+            anchor.push('-');
+        }
+        if !part.is_empty() {
+            anchor.push_str(part);
+            anchor.push('-');
+        }
+    }
+    anchor.trim_end_matches('-').to_owned()
 }
 
 #[derive(Clone)]
@@ -285,13 +616,16 @@ impl SubItem {
                 })
                 .collect(),
 
-            CppItemKind::Namespace | CppItemKind::Function => Vec::new(),
+            CppItemKind::Namespace | CppItemKind::Function | CppItemKind::Variable => Vec::new(),
         }
     }
 }
 
 pub enum NavItem {
     Root(Option<String>, Vec<NavItem>),
+    /// A named root with an icon and a description, for the entities of a
+    /// source
+    Section(String, Option<(String, bool)>, Option<String>, Vec<NavItem>),
     Dir(String, Vec<NavItem>, Option<(String, bool)>, bool),
     Link(String, UrlPath, Option<(String, bool)>, Vec<SubItem>),
 }
@@ -328,6 +662,16 @@ impl NavItem {
         NavItem::Root(name.map(|s| s.into()), items)
     }
 
+    /// Create the navbar section of a source, titled with its `nav_title`
+    pub fn new_section(source: &Source, items: Vec<NavItem>) -> NavItem {
+        NavItem::Section(
+            source.nav_title.clone().unwrap_or(source.name.clone()),
+            source.icon.clone().map(|icon| (icon, false)),
+            source.description.clone(),
+            items,
+        )
+    }
+
     pub fn suboptions_titles(&self, config: Arc<Config>) -> HashMap<String, usize> {
         match self {
             NavItem::Link(name, _, _, suboptions) => {
@@ -348,7 +692,7 @@ impl NavItem {
                 .map(|(t, count)| (format!("{}::{}", name, t), count))
                 .collect(),
 
-            NavItem::Root(_, items) => items
+            NavItem::Root(_, items) | NavItem::Section(_, _, _, items) => items
                 .iter()
                 .flat_map(|i| i.suboptions_titles(config.clone()))
                 .collect(),
@@ -358,11 +702,18 @@ impl NavItem {
     pub fn to_json(&self, config: Arc<Config>) -> serde_json::Value {
         match self {
             NavItem::Link(name, url, icon, _) => {
+                // Relative links are resolved against the output root by
+                // the nav script
+                let url = if config.relative_links {
+                    url.url_safe_parts().join("/")
+                } else {
+                    url.to_absolute(config.clone()).to_string()
+                };
                 json!({
                     "type": "link",
                     "icon": icon,
                     "name": name,
-                    "url": url.to_absolute(config.clone()).to_string(),
+                    "url": url,
                 })
             }
 
@@ -383,6 +734,16 @@ impl NavItem {
                     "items": items.iter().map(|x| x.to_json(config.clone())).collect::<Vec<_>>()
                 })
             }
+
+            NavItem::Section(name, icon, description, items) => {
+                json!({
+                    "type": "section",
+                    "icon": icon,
+                    "name": name,
+                    "description": description,
+                    "items": items.iter().map(|x| x.to_json(config.clone())).collect::<Vec<_>>()
+                })
+            }
         }
     }
 }
@@ -399,6 +760,28 @@ pub trait Entry<'e> {
 pub trait OutputEntry<'e>: Entry<'e> {
     fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>);
     fn description(&self, builder: &'e Builder<'e>) -> String;
+    /// Plain markdown version of the page, output alongside it for LLM-based
+    /// tools when enabled
+    fn markdown(&self, _builder: &'e Builder<'e>) -> Option<String> {
+        None
+    }
+    /// The schema.org type of the page for its structured data
+    fn schema_type(&self) -> &'static str {
+        "WebPage"
+    }
+    /// Pages leading up to this one, shown as breadcrumbs at the top of it
+    fn breadcrumbs(&self, _builder: &'e Builder<'e>) -> Vec<(String, Option<UrlPath>)> {
+        Vec::new()
+    }
+    /// Hook commands to run once the page has been written
+    fn post_hooks(&self, _config: &Config) -> Vec<String> {
+        Vec::new()
+    }
+    /// What the page was made from, listed in the manifest, like the USR of
+    /// an entity or the path of a file relative to the input directory
+    fn source(&self) -> Option<String> {
+        None
+    }
 }
 
 pub trait ASTEntry<'e>: Entry<'e> {
@@ -412,12 +795,18 @@ pub trait ASTEntry<'e>: Entry<'e> {
             builder.config.project.name
         )
     }
+    /// Full and partial specializations documented on this entry's page
+    fn specializations(&self) -> &[Entity<'e>] {
+        &[]
+    }
 }
 
 pub enum Access {
+    /// Public and protected members
     All,
     Public,
     Protected,
+    Private,
 }
 
 pub enum Include {
@@ -1,42 +1,110 @@
-use log::info;
+use log::{debug, info, warn};
 
 use crate::{
     config::Config,
-    html::{Html, HtmlElement},
-    url::UrlPath,
+    html::{Html, HtmlElement, HtmlList},
+    url::{UrlPath, strip_path_prefix},
+};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    ffi::OsStr,
+    fs,
+    path::PathBuf,
+    sync::Arc,
 };
-use std::{cmp::Ordering, collections::HashMap, ffi::OsStr, fs, path::PathBuf, sync::Arc};
 
 use super::{
     builder::Builder,
-    markdown::{extract_metadata_from_md, output_tutorial, Metadata},
+    feed::{FeedEntry, git_dates, normalize_date},
+    markdown::{
+        Metadata, expand_includes, extract_metadata_from_md, fmt_series_nav, output_tutorial,
+        strip_metadata_from_md,
+    },
     shared::fmt_section,
+    tags::{TaggedTutorial, fmt_tag_cloud},
     traits::{BuildResult, Entry, NavItem, OutputEntry},
 };
 
+/// A tutorial in a series, for listing the series and linking between its
+/// parts
+pub struct SeriesPart {
+    pub title: String,
+    pub url: UrlPath,
+    index: Option<usize>,
+}
+
+/// Where a tree of tutorials is read from and where its pages are output
+struct TutorialRoot {
+    /// Directory of the tree, relative to the input directory
+    dir: PathBuf,
+    /// URL the pages are output under, which is empty for the tutorials in
+    /// the main language of the docs
+    prefix: UrlPath,
+}
+
+/// Get the online URL for a file in a tutorials directory, if the project
+/// has a tree set
+fn tutorial_tree_url(config: Arc<Config>, root: &TutorialRoot, path: &UrlPath) -> Option<String> {
+    let dir = UrlPath::try_from(&root.dir).ok()?;
+    config.source_url(&dir.join(path), None)
+}
+
+/// Get the directory on disk that a path in a tutorials directory is in
+fn tutorial_source_dir(config: Arc<Config>, root: &TutorialRoot, path: &UrlPath) -> PathBuf {
+    let dir = config.input_dir.join(&root.dir).join(path.to_pathbuf());
+    dir.parent().map(|p| p.to_path_buf()).unwrap_or(dir)
+}
+
+/// Get the template a tutorial page picked in its metadata, or the default one
+/// if it didn't pick one or the template can't be read
+fn tutorial_template(
+    config: Arc<Config>,
+    metadata: Option<&Metadata>,
+    default: &Arc<String>,
+) -> Arc<String> {
+    let Some(name) = metadata.and_then(|m| m.template.as_ref()) else {
+        return default.clone();
+    };
+    if config.templates.dir.is_none() {
+        warn!("Page uses template '{name}' but templates.dir is not set");
+        return default.clone();
+    }
+    match config.page_templates.get(name) {
+        Some(template) => template.clone(),
+        None => {
+            warn!("Page uses template '{name}' which isn't in templates.dir");
+            default.clone()
+        }
+    }
+}
+
 pub struct Tutorial {
+    root: Arc<TutorialRoot>,
     path: UrlPath,
     metadata: Metadata,
     unparsed_content: String,
 }
 
 impl Tutorial {
-    pub fn new(config: Arc<Config>, path: UrlPath) -> Self {
-        let unparsed_content = fs::read_to_string(
-            config
-                .input_dir
-                .join(&config.tutorials.as_ref().unwrap().dir)
-                .join(path.to_pathbuf()),
-        )
-        .unwrap_or_else(|_| panic!("Unable to read tutorial {}", path.to_raw_string()));
+    fn new(config: Arc<Config>, root: Arc<TutorialRoot>, path: UrlPath) -> Self {
+        let file = config.input_dir.join(&root.dir).join(path.to_pathbuf());
+        let unparsed_content = fs::read_to_string(&file)
+            .unwrap_or_else(|_| panic!("Unable to read tutorial {}", path.to_raw_string()));
+        let unparsed_content = expand_includes(
+            &unparsed_content,
+            &tutorial_source_dir(config.clone(), &root, &path),
+        );
 
         Self {
             metadata: extract_metadata_from_md(
                 &unparsed_content,
                 path.remove_extension(".md").raw_file_name(),
+                &file,
             )
             .unwrap_or_default(),
             unparsed_content,
+            root,
             path,
         }
     }
@@ -44,11 +112,16 @@ impl Tutorial {
 
 impl<'e> Entry<'e> for Tutorial {
     fn name(&self) -> String {
-        self.metadata.title.clone().unwrap()
+        self.metadata.title.clone().unwrap_or_else(|| {
+            self.path
+                .remove_extension(".md")
+                .raw_file_name()
+                .unwrap_or_default()
+        })
     }
 
     fn url(&self) -> UrlPath {
-        self.path.remove_extension(".md")
+        self.root.prefix.join(self.path.remove_extension(".md"))
     }
 
     fn build(&self, builder: &Builder<'e>) -> BuildResult {
@@ -57,7 +130,7 @@ impl<'e> Entry<'e> for Tutorial {
 
     fn nav(&self) -> NavItem {
         NavItem::new_link(
-            self.metadata.title.as_ref().unwrap(),
+            &self.name(),
             self.url(),
             Some(
                 self.metadata
@@ -74,12 +147,19 @@ impl<'e> Entry<'e> for Tutorial {
 impl<'e> OutputEntry<'e> for Tutorial {
     fn output(&self, builder: &Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
         (
-            builder.config.templates.tutorial.clone(),
+            tutorial_template(
+                builder.config.clone(),
+                Some(&self.metadata),
+                &builder.config.templates.tutorial,
+            ),
             output_tutorial(
                 self,
                 builder,
                 &self.unparsed_content,
-                Html::Raw(String::new()),
+                tutorial_source_dir(builder.config.clone(), &self.root, &self.path),
+                Some(&self.metadata),
+                fmt_series_nav(builder, &self.url()),
+                tutorial_tree_url(builder.config.clone(), &self.root, &self.path),
             ),
         )
     }
@@ -90,9 +170,77 @@ impl<'e> OutputEntry<'e> for Tutorial {
             .clone()
             .unwrap_or(format!("Tutorial for {}", builder.config.project.name))
     }
+
+    fn markdown(&self, _builder: &'e Builder<'e>) -> Option<String> {
+        Some(strip_metadata_from_md(&self.unparsed_content).to_owned())
+    }
+
+    fn breadcrumbs(&self, builder: &'e Builder<'e>) -> Vec<(String, Option<UrlPath>)> {
+        builder.tutorial_breadcrumbs(&self.url())
+    }
+
+    fn schema_type(&self) -> &'static str {
+        "TechArticle"
+    }
+
+    fn post_hooks(&self, config: &Config) -> Vec<String> {
+        config
+            .run
+            .as_ref()
+            .map(|r| r.posttutorial.clone())
+            .unwrap_or_default()
+    }
+
+    fn source(&self) -> Option<String> {
+        Some(
+            self.root
+                .dir
+                .join(self.path.to_pathbuf())
+                .to_string_lossy()
+                .replace('\\', "/"),
+        )
+    }
+}
+
+/// List every series of tutorials in order, for the tutorials index
+fn fmt_series_sections(config: Arc<Config>, series: &BTreeMap<String, Vec<SeriesPart>>) -> Html {
+    HtmlList::new(
+        series
+            .iter()
+            .map(|(name, parts)| {
+                fmt_section(
+                    name,
+                    vec![
+                        HtmlElement::new("ol")
+                            .with_class("series-parts")
+                            .with_children(
+                                parts
+                                    .iter()
+                                    .map(|part| {
+                                        HtmlElement::new("li")
+                                            .with_child(
+                                                HtmlElement::new("a")
+                                                    .with_text(&part.title)
+                                                    .with_attr(
+                                                        "href",
+                                                        part.url.to_absolute(config.clone()),
+                                                    ),
+                                            )
+                                            .into()
+                                    })
+                                    .collect(),
+                            )
+                            .into(),
+                    ],
+                )
+            })
+            .collect(),
+    )
+    .into()
 }
 
 pub struct TutorialFolder {
+    root: Arc<TutorialRoot>,
     is_root: bool,
     is_open: bool,
     path: UrlPath,
@@ -103,18 +251,17 @@ pub struct TutorialFolder {
 }
 
 impl TutorialFolder {
-    fn from_folder(config: Arc<Config>, path: &PathBuf, depth: i32) -> Option<Self> {
+    fn from_folder(
+        config: Arc<Config>,
+        root: Arc<TutorialRoot>,
+        path: &PathBuf,
+        depth: i32,
+    ) -> Option<Self> {
         let mut folders = HashMap::new();
         let mut tutorials = HashMap::new();
 
-        let stripped_path = path
-            .strip_prefix(
-                config
-                    .input_dir
-                    .join(&config.tutorials.as_ref().unwrap().dir),
-            )
-            .unwrap_or(path)
-            .to_path_buf();
+        let root_dir = config.input_dir.join(&root.dir);
+        let stripped_path = strip_path_prefix(path, &root_dir).unwrap_or(path.clone());
 
         // find tutorials (markdown files)
         for file in fs::read_dir(path).ok()? {
@@ -128,9 +275,12 @@ impl TutorialFolder {
 
             // if this is a directory, add it only if it has tutorials
             if ty.is_dir() {
-                if let Some(folder) =
-                    TutorialFolder::from_folder(config.clone(), &file.path(), depth + 1)
-                {
+                if let Some(folder) = TutorialFolder::from_folder(
+                    config.clone(),
+                    root.clone(),
+                    &file.path(),
+                    depth + 1,
+                ) {
                     folders.insert(folder.name(), folder);
                 }
             }
@@ -142,38 +292,47 @@ impl TutorialFolder {
                     None => false,
                 }
             {
-                let stripped_path = path
-                    .strip_prefix(
-                        config
-                            .input_dir
-                            .join(&config.tutorials.as_ref().unwrap().dir),
-                    )
-                    .unwrap_or(&path)
-                    .to_path_buf();
+                let stripped_path = strip_path_prefix(&path, &root_dir).unwrap_or(path.clone());
 
                 let Ok(url) = UrlPath::try_from(&stripped_path) else {
                     continue;
                 };
                 info!("creating tutorial for {}", url);
-                let tut = Tutorial::new(config.clone(), url);
+                let tut = Tutorial::new(config.clone(), root.clone(), url);
+                if tut.metadata.draft && !config.drafts {
+                    info!("skipping draft tutorial {}", tut.path);
+                    continue;
+                }
                 tutorials.insert(tut.name(), tut);
             }
         }
 
         let index = if path.join("index.md").exists() {
-            fs::read_to_string(path.join("index.md")).ok()
+            fs::read_to_string(path.join("index.md"))
+                .ok()
+                .map(|index| expand_includes(&index, path))
         } else {
             None
         };
 
+        let metadata = index
+            .as_ref()
+            .and_then(|i| extract_metadata_from_md(i, None, &path.join("index.md")));
+        if metadata.as_ref().is_some_and(|m| m.draft) && !config.drafts {
+            info!(
+                "skipping draft tutorial folder {}",
+                stripped_path.to_string_lossy()
+            );
+            return None;
+        }
+
         // only consider this a tutorial folder if it has some tutorials
         (!folders.is_empty() || !tutorials.is_empty()).then_some(Self {
+            root,
             is_root: false,
             is_open: depth < 2,
             path: UrlPath::try_from(&stripped_path).ok()?,
-            metadata: index
-                .as_ref()
-                .and_then(|i| extract_metadata_from_md(i, None)),
+            metadata,
             index,
             folders,
             tutorials,
@@ -181,14 +340,47 @@ impl TutorialFolder {
     }
 
     pub fn from_config(config: Arc<Config>) -> Self {
-        if let Some(ref tutorials) = config.tutorials
-            && let Some(mut res) =
-                Self::from_folder(config.clone(), &config.input_dir.join(&tutorials.dir), 0)
+        let dir = config
+            .tutorials
+            .as_ref()
+            .map(|t| t.dir.clone())
+            .unwrap_or_default();
+        Self::from_root(config, dir, UrlPath::new())
+    }
+
+    /// Get the tutorials translated to other languages, which are output
+    /// under the code of their locale
+    pub fn from_locales(config: Arc<Config>) -> Vec<Self> {
+        let Some(ref i18n) = config.i18n else {
+            return Vec::new();
+        };
+        i18n.locales
+            .iter()
+            .map(|(code, locale)| {
+                Self::from_root(
+                    config.clone(),
+                    locale.tutorials.clone(),
+                    UrlPath::part(code),
+                )
+            })
+            .collect()
+    }
+
+    fn from_root(config: Arc<Config>, dir: PathBuf, prefix: UrlPath) -> Self {
+        let root = Arc::new(TutorialRoot { dir, prefix });
+        if config.tutorials.is_some()
+            && let Some(mut res) = Self::from_folder(
+                config.clone(),
+                root.clone(),
+                &config.input_dir.join(&root.dir),
+                0,
+            )
         {
             res.is_root = true;
             res
         } else {
             Self {
+                root,
                 is_root: true,
                 is_open: true,
                 path: UrlPath::new(),
@@ -200,8 +392,172 @@ impl TutorialFolder {
         }
     }
 
+    fn is_unlisted(&self) -> bool {
+        self.metadata.as_ref().is_some_and(|m| m.unlisted)
+    }
+
+    /// Check if a tutorial or folder with the given URL is in this folder or
+    /// its subfolders
+    pub fn has_url(&self, url: &UrlPath) -> bool {
+        self.url() == *url
+            || self.tutorials.values().any(|tut| tut.url() == *url)
+            || self.folders.values().any(|dir| dir.has_url(url))
+    }
+
+    /// Get the URL of this tree's root, which is where the same path in
+    /// another language's tree starts from
+    pub fn prefix(&self) -> &UrlPath {
+        &self.root.prefix
+    }
+
+    /// Get the URLs of all unlisted tutorials and folders in this folder,
+    /// including everything in unlisted folders
+    pub fn unlisted_urls(&self) -> Vec<UrlPath> {
+        self.unlisted_urls_impl(false)
+    }
+
+    fn unlisted_urls_impl(&self, parent_unlisted: bool) -> Vec<UrlPath> {
+        let unlisted = parent_unlisted || self.is_unlisted();
+        let mut res = Vec::new();
+        if unlisted {
+            res.push(self.url());
+        }
+        for tut in self.tutorials.values() {
+            if unlisted || tut.metadata.unlisted {
+                res.push(tut.url());
+            }
+        }
+        for dir in self.folders.values() {
+            res.extend(dir.unlisted_urls_impl(unlisted));
+        }
+        res
+    }
+
+    /// Get the titles and URLs of all tutorials and folders in this folder and
+    /// its subfolders, including unlisted ones, for resolving wiki links
+    pub fn pages(&self) -> Vec<(String, UrlPath)> {
+        let mut res = Vec::new();
+        if !self.is_root {
+            res.push((self.name(), self.url()));
+        }
+        for tut in self.tutorials.values() {
+            res.push((tut.name(), tut.url()));
+        }
+        for dir in self.folders.values() {
+            res.extend(dir.pages());
+        }
+        res
+    }
+
+    /// Get all listed tutorials in this folder and its subfolders that are a
+    /// part of a series, grouped by series in order of their `series_index`.
+    /// Parts without one come after the others, in navbar order
+    pub fn series(&self) -> BTreeMap<String, Vec<SeriesPart>> {
+        let mut res = BTreeMap::new();
+        self.collect_series(&mut res);
+        for parts in res.values_mut() {
+            parts.sort_by_key(|part| part.index.unwrap_or(usize::MAX));
+        }
+        res
+    }
+
+    fn collect_series(&self, out: &mut BTreeMap<String, Vec<SeriesPart>>) {
+        for tut in self.tutorials_sorted() {
+            if let Some(ref series) = tut.metadata.series {
+                out.entry(series.clone()).or_default().push(SeriesPart {
+                    title: tut.name(),
+                    url: tut.url(),
+                    index: tut.metadata.series_index,
+                });
+            }
+        }
+        for dir in self.folders_sorted() {
+            dir.collect_series(out);
+        }
+    }
+
+    /// Get all listed tutorials in this folder and its subfolders that have
+    /// tags
+    pub fn tagged_tutorials(&self) -> Vec<TaggedTutorial> {
+        let mut res = self
+            .tutorials_sorted()
+            .into_iter()
+            .filter(|tut| !tut.metadata.tags.is_empty())
+            .map(|tut| TaggedTutorial {
+                title: tut.name(),
+                url: tut.url(),
+                tags: tut.metadata.tags.clone(),
+            })
+            .collect::<Vec<_>>();
+        for dir in self.folders_sorted() {
+            res.extend(dir.tagged_tutorials());
+        }
+        res
+    }
+
+    /// Get the folders leading up to a tutorial or folder at the given URL,
+    /// starting with this one
+    pub fn breadcrumbs(&self, root_name: &str, url: &UrlPath) -> Vec<(String, Option<UrlPath>)> {
+        let name = if self.is_root {
+            root_name.to_owned()
+        } else {
+            self.name()
+        };
+        let mut res = vec![(name, Some(self.url()))];
+        if let Some(dir) = self
+            .folders
+            .values()
+            .find(|dir| dir.url() != *url && url.starts_with(dir.url()))
+        {
+            res.extend(dir.breadcrumbs(root_name, url));
+        }
+        res
+    }
+
+    /// Get all listed tutorials in this folder and its subfolders for the
+    /// feed, leaving out ones without a date
+    pub fn feed_entries(&self, config: Arc<Config>) -> Vec<FeedEntry> {
+        self.collect_feed_entries(&git_dates(&config.input_dir.join(&self.root.dir)))
+    }
+
+    /// Get the feed entries of the tutorials in this folder and its
+    /// subfolders, given the dates of the last commits changing them
+    fn collect_feed_entries(&self, git_dates: &HashMap<String, String>) -> Vec<FeedEntry> {
+        let mut res = self
+            .tutorials_sorted()
+            .into_iter()
+            .filter_map(|tut| {
+                let date = tut
+                    .metadata
+                    .date
+                    .as_deref()
+                    .map(normalize_date)
+                    .or_else(|| git_dates.get(&tut.path.to_raw_string()).cloned());
+                let Some(date) = date else {
+                    debug!("Leaving {} out of the feed since it has no date", tut.path);
+                    return None;
+                };
+                Some(FeedEntry {
+                    title: tut.name(),
+                    url: tut.url(),
+                    description: tut.metadata.description.clone(),
+                    date,
+                })
+            })
+            .collect::<Vec<_>>();
+        for dir in self.folders_sorted() {
+            res.extend(dir.collect_feed_entries(git_dates));
+        }
+        res
+    }
+
+    /// Get the listed folders in this folder, in order
     pub fn folders_sorted(&self) -> Vec<&TutorialFolder> {
-        let mut vec = self.folders.iter().collect::<Vec<_>>();
+        let mut vec = self
+            .folders
+            .iter()
+            .filter(|(_, v)| !v.is_unlisted())
+            .collect::<Vec<_>>();
         vec.sort_unstable_by(|a, b| {
             match (
                 a.1.metadata.clone().and_then(|x| x.order),
@@ -216,8 +572,13 @@ impl TutorialFolder {
         vec.into_iter().map(|(_, v)| v).collect()
     }
 
+    /// Get the listed tutorials in this folder, in order
     pub fn tutorials_sorted(&self) -> Vec<&Tutorial> {
-        let mut vec = self.tutorials.iter().collect::<Vec<_>>();
+        let mut vec = self
+            .tutorials
+            .iter()
+            .filter(|(_, v)| !v.metadata.unlisted)
+            .collect::<Vec<_>>();
         vec.sort_unstable_by(|a, b| match (a.1.metadata.order, b.1.metadata.order) {
             (Some(a), Some(b)) => a.cmp(&b),
             (Some(_), None) => Ordering::Less,
@@ -238,9 +599,9 @@ impl<'e> Entry<'e> for TutorialFolder {
 
     fn url(&self) -> UrlPath {
         if self.is_root {
-            UrlPath::new()
+            self.root.prefix.clone()
         } else {
-            self.path.clone()
+            self.root.prefix.join(&self.path)
         }
     }
 
@@ -287,31 +648,63 @@ impl<'e> Entry<'e> for TutorialFolder {
 impl<'e> OutputEntry<'e> for TutorialFolder {
     fn output(&self, builder: &Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
         (
-            if self.index.is_some() {
-                builder.config.templates.tutorial.clone()
-            } else {
-                builder.config.templates.tutorial_index.clone()
-            },
+            tutorial_template(
+                builder.config.clone(),
+                self.metadata.as_ref(),
+                if self.index.is_some() {
+                    &builder.config.templates.tutorial
+                } else {
+                    &builder.config.templates.tutorial_index
+                },
+            ),
             output_tutorial(
                 self,
                 builder,
                 self.index.as_deref().unwrap_or(""),
-                fmt_section(
-                    "Pages",
-                    self.tutorials_sorted()
-                        .iter()
-                        .map(|tut| {
-                            HtmlElement::new("ul")
-                                .with_child(HtmlElement::new("li").with_child(
-                                    HtmlElement::new("a").with_text(tut.name()).with_attr(
-                                        "href",
-                                        tut.url().to_absolute(builder.config.clone()),
-                                    ),
-                                ))
-                                .into()
-                        })
-                        .collect(),
+                tutorial_source_dir(
+                    builder.config.clone(),
+                    &self.root,
+                    &self.path.join(UrlPath::part("index.md")),
                 ),
+                self.metadata.as_ref(),
+                HtmlList::new(vec![
+                    fmt_section(
+                        &builder
+                            .config
+                            .tr_in(builder.config.locale_of(&self.url()), "pages"),
+                        self.tutorials_sorted()
+                            .iter()
+                            .map(|tut| {
+                                HtmlElement::new("ul")
+                                    .with_child(HtmlElement::new("li").with_child(
+                                        HtmlElement::new("a").with_text(tut.name()).with_attr(
+                                            "href",
+                                            tut.url().to_absolute(builder.config.clone()),
+                                        ),
+                                    ))
+                                    .into()
+                            })
+                            .collect(),
+                    ),
+                    if self.is_root {
+                        fmt_series_sections(builder.config.clone(), &self.series())
+                    } else {
+                        Html::empty()
+                    },
+                    if self.is_root {
+                        fmt_tag_cloud(builder.config.clone(), &self.tagged_tutorials())
+                    } else {
+                        Html::empty()
+                    },
+                ])
+                .into(),
+                self.index.as_ref().and_then(|_| {
+                    tutorial_tree_url(
+                        builder.config.clone(),
+                        &self.root,
+                        &self.path.join(UrlPath::part("index.md")),
+                    )
+                }),
             ),
         )
     }
@@ -326,4 +719,41 @@ impl<'e> OutputEntry<'e> for TutorialFolder {
                 .unwrap_or(format!("Tutorials for {}", builder.config.project.name))
         }
     }
+
+    fn markdown(&self, _builder: &'e Builder<'e>) -> Option<String> {
+        self.index
+            .as_deref()
+            .map(|index| strip_metadata_from_md(index).to_owned())
+    }
+
+    fn breadcrumbs(&self, builder: &'e Builder<'e>) -> Vec<(String, Option<UrlPath>)> {
+        if self.is_root {
+            Vec::new()
+        } else {
+            builder.tutorial_breadcrumbs(&self.url())
+        }
+    }
+
+    fn schema_type(&self) -> &'static str {
+        "TechArticle"
+    }
+
+    fn post_hooks(&self, config: &Config) -> Vec<String> {
+        config
+            .run
+            .as_ref()
+            .map(|r| r.posttutorial.clone())
+            .unwrap_or_default()
+    }
+
+    fn source(&self) -> Option<String> {
+        self.index.as_ref().map(|_| {
+            self.root
+                .dir
+                .join(self.path.to_pathbuf())
+                .join("index.md")
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+    }
 }
@@ -0,0 +1,177 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::{
+    diagnostics::{WarningKind, report},
+    html::{Html, HtmlElement, HtmlText},
+    url::UrlPath,
+};
+use clang::Entity;
+
+use super::{
+    builder::Builder,
+    namespace::CppItemKind,
+    shared::{fmt_section, member_fun_link},
+    traits::{Access, BuildResult, EntityMethods, Entry, Include, NavItem, OutputEntry},
+};
+
+/// A public entity without a doc comment
+struct Undocumented {
+    name: String,
+    url: UrlPath,
+    file: String,
+    line: u32,
+}
+
+impl Undocumented {
+    fn new(entity: &Entity, url: UrlPath) -> Self {
+        let (file, line) = entity.source_location();
+        Self {
+            name: entity.full_name().join("::"),
+            url,
+            file,
+            line,
+        }
+    }
+}
+
+/// A maintenance page listing the public entities that don't have doc
+/// comments, grouped by the header they are declared in. It isn't linked from
+/// anywhere or included in indexes
+pub struct UndocumentedReport {
+    files: BTreeMap<String, Vec<Undocumented>>,
+}
+
+impl UndocumentedReport {
+    pub fn new(builder: &Builder) -> Self {
+        let config = builder.config.clone();
+        let mut found = Vec::new();
+        let entries = builder.root.get(&|entry| {
+            !matches!(
+                CppItemKind::from(entry.entity()),
+                Some(CppItemKind::Namespace) | None
+            )
+        });
+        for entry in entries {
+            let entity = entry.entity();
+            if entity.get_comment().is_none() {
+                found.push((*entity, Undocumented::new(entity, entry.url())));
+            }
+            for member in entity
+                .get_constructors(Access::Public)
+                .into_iter()
+                .chain(entity.get_member_functions(Access::Public, Include::All))
+                .filter(|m| m.get_comment().is_none() && !m.is_hidden(config.clone()))
+            {
+                let url = entry
+                    .url()
+                    .with_fragment(&member_fun_link(&member).unwrap_or_default());
+                found.push((member, Undocumented::new(&member, url)));
+            }
+        }
+
+        let mut files = BTreeMap::<String, Vec<Undocumented>>::new();
+        for (entity, item) in found {
+            let file = entity
+                .header(config.clone())
+                .map(|h| h.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_default();
+            files.entry(file).or_default().push(item);
+        }
+        for items in files.values_mut() {
+            items.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+        Self { files }
+    }
+
+    /// List the undocumented entities in the console
+    pub fn log(&self) {
+        for item in self.files.values().flatten() {
+            report(
+                WarningKind::Undocumented,
+                (&item.file, item.line),
+                format_args!("{} has no documentation", item.name),
+            );
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.files.values().map(|items| items.len()).sum()
+    }
+}
+
+impl<'e> Entry<'e> for UndocumentedReport {
+    fn name(&self) -> String {
+        String::from("Undocumented items")
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::parse("_maintenance/undocumented").unwrap()
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(
+            &self.name(),
+            self.url(),
+            Some(("alert-circle", false)),
+            Vec::new(),
+        )
+    }
+}
+
+impl<'e> OutputEntry<'e> for UndocumentedReport {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+        let config = builder.config.clone();
+        (
+            builder.config.templates.tutorial_index.clone(),
+            vec![
+                (
+                    "title",
+                    HtmlText::new(format!("{} ({})", self.name(), self.count())).into(),
+                ),
+                (
+                    "links",
+                    HtmlElement::new("div")
+                        .with_children(
+                            self.files
+                                .iter()
+                                .map(|(file, items)| {
+                                    fmt_section(
+                                        file,
+                                        items
+                                            .iter()
+                                            .map(|item| {
+                                                HtmlElement::new("div")
+                                                    .with_child(
+                                                        HtmlElement::new("a")
+                                                            .with_attr(
+                                                                "href",
+                                                                item.url
+                                                                    .to_absolute(config.clone()),
+                                                            )
+                                                            .with_text(&item.name),
+                                                    )
+                                                    .with_text(format!(" (line {})", item.line))
+                                                    .into()
+                                            })
+                                            .collect(),
+                                    )
+                                })
+                                .collect(),
+                        )
+                        .into(),
+                ),
+            ],
+        )
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!(
+            "Public entities without documentation in {}",
+            builder.config.project.name
+        )
+    }
+}
@@ -0,0 +1,182 @@
+use std::{collections::HashMap, fs, sync::Arc};
+
+use clang::Entity;
+use glob::glob;
+
+use crate::{
+    config::Config,
+    html::{Html, HtmlText},
+    url::UrlPath,
+};
+
+use super::{
+    builder::Builder,
+    source::fmt_source_lines,
+    traits::{BuildResult, Entry, NavItem, OutputEntry},
+};
+
+const SOURCE_EXTENSIONS: &[&str] = &["c", "cc", "cpp", "cxx", "h", "hh", "hpp", "hxx", "mm"];
+
+/// A source file from one of the `examples.usage-dirs`, which gets its own
+/// highlighted page so entity pages can link to where they're used
+pub struct UsageFile {
+    /// Path relative to the input directory
+    path: UrlPath,
+    /// Path relative to the usage directory it's in, shown in links to it
+    name: String,
+    /// The line each identifier in the file is first used on
+    idents: HashMap<String, usize>,
+}
+
+impl UsageFile {
+    fn new(path: UrlPath, name: String, data: &str) -> Self {
+        let mut idents = HashMap::new();
+        for (i, line) in data.lines().enumerate() {
+            for ident in line
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .filter(|word| word.starts_with(|c: char| c.is_alphabetic() || c == '_'))
+            {
+                idents.entry(ident.to_owned()).or_insert(i + 1);
+            }
+        }
+        Self { path, name, idents }
+    }
+}
+
+impl<'e> Entry<'e> for UsageFile {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::parse("examples").unwrap().join(&self.path)
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("code", false)), Vec::new())
+    }
+}
+
+impl<'e> OutputEntry<'e> for UsageFile {
+    fn output(&self, builder: &'e Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+        let data = fs::read_to_string(builder.config.input_dir.join(self.path.to_raw_string()))
+            .unwrap_or_default();
+        (
+            builder.config.templates.source.clone(),
+            vec![
+                ("name", HtmlText::new(self.name()).into()),
+                ("file_path", HtmlText::new(self.path.to_raw_string()).into()),
+                (
+                    "file_url",
+                    HtmlText::new(
+                        builder
+                            .config
+                            .source_url(&self.path, None)
+                            .unwrap_or_default(),
+                    )
+                    .into(),
+                ),
+                ("source", fmt_source_lines(&data)),
+            ],
+        )
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        format!(
+            "Example {} using {}",
+            self.name, builder.config.project.name
+        )
+    }
+
+    fn schema_type(&self) -> &'static str {
+        "SoftwareSourceCode"
+    }
+
+    fn source(&self) -> Option<String> {
+        Some(self.path.to_raw_string())
+    }
+}
+
+/// The source files in the `examples.usage-dirs`, indexed by the identifiers
+/// used in them
+pub struct UsageIndex {
+    files: Vec<UsageFile>,
+}
+
+impl UsageIndex {
+    pub fn new(config: Arc<Config>) -> Result<Self, String> {
+        let mut files = Vec::new();
+        let dirs = config
+            .examples
+            .as_ref()
+            .map(|e| e.usage_dirs.clone())
+            .unwrap_or_default();
+        for dir in dirs {
+            let pattern = config.input_dir.join(&dir).join("**").join("*");
+            for path in glob(&pattern.to_string_lossy())
+                .map_err(|e| format!("Unable to search {}: {e}", dir.display()))?
+                .filter_map(|p| p.ok())
+                .filter(|p| {
+                    p.extension()
+                        .is_some_and(|ext| SOURCE_EXTENSIONS.contains(&&*ext.to_string_lossy()))
+                })
+            {
+                let data = fs::read_to_string(&path)
+                    .map_err(|e| format!("Unable to read {}: {e}", path.display()))?;
+                let Ok(rel) = path.strip_prefix(&config.input_dir) else {
+                    continue;
+                };
+                let name = path
+                    .strip_prefix(config.input_dir.join(&dir))
+                    .unwrap_or(rel)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                files.push(UsageFile::new(
+                    UrlPath::try_from(&rel.to_path_buf())?,
+                    name,
+                    &data,
+                ));
+            }
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Self { files })
+    }
+
+    /// Get the files an entity is used in, matching by name, along with the
+    /// line it's first used on in each
+    pub fn files_using(&self, entity: &Entity) -> Vec<(&UsageFile, usize)> {
+        let Some(name) = entity.get_name() else {
+            return Vec::new();
+        };
+        self.files
+            .iter()
+            .filter_map(|file| Some((file, *file.idents.get(&name)?)))
+            .collect()
+    }
+}
+
+impl<'e> Entry<'e> for UsageIndex {
+    fn name(&self) -> String {
+        String::from("Examples")
+    }
+
+    fn url(&self) -> UrlPath {
+        UrlPath::parse("examples").unwrap()
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        let mut handles = Vec::new();
+        for file in &self.files {
+            handles.extend(file.build(builder)?);
+        }
+        Ok(handles)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_root(None, self.files.iter().map(|f| f.nav()).collect())
+    }
+}
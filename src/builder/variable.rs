@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use crate::{config::Config, html::Html, url::UrlPath};
+use clang::Entity;
+
+use super::{
+    builder::Builder,
+    shared::{entity_breadcrumbs, output_entity_markdown, output_variable},
+    traits::{ASTEntry, BuildResult, EntityMethods, Entry, NavItem, OutputEntry},
+};
+
+pub struct Variable<'e> {
+    entity: Entity<'e>,
+    config: Arc<Config>,
+}
+
+impl<'e> Variable<'e> {
+    pub fn new(entity: Entity<'e>, config: Arc<Config>) -> Self {
+        Self { entity, config }
+    }
+}
+
+impl<'e> Entry<'e> for Variable<'e> {
+    fn name(&self) -> String {
+        self.entity
+            .get_name()
+            .unwrap_or("`Anonymous variable`".into())
+    }
+
+    fn url(&self) -> UrlPath {
+        self.entity
+            .rel_docs_url(self.config.clone())
+            .expect("Unable to get variable URL")
+    }
+
+    fn build(&self, builder: &Builder<'e>) -> BuildResult {
+        builder.create_output_for(self)
+    }
+
+    fn nav(&self) -> NavItem {
+        NavItem::new_link(&self.name(), self.url(), Some(("hash", false)), Vec::new())
+    }
+}
+
+impl<'e> ASTEntry<'e> for Variable<'e> {
+    fn entity(&self) -> &Entity<'e> {
+        &self.entity
+    }
+
+    fn category(&self) -> &'static str {
+        "variable"
+    }
+}
+
+impl<'e> OutputEntry<'e> for Variable<'e> {
+    fn output(&self, builder: &Builder<'e>) -> (Arc<String>, Vec<(&'static str, Html)>) {
+        (
+            builder.config.templates.variable.clone(),
+            output_variable(self, builder),
+        )
+    }
+
+    fn description(&self, builder: &'e Builder<'e>) -> String {
+        self.output_description(builder)
+    }
+
+    fn markdown(&self, builder: &'e Builder<'e>) -> Option<String> {
+        Some(output_entity_markdown(self, builder))
+    }
+
+    fn breadcrumbs(&self, builder: &'e Builder<'e>) -> Vec<(String, Option<UrlPath>)> {
+        entity_breadcrumbs(builder.config.clone(), self)
+    }
+
+    fn schema_type(&self) -> &'static str {
+        "SoftwareSourceCode"
+    }
+
+    fn source(&self) -> Option<String> {
+        self.entity().get_usr().map(|usr| usr.0)
+    }
+}
@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use serde_json::Value;
 use std::{fs, path::PathBuf, process::Command, sync::Arc};
 
 use crate::config::Config;
@@ -49,23 +50,232 @@ impl CompileCommand {
             .chain(vec![format!("-I{}", config.input_dir.to_str().unwrap())])
             // Set working directory
             .chain(vec![format!("-working-directory={}", self.directory.to_str().unwrap())])
-            // Add extra compile args
-            .chain(config.analysis.compile_args.clone())
-            // Retain comments from external libraries that might be included in the docs
-            .chain(vec!["-fretain-comments-from-system-headers".into()])
             .collect();
+        finish_compile_args(config, &mut list);
+        list
+    }
+}
 
-        // Passing -c or -arch crashes LibClang
-        while let Some(ix) = list.iter().position(|s| s == "-c" || s == "-arch") {
-            list.drain(ix..ix + 2);
+/// Flags of MSVC that don't matter for parsing headers and that clang either
+/// rejects or takes to mean something else, without the leading `/` or `-`
+const MSVC_IGNORED_FLAGS: &[&str] = &[
+    "EH",
+    "Zc:",
+    "Zi",
+    "Z7",
+    "Fo",
+    "Fd",
+    "Fp",
+    "FS",
+    "GR",
+    "GS",
+    "Gy",
+    "Ob",
+    "RTC",
+    "MP",
+    "nologo",
+    "permissive",
+    "utf-8",
+    "bigobj",
+    "diagnostics:",
+    "external:W",
+    "analyze",
+    "W",
+];
+
+/// Translate MSVC-style flags like `/I`, `/D` and `/std:c++20` into ones
+/// LibClang understands, and leave out the ones that only matter for
+/// compiling with MSVC
+fn translate_msvc_args(args: Vec<String>) -> Vec<String> {
+    let mut res = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let Some(flag) = arg.strip_prefix('/').or(arg.strip_prefix('-')) else {
+            res.push(arg);
+            continue;
+        };
+        // Flags with a value can have it in the next argument, like `/I dir`
+        let mut value = |prefix: &str| {
+            let rest = flag.strip_prefix(prefix)?;
+            if rest.is_empty() {
+                args.next()
+            } else {
+                Some(rest.to_owned())
+            }
+        };
+        if let Some(dir) = value("external:I") {
+            res.extend(["-isystem".into(), dir]);
+        } else if let Some(std) = value("std:") {
+            let std = if std == "c++latest" {
+                "c++2b".into()
+            } else {
+                std
+            };
+            res.push(format!("-std={std}"));
+        } else if let Some(file) = value("FI") {
+            res.extend(["-include".into(), file]);
+        } else if let Some(dir) = value("I") {
+            res.push(format!("-I{dir}"));
+        } else if let Some(def) = value("D") {
+            res.push(format!("-D{def}"));
+        } else if let Some(def) = value("U") {
+            res.push(format!("-U{def}"));
+        } else if matches!(flag, "MD" | "MDd" | "MT" | "MTd")
+            || MSVC_IGNORED_FLAGS.iter().any(|f| flag.starts_with(f))
+            // Anything else in MSVC style is unknown to clang
+            || arg.starts_with('/')
+        {
+            continue;
+        } else {
+            res.push(arg);
         }
+    }
+    res.extend(["-fms-extensions".into(), "-fms-compatibility".into()]);
+    res
+}
+
+/// Add the arguments Flash always passes to LibClang to ones from CMake
+fn finish_compile_args(config: Arc<Config>, list: &mut Vec<String>) {
+    if config.analysis.driver == "cl" {
+        *list = translate_msvc_args(std::mem::take(list));
+    }
+    // Add extra compile args
+    list.extend(config.analysis.compile_args.clone());
+    // Retain comments from external libraries that might be included in the docs
+    list.push("-fretain-comments-from-system-headers".into());
+
+    // Passing -c or -arch crashes LibClang
+    while let Some(ix) = list.iter().position(|s| s == "-c" || s == "-arch") {
+        list.drain(ix..(ix + 2).min(list.len()));
+    }
 
-        list.push("-ferror-limit=200".into());
+    list.push("-ferror-limit=200".into());
+}
+
+#[derive(Deserialize)]
+struct Include {
+    path: String,
+    #[serde(default, rename = "isSystem")]
+    is_system: bool,
+}
+
+#[derive(Deserialize)]
+struct Define {
+    define: String,
+}
 
+#[derive(Deserialize)]
+struct Fragment {
+    fragment: String,
+}
+
+#[derive(Deserialize)]
+struct LanguageStandard {
+    standard: String,
+}
+
+/// A group of sources in a CMake target that are compiled with the same
+/// arguments, from the CMake File API
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompileGroup {
+    language: String,
+    #[serde(default)]
+    includes: Vec<Include>,
+    #[serde(default)]
+    defines: Vec<Define>,
+    #[serde(default)]
+    compile_command_fragments: Vec<Fragment>,
+    language_standard: Option<LanguageStandard>,
+}
+
+impl CompileGroup {
+    fn get_command_list(&self, config: Arc<Config>) -> Vec<String> {
+        let mut list = self
+            .compile_command_fragments
+            .iter()
+            .flat_map(|f| shlex::split(&f.fragment).unwrap_or_default())
+            .chain(self.includes.iter().map(|i| {
+                if i.is_system {
+                    format!("-isystem{}", i.path)
+                } else {
+                    format!("-I{}", i.path)
+                }
+            }))
+            .chain(self.defines.iter().map(|d| format!("-D{}", d.define)))
+            .chain(
+                self.language_standard
+                    .as_ref()
+                    .map(|s| format!("-std=c++{}", s.standard)),
+            )
+            // Add header root to include directories
+            .chain(vec![format!("-I{}", config.input_dir.to_str().unwrap())])
+            .collect::<Vec<_>>();
+        finish_compile_args(config, &mut list);
         list
     }
 }
 
+/// Get the directory of the CMake File API in the build directory
+fn file_api_dir(config: &Config) -> PathBuf {
+    config
+        .input_dir
+        .join(&config.cmake.as_ref().unwrap().build_dir)
+        .join(".cmake/api/v1")
+}
+
+/// Ask CMake to write the code model of the project to the build directory
+/// the next time it is configured, for getting the arguments of a target
+pub fn cmake_query_file_api(config: Arc<Config>) -> Result<(), String> {
+    let dir = file_api_dir(&config).join("query/client-flash");
+    fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(dir.join("codemodel-v2"), ""))
+        .map_err(|e| format!("Unable to create CMake File API query: {e}"))
+}
+
+/// Read a reply file of the CMake File API
+fn read_reply(config: &Config, name: &str) -> Result<Value, String> {
+    serde_json::from_str(
+        &fs::read_to_string(file_api_dir(config).join("reply").join(name))
+            .map_err(|e| format!("Unable to read CMake File API reply {name}: {e}"))?,
+    )
+    .map_err(|e| format!("Unable to parse CMake File API reply {name}: {e}"))
+}
+
+/// Get the compile args of a target from the CMake File API
+fn cmake_target_args(config: Arc<Config>, target: &str) -> Result<Vec<String>, String> {
+    // The newest index is the last one by name
+    let index = fs::read_dir(file_api_dir(&config).join("reply"))
+        .map_err(|e| format!("Unable to read CMake File API replies (was CMake configured?): {e}"))?
+        .filter_map(|f| f.ok()?.file_name().into_string().ok())
+        .filter(|f| f.starts_with("index-") && f.ends_with(".json"))
+        .max()
+        .ok_or(String::from("CMake File API has no replies"))?;
+    let index = read_reply(&config, &index)?;
+    let codemodel = index["reply"]["client-flash"]["codemodel-v2"]["jsonFile"]
+        .as_str()
+        .ok_or(String::from("CMake File API reply has no code model"))?;
+
+    let codemodel = read_reply(&config, codemodel)?;
+    let target_file = codemodel["configurations"]
+        .as_array()
+        .and_then(|c| c.first())
+        .and_then(|c| c["targets"].as_array())
+        .and_then(|targets| targets.iter().find(|t| t["name"] == target))
+        .and_then(|t| t["jsonFile"].as_str())
+        .ok_or(format!("Unable to find CMake target '{target}'"))?;
+
+    let groups: Vec<CompileGroup> =
+        serde_json::from_value(read_reply(&config, target_file)?["compileGroups"].clone())
+            .map_err(|e| format!("Unable to parse compile groups of '{target}': {e}"))?;
+    let group = groups
+        .iter()
+        .find(|g| g.language == "CXX")
+        .or(groups.first())
+        .ok_or(format!("CMake target '{target}' has no sources"))?;
+    Ok(group.get_command_list(config))
+}
+
 type CompileCommands = Vec<CompileCommand>;
 
 pub fn cmake_configure(build_dir: &str, args: &Vec<String>) -> Result<(), String> {
@@ -109,11 +319,16 @@ pub fn cmake_compile_commands(config: Arc<Config>) -> Result<CompileCommands, St
 }
 
 pub fn cmake_compile_args_for(config: Arc<Config>) -> Result<Vec<String>, String> {
-    let from = &config
+    let cmake = config
         .cmake
         .as_ref()
-        .ok_or(String::from("Project does not use CMake"))?
-        .infer_args_from;
+        .ok_or(String::from("Project does not use CMake"))?;
+    if let Some(ref target) = cmake.target {
+        return cmake_target_args(config.clone(), target);
+    }
+    let from = cmake.infer_args_from.as_ref().ok_or(String::from(
+        "Either cmake.target or cmake.infer-args-from has to be set",
+    ))?;
     for cmd in cmake_compile_commands(config.clone())? {
         if cmd.file == config.input_dir.join(from) {
             return Ok(cmd.get_command_list(config));
@@ -1,10 +1,23 @@
 use flash_macros::decl_config;
 use glob::glob;
+use log::warn;
+use percent_encoding::utf8_percent_encode;
 use regex_lite::Regex;
 use serde::{Deserialize, Deserializer};
-use std::{fs, path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, OnceLock},
+};
+use strfmt::strfmt;
 
-use crate::url::UrlPath;
+use crate::{
+    cppreference::load_index,
+    diagnostics::WarningLevel,
+    i18n::{default_string, load_strings},
+    url::{URL_RESERVED, UrlPath},
+};
 
 fn parse_template<'de, D>(deserializer: D) -> Result<Arc<String>, D::Error>
 where
@@ -40,6 +53,17 @@ where
         .collect())
 }
 
+/// What to do with unsafe raw HTML in tutorials, set with
+/// `markdown.sanitize-html`
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum SanitizeHtml {
+    /// Leave it out of the page
+    Strip,
+    /// Show it as text
+    Escape,
+}
+
 pub struct MyRegex(Regex);
 
 impl<'de> serde::Deserialize<'de> for MyRegex {
@@ -61,6 +85,54 @@ impl std::ops::Deref for MyRegex {
     }
 }
 
+/// Built-in partials that templates can include with `{{> name}}`
+fn default_partial(name: &str) -> Option<&'static str> {
+    match name {
+        "entity-links" => Some(include_str!("../templates/partials/entity-links.html")),
+        "description" => Some(include_str!("../templates/partials/description.html")),
+        "examples" => Some(include_str!("../templates/partials/examples.html")),
+        _ => None,
+    }
+}
+
+/// `{{> name}}` includes of partials in templates, with the name of the partial
+/// in the first group
+static PARTIAL_INCLUDE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{>\s*([\w-]+)\s*\}\}").unwrap());
+
+/// Replace `{{> name}}` includes in a template with `name.html` from the
+/// templates directory, or the built-in partial with that name
+fn expand_partials_in(template: &str, dir: Option<&Path>, depth: usize) -> Result<String, String> {
+    if !PARTIAL_INCLUDE.is_match(template) {
+        return Ok(template.to_owned());
+    }
+    if depth >= 16 {
+        return Err("Template partials are nested too deeply, does one include itself?".into());
+    }
+
+    let mut res = String::new();
+    let mut last = 0;
+    for caps in PARTIAL_INCLUDE.captures_iter(template) {
+        let include = caps.get(0).unwrap();
+        let name = &caps[1];
+        let partial = match dir
+            .map(|d| d.join(format!("{name}.html")))
+            .filter(|p| p.is_file())
+        {
+            Some(path) => fs::read_to_string(path)
+                .map_err(|e| format!("Unable to read template partial '{name}': {e}"))?,
+            None => default_partial(name)
+                .ok_or(format!("Unknown template partial '{name}'"))?
+                .to_owned(),
+        };
+        res.push_str(&template[last..include.start()]);
+        res += &expand_partials_in(partial.trim_end(), dir, depth + 1)?;
+        last = include.end();
+    }
+    res.push_str(&template[last..]);
+    Ok(res)
+}
+
 macro_rules! default_template {
     ($name: expr) => {
         Arc::from(include_str!($name).to_string())
@@ -90,6 +162,14 @@ pub struct Source {
     pub dir: UrlPath,
     pub include: Vec<PathBuf>,
     pub exists_online: bool,
+    /// Library to link against to use the entities in this source
+    pub link_library: Option<String>,
+    /// Title of the section of the navbar with the entities in this source
+    pub nav_title: Option<String>,
+    /// Feather icon shown next to the navbar section
+    pub icon: Option<String>,
+    /// Short description shown under the title of the navbar section
+    pub description: Option<String>,
 }
 
 impl Source {
@@ -121,6 +201,10 @@ impl Source {
             name: src.name,
             dir: src.dir,
             exists_online: src.exists_online,
+            link_library: src.link_library,
+            nav_title: src.nav_title,
+            icon: src.icon,
+            description: src.description,
             include,
         })
     }
@@ -138,11 +222,41 @@ decl_config! {
         include: Vec<PathBuf>,
         exclude: Vec<PathBuf> = Vec::new(),
         exists_online: bool = true,
+        link_library: Option<String>,
+        nav_title: Option<String>,
+        icon: Option<String>,
+        description: Option<String>,
     }
 
     struct ExternalLib {
         pattern: String,
         repository: String,
+        url: Option<String>,
+        version: Option<String>,
+    }
+
+    struct MarkdownStyle {
+        class: Option<String>,
+        heading_class: Option<String>,
+        heading_levels: Vec<usize> = vec![1, 2],
+        wrap_after: Option<usize>,
+        wrap_tag: String = String::from("blockquote"),
+        wrap_class: Option<String>,
+    }
+
+    struct DocsCategory {
+        slug: Option<String>,
+        name: Option<String>,
+    }
+
+    struct Locale {
+        name: String,
+        tutorials: PathBuf,
+    }
+
+    struct Platform {
+        name: String,
+        defines: Vec<String> = Vec::new(),
     }
 
     struct RegexPattern {
@@ -156,47 +270,424 @@ decl_config! {
             version: String,
             repository?: String,
             tree?: String,
+            host: String = String::from("github"),
+            branch?: String,
             icon?: PathBuf,
         },
         tutorials? {
             dir: PathBuf,
             assets: Vec<PathBuf> as parse_glob = Vec::new(),
+            feed: bool = false,
+            shift_headings: bool = false,
+            images? {
+                widths: Vec<u32> = vec![480, 960],
+                webp: bool = true,
+                lazy: bool = false,
+            },
         },
         sources: Vec<Arc<Source>> as parse_sources,
         run? {
             prebuild: Vec<String> = Vec::new(),
+            postbuild: Vec<String> = Vec::new(),
+            prepage: Vec<String> = Vec::new(),
+            posttutorial: Vec<String> = Vec::new(),
         },
         analysis {
             compile_args: Vec<String> = Vec::new(),
+            show_layout: bool = false,
+            show_private: bool = false,
+            show_protected: bool = false,
+            hide_attributes: Vec<String> = Vec::new(),
+            require_macro?: String,
+            require_attributes: Vec<String> = Vec::new(),
+            platforms: Vec<Platform> = Vec::new(),
+            driver: String = String::from("clang"),
+            language: String = String::from("c++"),
+            detect_system_includes: bool = false,
+            system_includes?: Vec<PathBuf>,
+            sysroot?: PathBuf,
+            precompile?: PathBuf,
         },
         cmake? {
             config_args: Vec<String> = Vec::new(),
             build_args: Vec<String> = Vec::new(),
             build: bool = false,
             build_dir: String = String::from("build"),
-            infer_args_from: PathBuf,
+            infer_args_from?: PathBuf,
+            target?: String,
         },
         templates {
             class:          Arc<String> as parse_template = default_template!("../templates/class.html"),
             struct_:        Arc<String> as parse_template = default_template!("../templates/struct.html"),
             function:       Arc<String> as parse_template = default_template!("../templates/function.html"),
+            variable:       Arc<String> as parse_template = default_template!("../templates/variable.html"),
+            group:          Arc<String> as parse_template = default_template!("../templates/group.html"),
+            namespace:      Arc<String> as parse_template = default_template!("../templates/namespace.html"),
+            source:         Arc<String> as parse_template = default_template!("../templates/source.html"),
             head:           Arc<String> as parse_template = default_template!("../templates/head.html"),
             nav:            Arc<String> as parse_template = default_template!("../templates/nav.html"),
             file:           Arc<String> as parse_template = default_template!("../templates/file.html"),
             page:           Arc<String> as parse_template = default_template!("../templates/page.html"),
             tutorial:       Arc<String> as parse_template = default_template!("../templates/tutorial.html"),
             tutorial_index: Arc<String> as parse_template = default_template!("../templates/tutorial-index.html"),
+            dir?: PathBuf,
         },
         scripts {
             css: Vec<Script> = default_scripts!("default.css", "nav.css", "content.css", "themes.css"),
             js:  Vec<Script> = default_scripts!("script.js"),
         },
         external_libs: Vec<Arc<ExternalLib>> = Vec::new(),
+        external_links {
+            decorate: bool = true,
+            allow: Vec<String> = Vec::new(),
+        },
+        search? {
+            algolia: bool = false,
+            pagefind: bool = false,
+        },
+        namespaces {
+            rename: HashMap<String, String> = HashMap::new(),
+            collapse: Vec<String> = Vec::new(),
+            merge_using: bool = false,
+        },
+        since {
+            infer_from_tags: bool = false,
+            version_index: bool = true,
+        },
+        search_ranking {
+            fuzzy: bool = true,
+            exact_match: f64 = 20.0,
+            prefix_match: f64 = 10.0,
+            member_penalty: f64 = 5.0,
+            deprecated_penalty: f64 = 15.0,
+        },
+        markdown? {
+            emoji: HashMap<String, String> = HashMap::new(),
+            twemoji: bool = true,
+            styles: HashMap<String, MarkdownStyle> = HashMap::new(),
+            sanitize_html?: SanitizeHtml,
+        },
+        html? {
+            head_extra: String = String::new(),
+            csp?: String,
+        },
+        theme? {
+            colors: HashMap<String, String> = HashMap::new(),
+        },
+        banner? {
+            content: String,
+            id?: String,
+            dismissible: bool = true,
+        },
+        examples? {
+            dir?: PathBuf,
+            includes: Vec<PathBuf> = Vec::new(),
+            prelude: String = String::new(),
+            usage_dirs: Vec<PathBuf> = Vec::new(),
+        },
+        i18n? {
+            locale: String = String::from("en"),
+            locale_name: String = String::from("English"),
+            dir?: PathBuf,
+            locales: HashMap<String, Locale> = HashMap::new(),
+        },
+        cppreference? {
+            index: PathBuf,
+        },
+        categories: HashMap<String, DocsCategory> = HashMap::new(),
+        static_dir: Option<PathBuf>,
+        canonical_url: Option<String>,
+        trailing_slash: bool = false,
+        lowercase_urls: bool = false,
+        minify: bool = true,
+        fingerprint_assets: bool = false,
+        manifest_file: String = String::from("manifest.json"),
+        source_browser: bool = false,
+        llms_txt: bool = false,
+        undocumented_report: bool = false,
+        warnings: HashMap<String, WarningLevel> = HashMap::new(),
         ignore: Option<RegexPattern>,
         include: Option<RegexPattern>,
         let input_dir: PathBuf,
         let output_dir: PathBuf,
         let output_url: Option<UrlPath>,
+        let single_page: bool,
+        let relative_links: bool,
+        let drafts: bool,
+        let jobs: usize,
+        let quiet: bool,
+        let verbose: bool,
+        let check_only: bool,
+        let strings: HashMap<String, HashMap<String, String>>,
+        let cppreference_pages: HashMap<String, String>,
+        let url_overrides: OnceLock<HashMap<String, UrlPath>>,
+        let page_templates: HashMap<String, Arc<String>>,
+    }
+}
+
+impl ExternalLib {
+    /// Get the online URL of an entity in this library from its `url`
+    /// pattern, or the repository if it has no pattern. The path is the part
+    /// of the header's path after `pattern`
+    pub fn url_for(&self, name: &str, path: &str, line: u32) -> String {
+        let Some(ref url) = self.url else {
+            return self.repository.clone();
+        };
+        let path = path
+            .split_once(&self.pattern)
+            .map_or(path, |(_, rest)| rest)
+            .replace('\\', "/");
+        let vars = HashMap::from([
+            ("repository".to_owned(), self.repository.clone()),
+            ("name".to_owned(), name.to_owned()),
+            ("path".to_owned(), path.trim_start_matches('/').to_owned()),
+            (
+                "version".to_owned(),
+                self.version.clone().unwrap_or_default(),
+            ),
+            ("line".to_owned(), line.to_string()),
+        ]);
+        strfmt(url, &vars)
+            .inspect_err(|e| warn!("Unable to format URL for {name}: {e}"))
+            .unwrap_or_else(|_| self.repository.clone())
+    }
+}
+
+impl MarkdownStyle {
+    /// The built-in style for FAQ pages, where the answers after second-level
+    /// headings are put in blockquotes
+    pub fn qna() -> Self {
+        Self {
+            class: None,
+            heading_class: Some(String::from("qna-question")),
+            heading_levels: vec![1, 2],
+            wrap_after: Some(2),
+            wrap_tag: String::from("blockquote"),
+            wrap_class: None,
+        }
+    }
+}
+
+impl Clone for MarkdownStyle {
+    fn clone(&self) -> Self {
+        Self {
+            class: self.class.clone(),
+            heading_class: self.heading_class.clone(),
+            heading_levels: self.heading_levels.clone(),
+            wrap_after: self.wrap_after,
+            wrap_tag: self.wrap_tag.clone(),
+            wrap_class: self.wrap_class.clone(),
+        }
+    }
+}
+
+/// Read a config file, merging in the files listed in its `include` key first
+/// so its own values take precedence. Tables are merged and arrays like
+/// `[[sources]]` are joined. `include` may also be the table of entity
+/// patterns to include, which is left alone
+fn read_config_table(path: &Path, depth: usize) -> Result<toml::Table, String> {
+    if depth > 8 {
+        return Err("Config includes are nested too deeply, does one include itself?".into());
+    }
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let text = fs::read_to_string(path).map_err(|e| format!("Unable to read {name}: {e}"))?;
+    let mut table: toml::Table =
+        toml::from_str(&text).map_err(|e| format!("Unable to parse {name}: {e}"))?;
+    match validate_config_table(&table, &text, &name) {
+        0 => {}
+        1 => return Err(format!("Found 1 problem in {name}")),
+        n => return Err(format!("Found {n} problems in {name}")),
+    }
+
+    let Some(toml::Value::Array(includes)) = table.get("include").cloned() else {
+        return Ok(table);
+    };
+    table.remove("include");
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut merged = toml::Table::new();
+    for include in includes {
+        let include = include.as_str().ok_or(format!(
+            "Unable to parse {name}: include should be a list of paths"
+        ))?;
+        merge_config_tables(
+            &mut merged,
+            read_config_table(&dir.join(include), depth + 1)?,
+        );
+    }
+    merge_config_tables(&mut merged, table);
+    Ok(merged)
+}
+
+/// Check a config file for mistakes that serde would either ignore or report
+/// without saying where they are. Unknown keys are warned about along with
+/// the closest known key, while missing templates and invalid glob patterns
+/// are logged as errors. Returns how many errors there were
+fn validate_config_table(table: &toml::Table, text: &str, name: &str) -> usize {
+    let key_line = |path: &str, key: &str| line_of_key(text, path).or(line_of_key(text, key));
+    let mut unknown = |path: &str, key: &str, known: &[&str]| {
+        let suggestion = closest_key(key, known)
+            .map(|k| format!(", did you mean '{k}'?"))
+            .unwrap_or_default();
+        log_config_problem(
+            log::Level::Warn,
+            name,
+            key_line(path, key),
+            &format!("Unknown config key '{path}'{suggestion}"),
+        );
+    };
+    Config::find_unknown_keys(table, "", &mut unknown);
+    for (i, source) in table_array(table.get("sources")) {
+        RawSource::find_unknown_keys(source, &format!("sources[{i}]"), &mut unknown);
+    }
+    for (i, lib) in table_array(table.get("external-libs")) {
+        ExternalLib::find_unknown_keys(lib, &format!("external-libs[{i}]"), &mut unknown);
+    }
+    let analysis = table.get("analysis");
+    for (i, platform) in table_array(analysis.and_then(|a| a.get("platforms"))) {
+        Platform::find_unknown_keys(platform, &format!("analysis.platforms[{i}]"), &mut unknown);
+    }
+
+    let mut errors = Vec::new();
+
+    // Templates are read relative to the input directory
+    let templates = table.get("templates").and_then(|t| t.as_table());
+    for (key, value) in templates.into_iter().flatten() {
+        if key != "dir"
+            && let Some(path) = value.as_str()
+            && !Path::new(path).is_file()
+        {
+            errors.push((
+                line_of_value(text, path),
+                format!("Unable to find template '{path}' set in templates.{key}"),
+            ));
+        }
+    }
+    for kind in ["css", "js"] {
+        let scripts = table.get("scripts").and_then(|s| s.get(kind));
+        for (i, script) in table_array(scripts) {
+            if let Some(path) = script.get("content").and_then(|c| c.as_str())
+                && !Path::new(path).is_file()
+            {
+                errors.push((
+                    line_of_value(text, path),
+                    format!("Unable to find script '{path}' set in scripts.{kind}[{i}].content"),
+                ));
+            }
+        }
+    }
+
+    let mut globs = Vec::new();
+    if let Some(tutorials) = table.get("tutorials") {
+        globs.push((String::from("tutorials.assets"), tutorials.get("assets")));
+    }
+    for (i, source) in table_array(table.get("sources")) {
+        globs.push((format!("sources[{i}].include"), source.get("include")));
+        globs.push((format!("sources[{i}].exclude"), source.get("exclude")));
+    }
+    for (key, patterns) in globs {
+        let patterns = patterns.and_then(|p| p.as_array()).into_iter().flatten();
+        for pattern in patterns.filter_map(|p| p.as_str()) {
+            if let Err(e) = glob::Pattern::new(pattern) {
+                errors.push((
+                    line_of_value(text, pattern),
+                    format!("Invalid glob pattern '{pattern}' in {key}: {e}"),
+                ));
+            }
+        }
+    }
+
+    for (line, message) in &errors {
+        log_config_problem(log::Level::Error, name, *line, message);
+    }
+    errors.len()
+}
+
+/// Get the tables in an array of tables like `[[sources]]`, with their indices
+fn table_array(value: Option<&toml::Value>) -> impl Iterator<Item = (usize, &toml::Table)> {
+    value
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_table())
+        .enumerate()
+}
+
+/// Find the line a key is set on in a config file, either as `key = ...` or as
+/// a table header. This only looks at the text, so a key used in several
+/// tables is found wherever it comes first
+fn line_of_key(text: &str, key: &str) -> Option<u32> {
+    text.lines()
+        .position(|line| {
+            line.trim_start()
+                .trim_start_matches('[')
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with(['=', ']', '.']))
+        })
+        .map(|i| i as u32 + 1)
+}
+
+/// Find the line a string value is on in a config file
+fn line_of_value(text: &str, value: &str) -> Option<u32> {
+    text.lines()
+        .position(|line| {
+            line.contains(&format!("\"{value}\"")) || line.contains(&format!("'{value}'"))
+        })
+        .map(|i| i as u32 + 1)
+}
+
+fn log_config_problem(level: log::Level, file: &str, line: Option<u32>, message: &str) {
+    match line {
+        Some(line) => log::log!(level, file = file, line = line; "{message}"),
+        None => log::log!(level, "{file}: {message}"),
+    }
+}
+
+/// Find the known key closest to a misspelled one, if any are close enough to
+/// be what was meant
+fn closest_key<'k>(key: &str, known: &[&'k str]) -> Option<&'k str> {
+    known
+        .iter()
+        .map(|k| (edit_distance(key, k), *k))
+        .filter(|(dist, k)| *dist <= (k.len() / 3).max(2))
+        .min_by_key(|(dist, _)| *dist)
+        .map(|(_, k)| k)
+}
+
+/// The number of characters that have to be inserted, removed or replaced to
+/// turn one string into another
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(cur)
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+fn merge_config_tables(into: &mut toml::Table, from: toml::Table) {
+    for (key, value) in from {
+        let value = match (into.get_mut(&key), value) {
+            (Some(toml::Value::Table(table)), toml::Value::Table(other)) => {
+                merge_config_tables(table, other);
+                continue;
+            }
+            (Some(toml::Value::Array(array)), toml::Value::Array(other)) => {
+                array.extend(other);
+                continue;
+            }
+            (_, value) => value,
+        };
+        into.insert(key, value);
     }
 }
 
@@ -205,17 +696,273 @@ impl Config {
         input_dir: PathBuf,
         output_dir: PathBuf,
         output_url: Option<UrlPath>,
-    ) -> Result<Arc<Config>, String> {
-        let mut config: Config = toml::from_str(
-            &fs::read_to_string(input_dir.join("flash.toml"))
-                .map_err(|e| format!("Unable to read flash.toml: {e}"))?,
-        )
-        .map_err(|e| format!("Unable to parse config: {e}"))?;
+    ) -> Result<Config, String> {
+        let mut config: Config =
+            toml::Value::Table(read_config_table(&input_dir.join("flash.toml"), 0)?)
+                .try_into()
+                .map_err(|e| format!("Unable to parse config: {e}"))?;
+
+        // The extra head content may also be a path to a file with it
+        if let Some(ref mut html) = config.html
+            && !html.head_extra.is_empty()
+            && input_dir.join(&html.head_extra).is_file()
+        {
+            html.head_extra = fs::read_to_string(input_dir.join(&html.head_extra))
+                .map_err(|e| format!("Unable to read extra head content: {e}"))?;
+        }
+
+        if !matches!(config.analysis.driver.as_str(), "clang" | "cl") {
+            return Err(format!(
+                "Unable to use analysis.driver '{}': expected clang or cl",
+                config.analysis.driver
+            ));
+        }
+
+        if !matches!(config.analysis.language.as_str(), "c++" | "c") {
+            return Err(format!(
+                "Unable to use analysis.language '{}': expected c++ or c",
+                config.analysis.language
+            ));
+        }
+
+        if config.manifest_file.is_empty()
+            || config.manifest_file.contains(['/', '\\'])
+            || matches!(config.manifest_file.as_str(), "." | "..")
+        {
+            return Err(format!(
+                "Unable to use manifest-file '{}': expected a file name",
+                config.manifest_file
+            ));
+        }
+
+        if config.source_pattern().is_none() {
+            return Err(format!(
+                "Unable to use project.host '{}': expected github, gitlab, gitea, \
+                sourcehut or a pattern with {{path}}",
+                config.project.host
+            ));
+        }
+
+        if let Some(ref cppreference) = config.cppreference {
+            config.cppreference_pages = load_index(&input_dir.join(&cppreference.index))?;
+        }
+
+        // Load the translations of UI strings for every locale up front
+        if let Some(ref i18n) = config.i18n
+            && let Some(ref dir) = i18n.dir
+        {
+            let dir = input_dir.join(dir);
+            for locale in i18n.locales.keys().chain([&i18n.locale]) {
+                config
+                    .strings
+                    .insert(locale.clone(), load_strings(&dir, locale)?);
+            }
+        }
+
+        // Partials are included once here so pages don't have to do it
+        let partials_dir = config.templates.dir.as_ref().map(|d| input_dir.join(d));
+        let templates = &mut config.templates;
+        for template in [
+            &mut templates.class,
+            &mut templates.struct_,
+            &mut templates.function,
+            &mut templates.variable,
+            &mut templates.group,
+            &mut templates.namespace,
+            &mut templates.source,
+            &mut templates.head,
+            &mut templates.nav,
+            &mut templates.file,
+            &mut templates.page,
+            &mut templates.tutorial,
+            &mut templates.tutorial_index,
+        ] {
+            *template = Arc::from(expand_partials_in(template, partials_dir.as_deref(), 0)?);
+        }
+
+        // Tutorials can pick any template in the templates directory, which
+        // are read once here instead of for every page that uses them
+        if let Some(ref dir) = partials_dir {
+            for path in fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .map(|e| e.path())
+            {
+                let Some(name) = path
+                    .file_stem()
+                    .filter(|_| path.extension().is_some_and(|e| e == "html"))
+                    .map(|s| s.to_string_lossy().into_owned())
+                else {
+                    continue;
+                };
+                match fs::read_to_string(&path)
+                    .map_err(|e| {
+                        format!("Unable to read template '{}': {e}", path.to_string_lossy())
+                    })
+                    .and_then(|t| expand_partials_in(&t, Some(dir.as_path()), 0))
+                {
+                    Ok(template) => {
+                        config.page_templates.insert(name, Arc::from(template));
+                    }
+                    Err(e) => warn!("{e}"),
+                }
+            }
+        }
 
         config.input_dir = input_dir;
         config.output_dir = output_dir;
         config.output_url = output_url;
-        Ok(Arc::from(config))
+        Ok(config)
+    }
+
+    /// Get the locale of the docs, or of the tutorials translated to another
+    /// language if the page is in one of their trees
+    pub fn locale_of(&self, url: &UrlPath) -> &str {
+        let Some(ref i18n) = self.i18n else {
+            return "en";
+        };
+        i18n.locales
+            .keys()
+            .find(|code| url.starts_with(UrlPath::part(code)))
+            .unwrap_or(&i18n.locale)
+    }
+
+    /// Check if the project is written in C instead of C++
+    pub fn is_c(&self) -> bool {
+        self.analysis.language == "c"
+    }
+
+    /// Get a UI string in the locale of the docs
+    pub fn tr(&self, key: &str) -> String {
+        self.tr_in(self.i18n.as_ref().map_or("en", |i| i.locale.as_str()), key)
+    }
+
+    /// Get a UI string in the given locale, falling back to English if there
+    /// is no translation for it
+    pub fn tr_in(&self, locale: &str, key: &str) -> String {
+        self.strings
+            .get(locale)
+            .and_then(|s| s.get(key))
+            .cloned()
+            .unwrap_or_else(|| default_string(key).to_owned())
+    }
+
+    /// Get the pattern for links to files in the project's repository, which
+    /// is appended to `project.tree` if it is set for the built-in hosts
+    fn source_pattern(&self) -> Option<&str> {
+        let preset = |pattern| {
+            if self.project.tree.is_some() {
+                "{tree}/{path}#L{line}"
+            } else {
+                pattern
+            }
+        };
+        match self.project.host.as_str() {
+            "github" => Some(preset("{repository}/blob/{branch}/{path}#L{line}")),
+            "gitlab" => Some(preset("{repository}/-/blob/{branch}/{path}#L{line}")),
+            "gitea" => Some(preset("{repository}/src/branch/{branch}/{path}#L{line}")),
+            "sourcehut" => Some(preset("{repository}/tree/{branch}/item/{path}#L{line}")),
+            custom if custom.contains("{path}") => Some(custom),
+            _ => None,
+        }
+    }
+
+    /// Get the online URL of a file in the project, or of a line in it, if
+    /// the project has a tree, or a repository and branch, set
+    pub fn source_url(&self, path: &UrlPath, line: Option<u32>) -> Option<String> {
+        let mut pattern = self.source_pattern()?;
+        // Links to whole files leave out the line anchor
+        if line.is_none()
+            && let Some((file, _)) = pattern.split_once('#')
+        {
+            pattern = file;
+        }
+        let vars = HashMap::from([
+            (
+                "tree".to_owned(),
+                self.project.tree.clone().unwrap_or_default(),
+            ),
+            (
+                "repository".to_owned(),
+                self.project.repository.clone().unwrap_or_default(),
+            ),
+            (
+                "branch".to_owned(),
+                self.project.branch.clone().unwrap_or_default(),
+            ),
+            ("path".to_owned(), path.url_safe_parts().join("/")),
+            ("line".to_owned(), line.unwrap_or_default().to_string()),
+        ]);
+        let uses = |var: &str| pattern.contains(&format!("{{{var}}}"));
+        // Links to a guessed branch would be broken if it's called something
+        // else, so they're only made if the branch is set
+        if (uses("tree") && self.project.tree.is_none())
+            || (uses("repository") && self.project.repository.is_none())
+            || (uses("branch") && self.project.branch.is_none())
+        {
+            return None;
+        }
+        strfmt(pattern, &vars).ok()
+    }
+
+    /// Get the page of a std item on cppreference, or a search for it if it's
+    /// not in the index
+    pub fn cppreference_url(&self, name: &str) -> UrlPath {
+        match self.cppreference_pages.get(name) {
+            Some(page) => UrlPath::parse(&format!("https://en.cppreference.com/w/{page}")),
+            None => UrlPath::parse("https://en.cppreference.com/mwiki/index.php").map(|url| {
+                url.with_query(&format!(
+                    "search={}",
+                    utf8_percent_encode(name, URL_RESERVED)
+                ))
+            }),
+        }
+        .unwrap_or_else(|_| UrlPath::new())
+    }
+
+    /// Get the URL of a category of API docs like `classes`, which may be
+    /// remapped in the config to another path like `api/classes`
+    pub fn category_url(&self, category: &str) -> UrlPath {
+        match self.categories.get(category).and_then(|c| c.slug.as_ref()) {
+            Some(slug) => UrlPath::parse(slug).unwrap_or_else(|_| UrlPath::part(slug)),
+            // Structs share their pages with classes unless told otherwise
+            None if category == "structs" => self.category_url("classes"),
+            None => UrlPath::part(category),
+        }
+    }
+
+    /// Get the display name of a category of API docs like `classes`
+    pub fn category_name(&self, category: &str) -> String {
+        self.categories
+            .get(category)
+            .and_then(|c| c.name.clone())
+            .unwrap_or_else(|| {
+                let mut chars = category.chars();
+                chars
+                    .next()
+                    .map(|c| c.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            })
+    }
+
+    /// Get the category of API docs a page is in from its URL, if any
+    pub fn category_of(&self, url: &UrlPath) -> Option<&'static str> {
+        ["namespaces", "classes", "structs", "functions", "variables"]
+            .into_iter()
+            .find(|c| url.starts_with(self.category_url(c)))
+    }
+
+    /// Get a markdown style by name, with styles from the config taking
+    /// priority over the built-in ones
+    pub fn markdown_style(&self, name: &str) -> Option<MarkdownStyle> {
+        self.markdown
+            .as_ref()
+            .and_then(|m| m.styles.get(name).cloned())
+            .or_else(|| match name {
+                "qna" => Some(MarkdownStyle::qna()),
+                _ => None,
+            })
     }
 
     pub fn all_includes(&self) -> Vec<PathBuf> {
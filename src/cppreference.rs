@@ -0,0 +1,49 @@
+use regex_lite::Regex;
+use std::{collections::HashMap, fs, path::Path};
+
+/// Load the pages of std items on cppreference from an index, which is either
+/// the Doxygen tag file cppreference publishes (`cppreference-doxygen-web.tag.xml`)
+/// or a TOML table of names to pages like `"std::vector" = "cpp/container/vector"`
+pub fn load_index(path: &Path) -> Result<HashMap<String, String>, String> {
+    let data =
+        fs::read_to_string(path).map_err(|e| format!("Unable to read cppreference index: {e}"))?;
+    if path.extension().is_some_and(|e| e == "xml") {
+        Ok(parse_tag_file(&data))
+    } else {
+        toml::from_str(&data).map_err(|e| format!("Unable to parse cppreference index: {e}"))
+    }
+}
+
+/// Get the pages of classes, functions and their members from a Doxygen tag
+/// file
+fn parse_tag_file(data: &str) -> HashMap<String, String> {
+    let compound = Regex::new(r"(?s)<compound[^>]*>(.*?)</compound>").unwrap();
+    let page = Regex::new(r"(?s)^\s*<name>(.*?)</name>\s*<filename>(.*?)</filename>").unwrap();
+    let member = Regex::new(
+        r"(?s)<member[^>]*>.*?<name>(.*?)</name>.*?<anchorfile>(.*?)</anchorfile>.*?</member>",
+    )
+    .unwrap();
+
+    let mut res = HashMap::new();
+    for caps in compound.captures_iter(data) {
+        let body = &caps[1];
+        let Some(own) = page.captures(body) else {
+            continue;
+        };
+        let name = unescape(&own[1]);
+        for member in member.captures_iter(body) {
+            res.entry(format!("{name}::{}", unescape(&member[1])))
+                .or_insert_with(|| unescape(&member[2]));
+        }
+        res.insert(name, unescape(&own[2]));
+    }
+    res
+}
+
+fn unescape(text: &str) -> String {
+    text.trim()
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
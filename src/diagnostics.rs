@@ -0,0 +1,184 @@
+use std::{
+    collections::HashMap,
+    fmt::Arguments,
+    sync::{
+        OnceLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use clang::Entity;
+use log::{error, warn};
+use serde::Deserialize;
+
+use crate::builder::traits::EntityMethods;
+
+/// How a kind of warning is reported, set per kind in the `warnings` config
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum WarningLevel {
+    Ignore,
+    Warn,
+    /// Print the warning as an error and fail the build once it's done
+    Error,
+}
+
+/// Warnings about the documented code and its docs that can be configured
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WarningKind {
+    /// A public entity has no doc comment
+    Undocumented,
+    /// A `@see` reference doesn't point to anything
+    UnresolvedReference,
+    /// A doc comment command is missing its parameter or value
+    CommentSyntax,
+    /// A namespace has nothing documented in it
+    EmptyNamespace,
+    /// An entity's name looks compiler-generated
+    InternalIdentifier,
+    /// A group is used with `@ingroup` but never defined
+    UnknownGroup,
+    /// Two headings on a page have the same id
+    DuplicateHeadingId,
+    /// Two output paths only differ in case
+    UrlCollision,
+    /// A code example that looks like a file name couldn't be read
+    MissingExample,
+    /// A `[[Page]]` link in a tutorial doesn't match any tutorial
+    DanglingWikiLink,
+    /// The metadata at the start of a tutorial couldn't be parsed
+    InvalidMetadata,
+}
+
+impl WarningKind {
+    pub const ALL: &[WarningKind] = &[
+        WarningKind::Undocumented,
+        WarningKind::UnresolvedReference,
+        WarningKind::CommentSyntax,
+        WarningKind::EmptyNamespace,
+        WarningKind::InternalIdentifier,
+        WarningKind::UnknownGroup,
+        WarningKind::DuplicateHeadingId,
+        WarningKind::UrlCollision,
+        WarningKind::MissingExample,
+        WarningKind::DanglingWikiLink,
+        WarningKind::InvalidMetadata,
+    ];
+
+    /// The name used for this kind in the config and with `@nowarn`
+    pub fn name(&self) -> &'static str {
+        match self {
+            WarningKind::Undocumented => "undocumented",
+            WarningKind::UnresolvedReference => "unresolved-reference",
+            WarningKind::CommentSyntax => "comment-syntax",
+            WarningKind::EmptyNamespace => "empty-namespace",
+            WarningKind::InternalIdentifier => "internal-identifier",
+            WarningKind::UnknownGroup => "unknown-group",
+            WarningKind::DuplicateHeadingId => "duplicate-heading-id",
+            WarningKind::UrlCollision => "url-collision",
+            WarningKind::MissingExample => "missing-example",
+            WarningKind::DanglingWikiLink => "dangling-wiki-link",
+            WarningKind::InvalidMetadata => "invalid-metadata",
+        }
+    }
+
+    /// How this kind is reported if the config doesn't say
+    fn default_level(&self) -> WarningLevel {
+        match self {
+            WarningKind::DanglingWikiLink => WarningLevel::Error,
+            _ => WarningLevel::Warn,
+        }
+    }
+}
+
+static LEVELS: OnceLock<HashMap<String, WarningLevel>> = OnceLock::new();
+static ERRORS: AtomicUsize = AtomicUsize::new(0);
+
+/// Set the levels from the `warnings` config. Kinds that aren't listed use
+/// their default level
+pub fn set_levels(levels: HashMap<String, WarningLevel>) {
+    for name in levels.keys() {
+        if !WarningKind::ALL.iter().any(|kind| kind.name() == name) {
+            warn!("Unknown warning kind '{name}' in the warnings config");
+        }
+    }
+    let _ = LEVELS.set(levels);
+}
+
+fn level(kind: WarningKind) -> WarningLevel {
+    LEVELS
+        .get()
+        .and_then(|levels| levels.get(kind.name()))
+        .copied()
+        .unwrap_or(kind.default_level())
+}
+
+/// Get the warning kinds suppressed with `@nowarn` in a doc comment. A bare
+/// `@nowarn` suppresses every kind, which is returned as `*`
+pub fn nowarn_kinds(raw: &str) -> Vec<String> {
+    let mut res = Vec::new();
+    for line in raw.lines() {
+        let Some((_, rest)) = line.split_once("@nowarn") else {
+            continue;
+        };
+        if rest.starts_with(|c: char| c.is_alphanumeric()) {
+            continue;
+        }
+        let kinds = rest
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .take_while(|word| !word.starts_with('@') && word != &"*/")
+            .filter(|word| !word.is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>();
+        if kinds.is_empty() {
+            res.push(String::from("*"));
+        }
+        res.extend(kinds);
+    }
+    res
+}
+
+/// Check if warnings of a kind are suppressed with `@nowarn` in a doc comment
+pub fn is_suppressed(nowarn: &[String], kind: WarningKind) -> bool {
+    nowarn.iter().any(|k| k == "*" || k == kind.name())
+}
+
+/// Report a warning at a location, which may be empty if it isn't about any
+/// particular file
+pub fn report(kind: WarningKind, (file, line): (&str, u32), message: Arguments) {
+    match level(kind) {
+        WarningLevel::Ignore => {}
+        WarningLevel::Warn => warn!(
+            file = file, line = line;
+            "{message} [{}]", kind.name()
+        ),
+        WarningLevel::Error => {
+            ERRORS.fetch_add(1, Ordering::Relaxed);
+            error!(
+                file = file, line = line;
+                "{message} [{}]", kind.name()
+            );
+        }
+    }
+}
+
+/// Report a warning about an entity unless its doc comment suppresses it
+pub fn report_for(kind: WarningKind, entity: &Entity, message: Arguments) {
+    if entity
+        .get_comment()
+        .is_some_and(|c| is_suppressed(&nowarn_kinds(&c), kind))
+    {
+        return;
+    }
+    let (file, line) = entity.source_location();
+    report(kind, (&file, line), message);
+}
+
+/// Fail if any warnings were configured to be errors
+pub fn check_errors() -> Result<(), String> {
+    match ERRORS.load(Ordering::Relaxed) {
+        0 => Ok(()),
+        1 => Err(String::from("1 warning was treated as an error")),
+        n => Err(format!("{n} warnings were treated as errors")),
+    }
+}
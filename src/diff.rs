@@ -0,0 +1,288 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    config::Config,
+    html::{GenHtml, Html, HtmlElement, HtmlText},
+    url::strip_path_prefix,
+};
+
+/// The public API of a project as saved to `api.json` when building docs
+#[derive(Serialize, Deserialize)]
+pub struct ApiDump {
+    pub project: String,
+    pub version: String,
+    pub symbols: Vec<ApiSymbol>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ApiSymbol {
+    /// Fully qualified name, like `geode::Mod::get`
+    pub name: String,
+    pub kind: String,
+    /// The declaration as written, without the body
+    pub signature: String,
+    /// Absolute URL of the docs for this symbol
+    pub url: String,
+}
+
+/// A symbol whose declarations differ between the versions, which includes
+/// overloads being added or removed
+struct Change {
+    name: String,
+    old: Vec<String>,
+    new: Vec<String>,
+}
+
+struct ApiDiff {
+    added: Vec<ApiSymbol>,
+    removed: Vec<ApiSymbol>,
+    changed: Vec<Change>,
+}
+
+impl ApiDiff {
+    fn new(old: &ApiDump, new: &ApiDump) -> Self {
+        let group = |dump: &ApiDump| {
+            let mut res: BTreeMap<String, (ApiSymbol, BTreeSet<String>)> = BTreeMap::new();
+            for symbol in &dump.symbols {
+                res.entry(symbol.name.clone())
+                    .or_insert_with(|| (symbol.clone(), BTreeSet::new()))
+                    .1
+                    .insert(normalize_signature(&symbol.signature));
+            }
+            res
+        };
+        let old = group(old);
+        let new = group(new);
+
+        let mut res = Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        };
+        for (name, (symbol, sigs)) in &new {
+            match old.get(name) {
+                None => res.added.push(symbol.clone()),
+                Some((_, old_sigs)) if old_sigs != sigs => res.changed.push(Change {
+                    name: name.clone(),
+                    old: old_sigs.difference(sigs).cloned().collect(),
+                    new: sigs.difference(old_sigs).cloned().collect(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for (name, (symbol, _)) in &old {
+            if !new.contains_key(name) {
+                res.removed.push(symbol.clone());
+            }
+        }
+        res
+    }
+
+    /// Check if code using the old version might not compile with the new one
+    fn is_breaking(&self) -> bool {
+        !self.removed.is_empty() || self.changed.iter().any(|c| !c.old.is_empty())
+    }
+}
+
+/// Collapse the whitespace in a declaration so formatting changes aren't
+/// reported as changes to the API
+fn normalize_signature(sig: &str) -> String {
+    sig.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Build the docs for a git ref of the project in a temporary worktree and
+/// get the path to its API dump
+fn build_ref(config: Arc<Config>, git_ref: &str, skip_build: bool) -> Result<PathBuf, String> {
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&config.input_dir)
+            .output()
+            .map_err(|e| format!("Unable to run git: {e}"))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+        } else {
+            Err(format!(
+                "Unable to run git {}: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    };
+
+    // The input directory might be a subdirectory of the repository
+    let top = PathBuf::from(git(&["rev-parse", "--show-toplevel"])?);
+    let rel_input = strip_path_prefix(&config.input_dir, &top).unwrap_or_default();
+
+    let name = git_ref.replace(|c: char| !c.is_alphanumeric(), "-");
+    let worktree = config.output_dir.join("_diff").join(&name);
+    let output = config.output_dir.join("_diff").join(format!("{name}-docs"));
+    if worktree.exists() {
+        git(&["worktree", "remove", "--force", &worktree.to_string_lossy()])?;
+    }
+    git(&[
+        "worktree",
+        "add",
+        "--detach",
+        &worktree.to_string_lossy(),
+        git_ref,
+    ])?;
+
+    info!("Building docs for {git_ref}");
+    let exe =
+        std::env::current_exe().map_err(|e| format!("Unable to find the Flash executable: {e}"))?;
+    let mut cmd = Command::new(exe);
+    cmd.arg("--input")
+        .arg(worktree.join(rel_input))
+        .arg("--output")
+        .arg(&output)
+        .args(["--overwrite", "--quiet"]);
+    if skip_build {
+        cmd.arg("--skip-build");
+    }
+    let status = cmd
+        .status()
+        .map_err(|e| format!("Unable to build docs for {git_ref}: {e}"));
+
+    // Clean up the worktree even if building failed
+    git(&["worktree", "remove", "--force", &worktree.to_string_lossy()])?;
+    if !status?.success() {
+        return Err(format!("Unable to build docs for {git_ref}"));
+    }
+    Ok(output.join("api.json"))
+}
+
+/// Load one side of a diff, which is either a saved `api.json` or a git ref
+fn load_dump(config: Arc<Config>, source: &str, skip_build: bool) -> Result<ApiDump, String> {
+    let path = if Path::new(source).is_file() {
+        PathBuf::from(source)
+    } else {
+        build_ref(config, source, skip_build)?
+    };
+    let data = fs::read_to_string(&path)
+        .map_err(|e| format!("Unable to read API dump {}: {e}", path.to_string_lossy()))?;
+    serde_json::from_str(&data)
+        .map_err(|e| format!("Unable to parse API dump {}: {e}", path.to_string_lossy()))
+}
+
+fn fmt_symbol_list(title: &str, symbols: &[ApiSymbol]) -> Html {
+    HtmlElement::new("section")
+        .with_child(HtmlElement::new("h2").with_text(format!("{title} ({})", symbols.len())))
+        .with_child(
+            HtmlElement::new("ul").with_children(
+                symbols
+                    .iter()
+                    .map(|symbol| {
+                        HtmlElement::new("li")
+                            .with_child(
+                                HtmlElement::new("a")
+                                    .with_attr("href", &symbol.url)
+                                    .with_child(HtmlElement::new("code").with_text(&symbol.name)),
+                            )
+                            .with_text(format!(" ({})", symbol.kind))
+                            .into()
+                    })
+                    .collect(),
+            ),
+        )
+        .into()
+}
+
+fn fmt_diff_page(old: &ApiDump, new: &ApiDump, diff: &ApiDiff) -> String {
+    let title = format!("What's new in {} {}", new.project, new.version);
+    let changed = HtmlElement::new("section")
+        .with_child(HtmlElement::new("h2").with_text(format!("Changed ({})", diff.changed.len())))
+        .with_children(
+            diff.changed
+                .iter()
+                .map(|change| {
+                    HtmlElement::new("div")
+                        .with_child(
+                            HtmlElement::new("h3")
+                                .with_child(HtmlElement::new("code").with_text(&change.name)),
+                        )
+                        .with_child(
+                            HtmlElement::new("pre").with_text(
+                                change
+                                    .old
+                                    .iter()
+                                    .map(|s| format!("- {s}"))
+                                    .chain(change.new.iter().map(|s| format!("+ {s}")))
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            ),
+                        )
+                        .into()
+                })
+                .collect(),
+        );
+
+    let body = HtmlElement::new("body")
+        .with_child(HtmlElement::new("h1").with_text(&title))
+        .with_child(
+            HtmlElement::new("p")
+                .with_text(format!("Changes to the public API since {}", old.version)),
+        )
+        .with_child(fmt_symbol_list("Added", &diff.added))
+        .with_child(fmt_symbol_list("Removed", &diff.removed))
+        .with_child(changed);
+
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{}</title></head>{}</html>",
+        HtmlText::new(&title).gen_html(),
+        body.gen_html()
+    )
+}
+
+/// Compare the public API of two versions, writing the differences to
+/// `diff.json` for tools and `whats-new.html` for people
+pub fn diff_apis(
+    config: Arc<Config>,
+    old: &str,
+    new: &str,
+    skip_build: bool,
+) -> Result<(), String> {
+    let old = load_dump(config.clone(), old, skip_build)?;
+    let new = load_dump(config.clone(), new, skip_build)?;
+    let diff = ApiDiff::new(&old, &new);
+    info!(
+        "{} added, {} removed, {} changed",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len()
+    );
+
+    let data = json!({
+        "old": old.version,
+        "new": new.version,
+        "breaking": diff.is_breaking(),
+        "added": diff.added,
+        "removed": diff.removed,
+        "changed": diff.changed.iter().map(|c| json!({
+            "name": c.name,
+            "old": c.old,
+            "new": c.new,
+        })).collect::<Vec<_>>(),
+    });
+    fs::write(
+        config.output_dir.join("diff.json"),
+        serde_json::to_string_pretty(&data).unwrap(),
+    )
+    .map_err(|e| format!("Unable to save API diff: {e}"))?;
+    fs::write(
+        config.output_dir.join("whats-new.html"),
+        fmt_diff_page(&old, &new, &diff),
+    )
+    .map_err(|e| format!("Unable to save API diff: {e}"))?;
+    Ok(())
+}
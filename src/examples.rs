@@ -0,0 +1,152 @@
+use std::{fs, path::PathBuf, sync::Arc};
+
+use clang::diagnostic::Severity;
+use glob::glob;
+use log::{error, info};
+use regex_lite::Regex;
+
+use crate::{
+    builder::{
+        comment::{load_example_file, parse_code_examples},
+        markdown::{expand_includes, extract_cpp_blocks},
+    },
+    config::Config,
+};
+
+/// A C++ code example found in a tutorial or a doc comment
+struct Snippet {
+    file: PathBuf,
+    line: usize,
+    code: String,
+}
+
+fn line_at(data: &str, offset: usize) -> usize {
+    data[..offset].matches('\n').count() + 1
+}
+
+fn collect_tutorial_snippets(config: Arc<Config>) -> Vec<Snippet> {
+    let Some(ref tutorials) = config.tutorials else {
+        return Vec::new();
+    };
+    let dir = config.input_dir.join(&tutorials.dir);
+    let Ok(files) = glob(dir.join("**").join("*.md").to_str().unwrap()) else {
+        return Vec::new();
+    };
+
+    let mut res = Vec::new();
+    for file in files.flatten() {
+        let Ok(data) = fs::read_to_string(&file) else {
+            continue;
+        };
+        // Included snippets are checked too, though their lines are then
+        // reported relative to the expanded document
+        let data = expand_includes(&data, file.parent().unwrap_or(dir.as_path()));
+        res.extend(
+            extract_cpp_blocks(&data)
+                .into_iter()
+                .map(|(code, offset)| Snippet {
+                    file: file.clone(),
+                    line: line_at(&data, offset),
+                    code,
+                }),
+        );
+    }
+    res
+}
+
+fn collect_comment_snippets(config: Arc<Config>) -> Vec<Snippet> {
+    let comment = Regex::new(r"(?s:/\*\*.*?\*/)|(?m:(?:^[ \t]*///[^\n]*\n?)+)").unwrap();
+
+    let mut res = Vec::new();
+    for file in config.all_includes() {
+        let path = config.input_dir.join(&file);
+        let Ok(data) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for found in comment.find_iter(&data) {
+            res.extend(
+                parse_code_examples(found.as_str())
+                    .into_iter()
+                    .map(|code| Snippet {
+                        file: path.clone(),
+                        line: line_at(&data, found.start()),
+                        code: load_example_file(config.clone(), &code).unwrap_or(code),
+                    }),
+            );
+        }
+    }
+    res
+}
+
+/// Put the configured includes and prelude before the code of an example
+fn wrap_snippet(config: Arc<Config>, code: &str) -> String {
+    let examples = config.examples.as_ref();
+    let includes = examples
+        .map(|e| e.includes.clone())
+        .filter(|i| !i.is_empty())
+        .unwrap_or_else(|| config.all_includes());
+
+    let mut data = String::from("// File generated by Flash for checking a code example\n");
+    for hdr in includes {
+        data += &format!("#include <{}>\n", hdr.to_string_lossy());
+    }
+    if let Some(examples) = examples {
+        data += &examples.prelude;
+        data += "\n";
+    }
+    data += code;
+    data
+}
+
+/// Compile every C++ code example in the tutorials and doc comments on its
+/// own, failing if any of them don't compile
+pub fn check_examples(config: Arc<Config>, args: &[String]) -> Result<(), String> {
+    let clang = clang::Clang::new()?;
+    let index = clang::Index::new(&clang, false, false);
+
+    let snippets = collect_tutorial_snippets(config.clone())
+        .into_iter()
+        .chain(collect_comment_snippets(config.clone()))
+        .collect::<Vec<_>>();
+    info!("Checking {} code examples", snippets.len());
+
+    let path = config.output_dir.join("_check_example.cpp");
+    let mut failed = 0;
+    for snippet in &snippets {
+        fs::write(&path, wrap_snippet(config.clone(), &snippet.code))
+            .map_err(|e| format!("Unable to create source file for code example: {e}"))?;
+
+        let file = snippet.file.to_string_lossy().to_string();
+        let errors = match index.parser(&path).arguments(args).parse() {
+            Ok(unit) => unit
+                .get_diagnostics()
+                .into_iter()
+                .filter(|d| matches!(d.get_severity(), Severity::Error | Severity::Fatal))
+                .map(|d| d.get_text())
+                .collect::<Vec<_>>(),
+            Err(e) => vec![e.to_string()],
+        };
+        if !errors.is_empty() {
+            failed += 1;
+            for e in errors {
+                error!(
+                    file = file.as_str(), line = snippet.line;
+                    "Code example does not compile: {e}"
+                );
+            }
+        }
+    }
+
+    // We don't really care if we can remove the file or not
+    drop(fs::remove_file(path));
+
+    if failed == 0 {
+        info!("All {} code examples compile", snippets.len());
+        Ok(())
+    } else {
+        Err(format!(
+            "{failed} of {} code examples do not compile",
+            snippets.len()
+        ))
+    }
+}
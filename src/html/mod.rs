@@ -1,7 +1,72 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 pub mod process;
 
+static PRETTY: AtomicBool = AtomicBool::new(false);
+
+/// Elements that are put on their own lines when pretty printing
+const BLOCK_TAGS: &[&str] = &[
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "thead",
+    "tr",
+    "ul",
+];
+
+/// Elements where whitespace matters, so they are never pretty printed
+const PREFORMATTED_TAGS: &[&str] = &["pre", "code", "textarea"];
+
+/// Elements that can't have children or a closing tag
+const VOID_TAGS: &[&str] = &["br", "hr", "img", "input", "link", "meta", "source", "wbr"];
+
+/// Indent generated HTML and put block elements on their own lines instead of
+/// generating it all on one line, which makes debugging templates easier
+pub fn set_pretty(pretty: bool) {
+    PRETTY.store(pretty, Ordering::Relaxed);
+}
+
+fn is_pretty() -> bool {
+    PRETTY.load(Ordering::Relaxed)
+}
+
+/// Generate each item on its own line, indented to the given depth
+fn gen_lines(items: Vec<Html>, depth: usize) -> String {
+    let indent = "    ".repeat(depth);
+    items
+        .into_iter()
+        .map(|i| i.gen_html_at(depth))
+        .filter(|i| !i.is_empty())
+        .map(|i| format!("\n{indent}{i}"))
+        .collect()
+}
+
 pub trait GenHtml: Into<Html> {
     fn gen_html(self) -> String;
 }
@@ -15,10 +80,15 @@ pub enum Html {
     /// element connecting them
     List(HtmlList),
     /// Raw HTML data
-    Raw(String),
+    Raw(RawHtml),
 }
 
 impl Html {
+    /// Nothing, for when there is no content to show
+    pub fn empty() -> Html {
+        Html::Raw(RawHtml::trusted(String::new()))
+    }
+
     /// Helper for creating a <p> element
     pub fn p<T: AsRef<str>>(text: T) -> Html {
         HtmlElement::new("p").with_text(text).into()
@@ -36,17 +106,70 @@ impl Html {
     }
 
     pub fn feather(icon: &str) -> Html {
-        HtmlElement::new("i").with_attr("data-feather", icon).into()
+        HtmlElement::new("i")
+            .with_attr("data-feather", icon)
+            .with_attr("aria-hidden", "true")
+            .into()
+    }
+
+    /// Whether this would be put on its own line when pretty printing
+    fn is_block(&self) -> bool {
+        match self {
+            Self::Element(e) => BLOCK_TAGS.contains(&e.tag.as_str()),
+            Self::List(l) => l.list.iter().all(|i| i.is_block()),
+            Self::Text(_) => false,
+            // Empty content doesn't stop its siblings from being put on
+            // their own lines
+            Self::Raw(s) => s.0.trim().is_empty(),
+        }
+    }
+
+    fn gen_html_at(self, depth: usize) -> String {
+        match self {
+            Self::Element(e) => e.gen_html_at(depth),
+            Self::Text(t) => t.gen_html(),
+            Self::List(l) => l.gen_html_at(depth),
+            Self::Raw(s) => s.0,
+        }
     }
 }
 
 impl GenHtml for Html {
     fn gen_html(self) -> String {
+        self.gen_html_at(0)
+    }
+}
+
+/// HTML that is inserted into the page as-is, without any escaping. Only
+/// create this from HTML that Flash generated or sanitized itself, never from
+/// text that came from the docs or the config
+pub struct RawHtml(String);
+
+impl RawHtml {
+    pub fn trusted<T: Into<String>>(html: T) -> Self {
+        Self(html.into())
+    }
+}
+
+impl From<RawHtml> for Html {
+    fn from(val: RawHtml) -> Self {
+        Html::Raw(val)
+    }
+}
+
+/// The value of an attribute of a HTML element
+pub enum AttrValue {
+    /// A text value, which is escaped when generating the HTML
+    Text(String),
+    /// A boolean attribute like `hidden`, which has no value
+    Flag,
+}
+
+impl AttrValue {
+    fn gen_html(&self, name: &str) -> String {
         match self {
-            Self::Element(e) => e.gen_html(),
-            Self::Text(t) => t.gen_html(),
-            Self::List(l) => l.gen_html(),
-            Self::Raw(s) => s,
+            Self::Text(value) => format!("{name}=\"{}\"", escape_attr(value)),
+            Self::Flag => name.to_owned(),
         }
     }
 }
@@ -54,7 +177,7 @@ impl GenHtml for Html {
 pub struct HtmlElement {
     tag: String,
     classes: Vec<String>,
-    attributes: HashMap<String, String>,
+    attributes: HashMap<String, AttrValue>,
     children: Vec<Html>,
 }
 
@@ -131,19 +254,30 @@ impl HtmlElement {
         self.attributes.contains_key(attr)
     }
 
-    pub fn attr_mut(&mut self, attr: &str) -> &mut String {
-        self.attributes.entry(attr.into()).or_default()
+    pub fn attr(&self, attr: &str) -> Option<&AttrValue> {
+        self.attributes.get(attr)
     }
 
     pub fn with_attrs(mut self, attrs: &Vec<(String, String)>) -> Self {
         for (attr, value) in attrs {
-            *self.attr_mut(attr) = value.to_string();
+            self = self.with_attr(attr, value);
         }
         self
     }
 
     pub fn with_attr<V: ToString>(mut self, attr: &str, value: V) -> Self {
-        *self.attr_mut(attr) = value.to_string();
+        self.attributes
+            .insert(attr.into(), AttrValue::Text(value.to_string()));
+        self
+    }
+
+    /// Add a boolean attribute like `hidden` or `open` if `value` is true
+    pub fn with_bool_attr(mut self, attr: &str, value: bool) -> Self {
+        if value {
+            self.attributes.insert(attr.into(), AttrValue::Flag);
+        } else {
+            self.attributes.remove(attr);
+        }
         self
     }
 
@@ -156,32 +290,48 @@ impl HtmlElement {
     }
 }
 
+impl HtmlElement {
+    fn gen_html_at(self, depth: usize) -> String {
+        let mut html = format!("<{}", self.tag);
+        if !self.classes.is_empty() {
+            html += &format!(" class=\"{}\"", escape_attr(&self.classes.join(" ")));
+        }
+        // Sorted so the same page always generates the same HTML
+        let mut attrs = self.attributes.iter().collect::<Vec<_>>();
+        attrs.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, value) in attrs {
+            html.push(' ');
+            html += &value.gen_html(name);
+        }
+        html.push('>');
+
+        if VOID_TAGS.contains(&self.tag.as_str()) {
+            return html;
+        }
+
+        if is_pretty()
+            && !PREFORMATTED_TAGS.contains(&self.tag.as_str())
+            && self.children.iter().all(Html::is_block)
+        {
+            let lines = gen_lines(self.children, depth + 1);
+            if !lines.is_empty() {
+                html += &lines;
+                html += &format!("\n{}", "    ".repeat(depth));
+            }
+        } else {
+            for child in self.children {
+                html += &child.gen_html_at(depth + 1);
+            }
+        }
+
+        html += &format!("</{}>", self.tag);
+        html
+    }
+}
+
 impl GenHtml for HtmlElement {
     fn gen_html(self) -> String {
-        format!(
-            "<{tag} {classes} {attrs}>{children}</{tag}>",
-            tag = self.tag,
-            classes = self
-                .classes
-                .is_empty()
-                .then_some(String::new())
-                .unwrap_or(format!("class=\"{}\"", self.classes.join(" "))),
-            attrs = self
-                .attributes
-                .iter()
-                .map(|(k, v)| match k.as_str() {
-                    "onclick" => format!("{k}=\"{v}\""),
-                    _ => format!("{k}=\"{}\"", v.escape_default()),
-                })
-                .collect::<Vec<_>>()
-                .join(" "),
-            children = self
-                .children
-                .into_iter()
-                .map(|c| c.gen_html())
-                .collect::<Vec<_>>()
-                .join("")
-        )
+        self.gen_html_at(0)
     }
 }
 
@@ -205,7 +355,7 @@ impl HtmlText {
 
 impl GenHtml for HtmlText {
     fn gen_html(self) -> String {
-        sanitize_html(&self.content)
+        escape_text(&self.content)
     }
 }
 
@@ -225,13 +375,24 @@ impl HtmlList {
     }
 }
 
+impl HtmlList {
+    fn gen_html_at(self, depth: usize) -> String {
+        if is_pretty() && self.list.iter().all(Html::is_block) {
+            // The first item goes where the list is put
+            gen_lines(self.list, depth).trim_start().to_owned()
+        } else {
+            self.list
+                .into_iter()
+                .map(|i| i.gen_html_at(depth))
+                .collect::<Vec<_>>()
+                .join("")
+        }
+    }
+}
+
 impl GenHtml for HtmlList {
     fn gen_html(self) -> String {
-        self.list
-            .into_iter()
-            .map(|i| i.gen_html())
-            .collect::<Vec<_>>()
-            .join("")
+        self.gen_html_at(0)
     }
 }
 
@@ -241,6 +402,16 @@ impl From<HtmlList> for Html {
     }
 }
 
-fn sanitize_html(html: &str) -> String {
-    html.replace('<', "&lt;").replace('>', "&gt;")
+/// Escape text so it can be put inside a HTML element
+pub fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Escape text so it can be put inside a quoted HTML attribute
+pub fn escape_attr(text: &str) -> String {
+    escape_text(text)
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
 }
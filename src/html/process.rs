@@ -1,4 +1,30 @@
+use crate::{config::Config, html::escape_attr, url::UrlPath};
+use base64::{Engine, prelude::BASE64_STANDARD};
 use lightningcss::stylesheet::{ParserOptions, PrinterOptions};
+use regex_lite::{Captures, Regex};
+use sha2::{Digest, Sha256};
+use std::sync::LazyLock;
+
+/// Links to files in `href` and `src` attributes, with the attribute in the
+/// first group, the directory of the file in the second and the file name in
+/// the third
+static ASSET_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"((?:href|src)=")((?:[^"]*/)?)([^"/]+)""#).unwrap());
+
+/// Root-relative links in `href` and `src` attributes, with the attribute in
+/// the first group, the link in the second and the closing quote in the third
+static ROOT_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(href="|src=")(/[^/"][^"]*|/)(")"#).unwrap());
+
+/// Inline scripts and styles, with the tag name in the first group, its
+/// attributes in the second and its contents in the third
+static INLINE_CODE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<(script|style)\b([^>]*)>(.*?)</(?:script|style)>").unwrap());
+
+/// The `<meta>` tag with the content security policy, however it was minified
+static CSP_META: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)<meta\s+http-equiv=["']?content-security-policy["']?[^>]*>"#).unwrap()
+});
 
 pub fn minify_html(input: String) -> Result<String, String> {
     String::from_utf8(minify_html::minify(
@@ -28,3 +54,147 @@ pub fn minify_css(input: String) -> Result<String, String> {
         .map(|s| s.code)
         .map_err(|e| format!("{e}"))
 }
+
+/// Add a hash of the content of an asset to its file name, like
+/// `script.1a2b3c4d.js`, so browsers can cache it until it changes
+pub fn fingerprint(name: &str, content: &str) -> String {
+    let hash = &format!("{:x}", Sha256::digest(content))[..8];
+    match name.rsplit_once('.') {
+        Some((stem, ext)) => format!("{stem}.{hash}.{ext}"),
+        None => format!("{name}.{hash}"),
+    }
+}
+
+/// Resolve a link in the page in `page_dir` to the URL in the docs it points
+/// to, without the output URL. Links to other sites and outside the docs give
+/// `None`
+pub fn resolve_link(config: &Config, page_dir: &UrlPath, link: &str) -> Option<UrlPath> {
+    // Links with a scheme like https: or data: are left alone
+    if link.is_empty() || link.split(['/', '?', '#']).next()?.contains(':') {
+        return None;
+    }
+    let (path, suffix) = link
+        .find(['?', '#'])
+        .map_or((link, ""), |i| link.split_at(i));
+    // Relative links point to the index.html of pages so they work on file://
+    let path = path.strip_suffix("index.html").unwrap_or(path);
+    let url = if path.starts_with('/') {
+        UrlPath::parse(&format!("{path}{suffix}")).ok()?
+    } else {
+        UrlPath::parse(&format!("{page_dir}/{path}{suffix}")).ok()?
+    };
+    let root = config.output_url.clone().unwrap_or_else(UrlPath::new);
+    url.starts_with(&root).then(|| url.strip_prefix(&root))
+}
+
+/// Point links to assets in the page in `page_dir` to their fingerprinted
+/// file names. Only links to the assets themselves are changed, not to files
+/// with the same name elsewhere
+pub fn rewrite_asset_links(
+    input: String,
+    assets: &[(String, String)],
+    page_dir: &UrlPath,
+    config: &Config,
+) -> String {
+    if assets.is_empty() {
+        return input;
+    }
+    ASSET_LINK
+        .replace_all(&input, |caps: &Captures| {
+            let Some((name, file_name)) = assets.iter().find(|(name, _)| name == &caps[3]) else {
+                return caps[0].to_owned();
+            };
+            let link = format!("{}{}", &caps[2], &caps[3]);
+            if resolve_link(config, page_dir, &link) == Some(UrlPath::part(name)) {
+                format!("{}{}{file_name}\"", &caps[1], &caps[2])
+            } else {
+                caps[0].to_owned()
+            }
+        })
+        .into_owned()
+}
+
+/// Add the hashes of the inline scripts and styles in a page to its content
+/// security policy, so they are allowed without allowing every inline script
+pub fn add_csp_hashes(input: String, policy: &str) -> String {
+    let mut script_hashes = Vec::new();
+    let mut style_hashes = Vec::new();
+    for caps in INLINE_CODE.captures_iter(&input) {
+        // Scripts loaded from files are allowed by their source instead
+        if &caps[1] == "script" && caps[2].contains("src=") {
+            continue;
+        }
+        let hash = format!(
+            "'sha256-{}'",
+            BASE64_STANDARD.encode(Sha256::digest(&caps[3]))
+        );
+        if &caps[1] == "script" {
+            script_hashes.push(hash);
+        } else {
+            style_hashes.push(hash);
+        }
+    }
+    if script_hashes.is_empty() && style_hashes.is_empty() {
+        return input;
+    }
+
+    let mut directives = policy
+        .split(';')
+        .map(|d| d.trim().to_owned())
+        .filter(|d| !d.is_empty())
+        .collect::<Vec<_>>();
+    for (name, hashes) in [("script-src", script_hashes), ("style-src", style_hashes)] {
+        if hashes.is_empty() {
+            continue;
+        }
+        let find = |name: &str| {
+            directives
+                .iter()
+                .position(|d| d.split_whitespace().next() == Some(name))
+        };
+        // Without its own directive the default one applies, so its sources
+        // are copied to not block anything it allowed. Without either,
+        // nothing is blocked to begin with
+        let directive = match find(name) {
+            Some(i) => directives.remove(i),
+            None => match find("default-src") {
+                Some(i) => directives[i].replacen("default-src", name, 1),
+                None => continue,
+            },
+        };
+        directives.push(format!(
+            "{} {}",
+            directive.replace(" 'none'", ""),
+            hashes.join(" ")
+        ));
+    }
+
+    CSP_META
+        .replace(&input, |_: &Captures| {
+            format!(
+                "<meta http-equiv=\"Content-Security-Policy\" content=\"{}\">",
+                escape_attr(&directives.join("; "))
+            )
+        })
+        .into_owned()
+}
+
+/// Rewrite all root-relative links (`href="/docs/..."` and `src="/..."`) in a
+/// page to be relative to the page's own directory, so the output can be
+/// browsed from disk or hosted under any prefix
+pub fn relativize_links(input: String, page: &UrlPath, config: &Config) -> String {
+    ROOT_LINK
+        .replace_all(&input, |caps: &Captures| {
+            // Links with invalid percent-escapes are left as they are
+            let Ok(url) = UrlPath::parse(&caps[2]) else {
+                return caps[0].to_owned();
+            };
+            let mut rel = url.relative_to(page);
+            // Browsers don't resolve directories to index.html on file://
+            if url.is_page(config) {
+                rel.push_str("/index.html");
+            }
+            format!("{}{rel}{}{}", &caps[1], url.suffix(), &caps[3])
+        })
+        .into_owned()
+}
@@ -0,0 +1,109 @@
+use log::warn;
+use std::{collections::HashMap, fs, path::Path};
+
+/// Built-in UI strings in English. Translation files use the same keys, and
+/// templates get every string as a `tr_<key>` variable
+pub const DEFAULT_STRINGS: &[(&str, &str)] = &[
+    // Navigation
+    ("tutorials", "Tutorials"),
+    ("entities", "Classes"),
+    ("api", "API"),
+    ("groups", "Groups"),
+    ("files", "Files"),
+    ("search", "Search items..."),
+    ("clear_search", "Clear search"),
+    ("skip_to_content", "Skip to content"),
+    ("menu", "Menu"),
+    ("breadcrumbs", "Breadcrumbs"),
+    ("languages", "Languages"),
+    // Doc comments
+    ("no_description", "No description provided"),
+    ("parameters", "Parameters"),
+    ("template_parameters", "Template parameters"),
+    ("return_value", "Return value"),
+    ("exceptions", "Exceptions"),
+    ("see_also", "See also"),
+    ("examples", "Examples"),
+    ("used_in_examples", "Used in examples:"),
+    ("view_documentation", "View documentation"),
+    // Members of classes
+    ("public_static_methods", "Public static methods"),
+    ("constructors", "Constructors"),
+    ("public_member_functions", "Public member functions"),
+    ("protected_member_functions", "Protected member functions"),
+    ("private_member_functions", "Private member functions"),
+    ("fields", "Fields"),
+    ("protected_fields", "Protected fields"),
+    ("private_fields", "Private fields"),
+    ("friends", "Friends"),
+    ("specializations", "Specializations"),
+    ("member_types", "Member types"),
+    ("platform_only", "{} only"),
+    ("since_version", "Since {}"),
+    ("api_versions", "API added in each version"),
+    ("enums", "Enums"),
+    ("aliases", "Aliases"),
+    ("reexports", "Brought in with using"),
+    ("originally_defined_in", "Originally defined in {namespace}"),
+    ("layout", "Layout"),
+    ("offset", "Offset"),
+    ("size", "Size"),
+    ("field", "Field"),
+    ("name", "Name"),
+    ("value", "Value"),
+    ("hex", "Hex"),
+    ("bit", "Bit"),
+    ("flags", "Flags"),
+    ("flags_example", "Flags can be combined with bitwise OR: "),
+    ("special_members", "Special member functions"),
+    ("default_constructor", "Default constructor"),
+    ("copy_constructor", "Copy constructor"),
+    ("move_constructor", "Move constructor"),
+    ("copy_assignment", "Copy assignment"),
+    ("move_assignment", "Move assignment"),
+    ("destructor", "Destructor"),
+    ("user_provided", "User-provided"),
+    ("defaulted", "Defaulted"),
+    ("deleted", "Deleted"),
+    ("implicit", "Implicit"),
+    ("not_declared", "Not declared"),
+    // Tutorials
+    ("pages", "Pages"),
+    ("tags", "Tags"),
+    ("edit_this_page", "Edit this page"),
+    ("dismiss", "Dismiss"),
+    ("series_part", "Part {part} of {count} in {series}"),
+    ("previous_part", "Previous"),
+    ("next_part", "Next"),
+    ("play_video", "Play video"),
+    ("video_notice", "Playing the video loads it from YouTube"),
+];
+
+/// Get the built-in English version of a UI string
+pub fn default_string(key: &str) -> &'static str {
+    DEFAULT_STRINGS
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, s)| *s)
+        .unwrap_or_else(|| panic!("Unknown UI string '{key}'"))
+}
+
+/// Load the translations of UI strings for a locale from `<locale>.toml` in
+/// a directory, if it has one
+pub fn load_strings(dir: &Path, locale: &str) -> Result<HashMap<String, String>, String> {
+    let path = dir.join(format!("{locale}.toml"));
+    if !path.is_file() {
+        return Ok(HashMap::new());
+    }
+    let strings: HashMap<String, String> = toml::from_str(
+        &fs::read_to_string(&path)
+            .map_err(|e| format!("Unable to read translations for {locale}: {e}"))?,
+    )
+    .map_err(|e| format!("Unable to parse translations for {locale}: {e}"))?;
+    for key in strings.keys() {
+        if !DEFAULT_STRINGS.iter().any(|(k, _)| k == key) {
+            warn!("Unknown UI string '{key}' in translations for {locale}");
+        }
+    }
+    Ok(strings)
+}
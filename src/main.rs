@@ -1,22 +1,72 @@
 #![feature(iter_advance_by)]
 #![feature(iter_intersperse)]
 
-use crate::{analyze::create_docs, normalize::Normalize, url::UrlPath};
+use crate::{
+    analyze::{check_examples, create_docs},
+    builder::manifest::Manifest,
+    normalize::Normalize,
+    url::UrlPath,
+};
 use clap::Parser;
 use config::Config;
-use log::{error, info};
-use std::{error::Error, fs, path::PathBuf, process::exit, time::Instant};
+use log::{error, info, warn};
+use std::{
+    error::Error,
+    fs,
+    path::PathBuf,
+    process::exit,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
 
 mod analyze;
 mod annotation;
 mod builder;
 mod cmake;
 mod config;
+mod cppreference;
+mod diagnostics;
+mod diff;
+mod examples;
 mod html;
+mod i18n;
 mod lookahead;
 mod normalize;
 mod url;
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum LogFormat {
+    /// Colored messages for humans
+    Text,
+    /// One JSON object per line, for CI systems
+    Json,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum FlashCommand {
+    /// Compile the C++ code examples in tutorials and doc comments instead of
+    /// building docs, failing if any of them don't compile
+    CheckExamples,
+    /// Parse the headers and check doc comments and tutorials for problems
+    /// without writing any docs, which is quicker than a full build
+    Check,
+    /// Remove the docs built into the output directory by earlier builds,
+    /// leaving any other files in it alone
+    Clean,
+    /// Compare the public API of two versions, given as git refs or as the
+    /// api.json files of earlier builds, writing the differences to diff.json
+    /// and whats-new.html in the output directory
+    Diff {
+        /// The older version
+        old: String,
+        /// The newer version
+        new: String,
+    },
+}
+
 #[derive(Parser, Debug)]
 #[command(name("Flash"), version, about)]
 struct Args {
@@ -35,20 +85,70 @@ struct Args {
     /// Whether to skip invoking CMake entirely, relies on existing build dir.
     #[arg(long, default_value_t = false, hide = true)]
     skip_build: bool,
+
+    /// Also export all pages into a single self-contained HTML file for
+    /// offline reading
+    #[arg(long, default_value_t = false)]
+    single_page: bool,
+
+    /// Emit links relative to the current page instead of absolute ones, so
+    /// the docs can be browsed from disk or hosted under any prefix
+    #[arg(long, default_value_t = false)]
+    relative_links: bool,
+
+    /// Indent the generated HTML instead of minifying it, which makes it
+    /// easier to debug templates
+    #[arg(long, default_value_t = false)]
+    pretty_html: bool,
+
+    /// Also build tutorials marked with `draft: true`
+    #[arg(long, default_value_t = false)]
+    drafts: bool,
+
+    /// How many pages to build and write at once. Defaults to the number of
+    /// CPUs
+    #[arg(short, long)]
+    jobs: Option<usize>,
+
+    /// Only print warnings and errors, without a progress bar
+    #[arg(short, long, default_value_t = false, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Print debug information and build statistics for every page
+    #[arg(short, long, default_value_t = false)]
+    verbose: bool,
+
+    /// How to format log messages
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    #[command(subcommand)]
+    command: Option<FlashCommand>,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    FlashLogger::init();
-
     let args = Args::parse();
 
+    FlashLogger::init(
+        args.log_format,
+        if args.quiet {
+            log::LevelFilter::Warn
+        } else if args.verbose || cfg!(debug_assertions) {
+            log::LevelFilter::Trace
+        } else {
+            log::LevelFilter::Info
+        },
+    );
+
     // Check if output dir exists
     if args.output.exists()
         // Check if it's empty
         && args.output.read_dir().map(|mut i| i.next().is_some()).unwrap_or(false)
         // Then overwrite must be specified
         && !args.overwrite
+        // Checking only uses the output directory for scratch files
+        && args.command.is_none()
     {
         error!(
             "Output directory {} already exists and no --overwrite option was specified, aborting",
@@ -86,19 +186,82 @@ async fn main() -> Result<(), Box<dyn Error>> {
     );
 
     // Parse config
-    let conf = Config::parse(full_input, full_output, relative_output)?;
+    let mut conf = Config::parse(full_input, full_output, relative_output)?;
+    conf.single_page = args.single_page;
+    conf.relative_links = args.relative_links;
+    conf.drafts = args.drafts;
+    if args.pretty_html {
+        // Minifying would undo all the indentation
+        conf.minify = false;
+        html::set_pretty(true);
+    }
+    // The progress bar would get in the way of parsing the JSON logs
+    conf.quiet = args.quiet || args.log_format == LogFormat::Json;
+    conf.verbose = args.verbose;
+    conf.check_only = matches!(args.command, Some(FlashCommand::Check));
+    conf.jobs = args
+        .jobs
+        .or(std::thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1)
+        .max(1);
+    diagnostics::set_levels(conf.warnings.clone());
+    let conf = Arc::new(conf);
+
+    if let Some(FlashCommand::CheckExamples) = args.command {
+        check_examples(conf, args.skip_build).await?;
+        return Ok(());
+    }
+
+    if let Some(FlashCommand::Clean) = args.command {
+        match Manifest::read(&conf) {
+            Some(manifest) => {
+                let removed = manifest.clean(&conf);
+                info!(
+                    "Removed {removed} files from {}",
+                    conf.output_dir.to_string_lossy()
+                );
+            }
+            None => warn!(
+                "No docs were built into {}, nothing to clean",
+                conf.output_dir.to_string_lossy()
+            ),
+        }
+        return Ok(());
+    }
+
+    if let Some(FlashCommand::Diff { old, new }) = &args.command {
+        diff::diff_apis(conf, old, new, args.skip_build)?;
+        return Ok(());
+    }
+
+    if conf.check_only {
+        info!(phase = "start"; "Checking docs for {}", conf.project.name);
+        let now = Instant::now();
+        create_docs(conf.clone(), args.skip_build).await?;
+        diagnostics::check_errors()?;
+        info!(
+            phase = "end";
+            "Checked docs for {} in {:.2}s",
+            conf.project.name,
+            now.elapsed().as_secs_f32()
+        );
+        return Ok(());
+    }
 
     // Build the docs
     info!(
+        phase = "start";
         "Building docs for {} ({})",
         conf.project.name, conf.project.version
     );
     let now = Instant::now();
     create_docs(conf.clone(), args.skip_build).await?;
+    diagnostics::check_errors()?;
     info!(
-        "Docs built for {} in {}s",
+        phase = "end";
+        "Docs built for {} in {:.2}s",
         conf.project.name,
-        now.elapsed().as_secs()
+        now.elapsed().as_secs_f32()
     );
 
     Ok(())
@@ -112,28 +275,51 @@ impl log::Log for FlashLogger {
     }
 
     fn log(&self, record: &log::Record) {
-        use log::Level;
+        use log::{Level, kv::Key};
         use owo_colors::OwoColorize;
+
+        // Warnings about entities carry where the entity is declared
+        let kv = record.key_values();
+        let file = kv.get(Key::from_str("file")).map(|v| v.to_string());
+        let line = kv.get(Key::from_str("line")).and_then(|v| v.to_u64());
+
+        if LOG_JSON.load(Ordering::Relaxed) {
+            println!(
+                "{}",
+                serde_json::json!({
+                    "level": record.level().as_str().to_lowercase(),
+                    "message": record.args().to_string(),
+                    "phase": kv.get(Key::from_str("phase")).map(|v| v.to_string()),
+                    "file": file,
+                    "line": line,
+                })
+            );
+            return;
+        }
+
         let header = match record.level() {
             Level::Warn => "[warn]".yellow().bold().to_string(),
             Level::Error => "[error]".red().bold().to_string(),
             Level::Info => "[info]".bright_blue().bold().to_string(),
             Level::Debug | Level::Trace => "[debug]".bright_purple().bold().to_string(),
         };
-        println!("{} {}", header, record.args());
+        match (file, line) {
+            (Some(file), Some(line)) if !file.is_empty() => {
+                println!("{} {file}:{line}: {}", header, record.args())
+            }
+            _ => println!("{} {}", header, record.args()),
+        }
     }
 
     fn flush(&self) {}
 }
 
 static LOGGER: FlashLogger = FlashLogger;
+static LOG_JSON: AtomicBool = AtomicBool::new(false);
 impl FlashLogger {
-    pub fn init() {
+    pub fn init(format: LogFormat, level: log::LevelFilter) {
+        LOG_JSON.store(format == LogFormat::Json, Ordering::Relaxed);
         log::set_logger(&LOGGER).expect("Failed to initialize logger");
-        log::set_max_level(if cfg!(debug_assertions) {
-            log::LevelFilter::Trace
-        } else {
-            log::LevelFilter::Info
-        });
+        log::set_max_level(level);
     }
 }
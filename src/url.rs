@@ -1,7 +1,12 @@
 use crate::config::Config;
-use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
-use serde::{de::Visitor, Deserialize};
-use std::{fmt::Display, path::PathBuf, sync::Arc};
+use percent_encoding::{AsciiSet, CONTROLS, percent_decode_str, utf8_percent_encode};
+use serde::{Deserialize, de::Visitor};
+use std::{
+    fmt::Display,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 // The URL crate doesn't support paths like /some/file, it needs the protocol and hostname
 // (which is undesirable for Flash as docs links are /docs/namespace/entity)
@@ -11,6 +16,8 @@ pub const URL_RESERVED: &AsciiSet = &CONTROLS
     .add(b'!')
     .add(b'#')
     .add(b'$')
+    // so parsing a formatted URL doesn't decode anything that wasn't encoded
+    .add(b'%')
     .add(b'&')
     .add(b'\'')
     .add(b'(')
@@ -36,31 +43,182 @@ pub const URL_RESERVED: &AsciiSet = &CONTROLS
     .add(b'|')
     .add(b'"');
 
-#[derive(Hash, Debug, Clone, PartialEq)]
+/// Directories of generated pages named after a file or a tag, whose last part
+/// may have a `.` in it without being a file
+const PAGE_DIRS: &[&str] = &["files", "source", "examples", "groups", "tags"];
+
+/// Get the parts of a path, with backslashes treated as separators and with
+/// drive letters and UNC shares written the same way whether the path is
+/// verbatim (`\\?\C:\...`) or not, so paths from LibClang, the command line
+/// and the config can be compared. Windows paths are parsed the same way on
+/// every host, since they can end up in configs and compile commands used on
+/// Unix. The drive letter or share and the root are returned separately from
+/// the rest of the parts
+fn path_parts(path: &Path) -> Result<(Vec<String>, Vec<String>), String> {
+    let path = path
+        .to_str()
+        .ok_or("Expected UTF-8".to_owned())?
+        .replace('\\', "/");
+    let path = match path.strip_prefix("//?/") {
+        Some(verbatim) => match verbatim.strip_prefix("UNC/") {
+            Some(unc) => format!("//{unc}"),
+            None => verbatim.to_owned(),
+        },
+        None => path,
+    };
+
+    let mut root = Vec::new();
+    let mut rest = path.as_str();
+    if let Some(unc) = rest.strip_prefix("//") {
+        let mut parts = unc.splitn(3, '/');
+        let server = parts.next().unwrap_or_default();
+        let share = parts.next().unwrap_or_default();
+        root.push(format!(
+            "//{}/{}",
+            server.to_lowercase(),
+            share.to_lowercase()
+        ));
+        root.push("/".into());
+        rest = parts.next().unwrap_or_default();
+    } else {
+        if let [drive, b':', ..] = rest.as_bytes()
+            && drive.is_ascii_alphabetic()
+        {
+            root.push(format!("{}:", drive.to_ascii_uppercase() as char));
+            rest = &rest[2..];
+        }
+        if let Some(stripped) = rest.strip_prefix('/') {
+            root.push("/".into());
+            rest = stripped;
+        }
+    }
+
+    let parts = rest
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .map(|part| part.to_owned())
+        .collect();
+    Ok((root, parts))
+}
+
+/// Strip a directory from the start of a path like [`Path::strip_prefix`],
+/// but without caring about the differences in how Windows paths can be
+/// written, like `c:/foo` and `\\?\C:\foo`
+pub fn strip_path_prefix(path: &Path, base: &Path) -> Option<PathBuf> {
+    let (path_root, path) = path_parts(path).ok()?;
+    let (base_root, base) = path_parts(base).ok()?;
+    (path_root == base_root && path.starts_with(&base))
+        .then(|| PathBuf::from_iter(&path[base.len()..]))
+}
+
+#[derive(Debug, Clone)]
 pub struct UrlPath {
     parts: Vec<String>,
+    /// Whether to end this URL with a slash when formatting it, set by
+    /// `to_absolute` based on the trailing slash policy in the config
+    trailing_slash: bool,
+    /// Query string without the leading `?`, like `q=foo`
+    query: Option<String>,
+    /// Fragment without the leading `#`, like `L42`
+    fragment: Option<String>,
 }
 
 #[allow(unused)]
 impl UrlPath {
     pub fn new() -> Self {
-        Self { parts: Vec::new() }
+        Self {
+            parts: Vec::new(),
+            trailing_slash: false,
+            query: None,
+            fragment: None,
+        }
     }
 
     pub fn new_with_path(parts: Vec<String>) -> Self {
-        Self { parts }.clean()
+        Self {
+            parts,
+            ..Self::new()
+        }
+        .clean()
     }
 
     pub fn part(part: &str) -> Self {
         Self {
             parts: vec![part.to_string()],
+            ..Self::new()
         }
     }
 
+    /// Parse a URL like `classes/Foo?q=bar#baz`. The parts of the path are
+    /// percent-decoded, so parsing a formatted URL gives back the same URL,
+    /// while the query and fragment are kept as-is
     pub fn parse(url: &str) -> Result<Self, String> {
-        Ok(UrlPath::new_with_path(
-            url.split('/').map(|s| s.to_owned()).collect(),
-        ))
+        let (url, fragment) = match url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment.to_owned())),
+            None => (url, None),
+        };
+        let (path, query) = match url.split_once('?') {
+            Some((path, query)) => (path, Some(query.to_owned())),
+            None => (url, None),
+        };
+        let parts = path
+            .split('/')
+            .map(|s| {
+                percent_decode_str(s)
+                    .decode_utf8()
+                    .map(|s| s.into_owned())
+                    .map_err(|e| format!("Invalid URL {url}: {e}"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            query,
+            fragment,
+            ..UrlPath::new_with_path(parts)
+        })
+    }
+
+    /// Set the query string of this URL, without the leading `?`
+    pub fn with_query(mut self, query: &str) -> Self {
+        self.query = Some(query.to_owned());
+        self
+    }
+
+    /// Set the fragment of this URL, without the leading `#`
+    pub fn with_fragment(mut self, fragment: &str) -> Self {
+        self.fragment = Some(fragment.to_owned());
+        self
+    }
+
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    pub fn fragment(&self) -> Option<&str> {
+        self.fragment.as_deref()
+    }
+
+    /// Get this URL without its query string and fragment
+    pub fn without_suffix(&self) -> Self {
+        Self {
+            query: None,
+            fragment: None,
+            ..self.clone()
+        }
+    }
+
+    /// Get the query string and fragment of this URL as they go after the
+    /// path, like `?q=foo#bar`
+    pub fn suffix(&self) -> String {
+        let mut res = String::new();
+        if let Some(ref query) = self.query {
+            res.push('?');
+            res.push_str(query);
+        }
+        if let Some(ref fragment) = self.fragment {
+            res.push('#');
+            res.push_str(fragment);
+        }
+        res
     }
 
     fn clean(mut self) -> Self {
@@ -73,19 +231,26 @@ impl UrlPath {
                     .then_some(p.to_owned())
             })
             .for_each(|p| {
+                // Slashes in parts are kept, since they are encoded as %2F
+                // like any other reserved character
                 if p == ".." {
                     filtered.pop();
                 } else {
-                    filtered.push(p.replace("/", ""));
+                    filtered.push(p);
                 }
             });
         self.parts = filtered;
         self
     }
 
+    /// Join another URL onto this one, which takes the trailing slash, query
+    /// string and fragment of the other URL
     pub fn join<T: AsRef<UrlPath>>(&self, other: T) -> Self {
         let mut buf = self.clone();
         buf.parts.extend(other.as_ref().parts.clone());
+        buf.trailing_slash = other.as_ref().trailing_slash;
+        buf.query = other.as_ref().query.clone();
+        buf.fragment = other.as_ref().fragment.clone();
         buf.clean()
     }
 
@@ -95,7 +260,11 @@ impl UrlPath {
         if self.parts.len() >= prefix.as_ref().parts.len()
             && self.parts[0..prefix.as_ref().parts.len()] == prefix.as_ref().parts
         {
-            return UrlPath::new_with_path(self.parts[prefix.as_ref().parts.len()..].into());
+            return Self {
+                query: self.query.clone(),
+                fragment: self.fragment.clone(),
+                ..UrlPath::new_with_path(self.parts[prefix.as_ref().parts.len()..].into())
+            };
         }
         self.clone()
     }
@@ -115,6 +284,8 @@ impl UrlPath {
             && self.parts[0..prefix.as_ref().parts.len()] == prefix.as_ref().parts
     }
 
+    /// Get the parts of the path percent-encoded, like `Ref%3CT%3E` for
+    /// `Ref<T>`
     pub fn url_safe_parts(&self) -> Vec<String> {
         self.parts
             .iter()
@@ -130,6 +301,7 @@ impl UrlPath {
         self.parts.last().map(|s| s.to_owned())
     }
 
+    /// Get the path without percent-encoding, for comparing and lookups
     pub fn to_raw_string(&self) -> String {
         self.parts.join("/")
     }
@@ -138,15 +310,55 @@ impl UrlPath {
         PathBuf::from_iter(&self.url_safe_parts())
     }
 
+    /// Check if this is a link to another site, like `https://example.com`
+    pub fn is_external(&self) -> bool {
+        self.parts
+            .first()
+            .is_some_and(|x| x == "http:" || x == "https:")
+    }
+
     pub fn to_absolute(&self, config: Arc<Config>) -> Self {
-        if self.is_absolute(config.clone()) {
+        self.to_absolute_under(config.output_url.as_ref(), config.trailing_slash)
+    }
+
+    fn to_absolute_under(&self, output_url: Option<&UrlPath>, trailing_slash: bool) -> Self {
+        if self.is_external() {
+            return self.clone();
+        }
+        let root = output_url.cloned().unwrap_or_else(UrlPath::new);
+        let mut res = if self.starts_with(&root) {
             self.clone()
         } else {
-            config
-                .output_url
-                .as_ref()
-                .unwrap_or(&UrlPath::new())
-                .join(self)
+            root.join(self)
+        };
+        // Only pages get the slash, not files like images or scripts
+        res.trailing_slash = trailing_slash && res.is_page_under(output_url);
+        res
+    }
+
+    /// Check if this URL is of a page, which is output as a directory with an
+    /// `index.html` in it, rather than of a file like an image or a script.
+    /// Pages in [`PAGE_DIRS`] may be named after a file or a tag, so they're
+    /// the only pages that may have a `.` in the last part
+    pub fn is_page(&self, config: &Config) -> bool {
+        self.is_page_under(config.output_url.as_ref())
+    }
+
+    fn is_page_under(&self, output_url: Option<&UrlPath>) -> bool {
+        let parts = self
+            .parts
+            .iter()
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>();
+        let base = output_url
+            .iter()
+            .flat_map(|u| u.parts.iter().filter(|p| !p.is_empty()))
+            .collect::<Vec<_>>();
+        let parts = parts.strip_prefix(base.as_slice()).unwrap_or(&parts);
+        match parts {
+            [] => true,
+            [first, _, ..] if PAGE_DIRS.contains(&first.as_str()) => true,
+            [.., last] => !last.contains('.'),
         }
     }
 
@@ -159,16 +371,33 @@ impl UrlPath {
     }
 
     pub fn to_unencoded_string(&self) -> String {
-        let leading = if self
+        if self.is_external() {
+            return format!(
+                "{}//{}{}",
+                self.parts[0],
+                &self.parts[1..].join("/"),
+                self.suffix()
+            );
+        }
+        format!("/{}{}", &self.parts.join("/"), self.suffix())
+    }
+
+    /// Get this URL as a path relative to the directory `base`, like
+    /// `../../classes/Foo`, without the query string and fragment. Both URLs
+    /// should be absolute
+    pub fn relative_to<T: AsRef<UrlPath>>(&self, base: T) -> String {
+        let base = base.as_ref();
+        let common = self
             .parts
-            .first()
-            .is_some_and(|x| x == "http:" || x == "https:")
-        {
-            ""
-        } else {
-            "/"
-        };
-        format!("{leading}{}", &self.parts.join("/"))
+            .iter()
+            .zip(&base.parts)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let res = std::iter::repeat_n("..".to_owned(), base.parts.len() - common)
+            .chain(self.url_safe_parts().split_off(common))
+            .collect::<Vec<_>>()
+            .join("/");
+        if res.is_empty() { ".".into() } else { res }
     }
 
     pub fn append_to_last(mut self, suffix: &str) -> Self {
@@ -212,6 +441,23 @@ impl<'de> Deserialize<'de> for UrlPath {
     }
 }
 
+// The trailing slash only matters when formatting, so two URLs to the same
+// page are equal either way
+
+impl PartialEq for UrlPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.parts == other.parts && self.query == other.query && self.fragment == other.fragment
+    }
+}
+
+impl Hash for UrlPath {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.parts.hash(state);
+        self.query.hash(state);
+        self.fragment.hash(state);
+    }
+}
+
 // Idk how to do this better :(
 
 impl AsRef<UrlPath> for UrlPath {
@@ -224,17 +470,8 @@ impl TryFrom<&PathBuf> for UrlPath {
     type Error = String;
 
     fn try_from(value: &PathBuf) -> Result<Self, Self::Error> {
-        Ok(UrlPath::new_with_path(
-            value
-                .components()
-                .map(|p| {
-                    p.as_os_str()
-                        .to_str()
-                        .map(|s| s.to_string())
-                        .ok_or("Expected UTF-8".to_owned())
-                })
-                .collect::<Result<_, _>>()?,
-        ))
+        // Drive letters and roots aren't part of URLs
+        Ok(UrlPath::new_with_path(path_parts(value)?.1))
     }
 }
 
@@ -263,11 +500,221 @@ impl TryFrom<&String> for UrlPath {
 
 impl Display for UrlPath {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_external() {
+            // The scheme and host can't be percent-encoded
+            f.write_fmt(format_args!(
+                "{}//{}/{}",
+                self.parts[0],
+                self.parts.get(1).map_or("", |s| s.as_str()),
+                &self
+                    .url_safe_parts()
+                    .split_off(2.min(self.parts.len()))
+                    .join("/")
+            ))?;
+        }
         // empty urls are just ""
-        if !self.is_empty() {
-            f.write_fmt(format_args!("/{}", &self.url_safe_parts().join("/")))
-        } else {
-            Ok(())
+        else if !self.is_empty() {
+            f.write_fmt(format_args!("/{}", &self.url_safe_parts().join("/")))?;
+            if self.trailing_slash {
+                f.write_str("/")?;
+            }
         }
+        // Links to anchors on the same page are just the fragment
+        f.write_str(&self.suffix())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip(path: &str, base: &str) -> Option<String> {
+        strip_path_prefix(Path::new(path), Path::new(base))
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+    }
+
+    #[test]
+    fn backslashes() {
+        let parts = |p: &str| path_parts(Path::new(p)).unwrap();
+        assert_eq!(parts("foo\\bar/baz.hpp").1, ["foo", "bar", "baz.hpp"]);
+        assert_eq!(parts("foo\\bar"), parts("foo/bar"));
+        assert_eq!(parts("./foo\\\\bar\\"), parts("foo/bar"));
+    }
+
+    #[test]
+    fn drive_letters() {
+        let (root, parts) = path_parts(Path::new("c:\\src\\lib.hpp")).unwrap();
+        assert_eq!(root, ["C:", "/"]);
+        assert_eq!(parts, ["src", "lib.hpp"]);
+        assert_eq!(
+            path_parts(Path::new("\\\\?\\C:\\src")).unwrap(),
+            path_parts(Path::new("C:/src")).unwrap()
+        );
+    }
+
+    #[test]
+    fn unc_shares() {
+        let (root, parts) = path_parts(Path::new("\\\\Server\\Share\\include")).unwrap();
+        assert_eq!(root, ["//server/share", "/"]);
+        assert_eq!(parts, ["include"]);
+        assert_eq!(
+            path_parts(Path::new("\\\\?\\UNC\\server\\share\\include")).unwrap(),
+            (root, parts)
+        );
+    }
+
+    fn parts(parts: &[&str]) -> UrlPath {
+        UrlPath::new_with_path(parts.iter().map(|p| p.to_string()).collect())
+    }
+
+    #[test]
+    fn round_trips_reserved_characters() {
+        let url = parts(&["classes", "Ref<T>", "a&b", "with space", "a/b", "a%b"]);
+        let formatted = url.to_string();
+        assert_eq!(
+            formatted,
+            "/classes/Ref%3CT%3E/a%26b/with%20space/a%2Fb/a%25b"
+        );
+        assert_eq!(UrlPath::parse(&formatted).unwrap(), url);
+        assert_eq!(UrlPath::parse(&formatted).unwrap().to_string(), formatted);
+    }
+
+    #[test]
+    fn decodes_parsed_parts() {
+        let url = UrlPath::parse("/files/a%2Fb/Ref%3CT%3E").unwrap();
+        assert_eq!(url.raw_file_name().as_deref(), Some("Ref<T>"));
+        assert_eq!(url.to_raw_string(), "files/a/b/Ref<T>");
+        assert_eq!(url, parts(&["files", "a/b", "Ref<T>"]));
+        assert_ne!(url, UrlPath::parse("/files/a/b/Ref%3CT%3E").unwrap());
+        assert!(UrlPath::parse("/files/%FF").is_err());
+    }
+
+    #[test]
+    fn parses_query_and_fragment() {
+        let url = UrlPath::parse("/classes/Foo?q=a%20b&x=1#L42").unwrap();
+        assert_eq!(url.to_raw_string(), "classes/Foo");
+        assert_eq!(url.query(), Some("q=a%20b&x=1"));
+        assert_eq!(url.fragment(), Some("L42"));
+
+        let url = UrlPath::parse("search#q=a?b").unwrap();
+        assert_eq!(url.query(), None);
+        assert_eq!(url.fragment(), Some("q=a?b"));
+
+        let url = UrlPath::parse("#section").unwrap();
+        assert!(url.is_empty());
+        assert_eq!(url.fragment(), Some("section"));
+    }
+
+    #[test]
+    fn formats_query_and_fragment() {
+        let url = UrlPath::parse("/classes/Foo?q=a%20b#L42").unwrap();
+        assert_eq!(url.to_string(), "/classes/Foo?q=a%20b#L42");
+        assert_eq!(url.suffix(), "?q=a%20b#L42");
+        assert_eq!(url.without_suffix().to_string(), "/classes/Foo");
+        assert_eq!(UrlPath::parse("#top").unwrap().to_string(), "#top");
+        assert_eq!(
+            parts(&["classes", "Foo"])
+                .with_query("a=1")
+                .with_fragment("b")
+                .to_string(),
+            "/classes/Foo?a=1#b"
+        );
+        assert_eq!(
+            UrlPath::parse("https://example.com/a?b#c")
+                .unwrap()
+                .to_string(),
+            "https://example.com/a?b#c"
+        );
+    }
+
+    #[test]
+    fn keeps_query_and_fragment_when_absolute() {
+        let base = UrlPath::parse("docs").unwrap();
+        let url = UrlPath::parse("classes/Foo?q=1#L42").unwrap();
+        let absolute = url.to_absolute_under(Some(&base), false);
+        assert_eq!(absolute.to_string(), "/docs/classes/Foo?q=1#L42");
+        let absolute = url.to_absolute_under(Some(&base), true);
+        assert_eq!(absolute.to_string(), "/docs/classes/Foo/?q=1#L42");
+        assert_eq!(absolute.to_absolute_under(Some(&base), true), absolute);
+
+        let file = UrlPath::parse("script.js?v=2").unwrap();
+        assert_eq!(
+            file.to_absolute_under(None, true).to_string(),
+            "/script.js?v=2"
+        );
+    }
+
+    #[test]
+    fn pages() {
+        let page = |url: &str| UrlPath::parse(url).unwrap().is_page_under(None);
+        assert!(page(""));
+        assert!(page("geode/Mod"));
+        assert!(page("files/include/Geode.hpp"));
+        assert!(page("source/include/Geode.hpp"));
+        assert!(page("examples/main.cpp"));
+        assert!(page("examples/src/hooks.cpp"));
+        assert!(page("tags/v1.0"));
+        assert!(page("examples"));
+        assert!(!page("script.js"));
+        assert!(!page("tutorials/images/logo.png"));
+
+        let base = UrlPath::parse("docs").unwrap();
+        let page = |url: &str| UrlPath::parse(url).unwrap().is_page_under(Some(&base));
+        assert!(page("docs/examples/main.cpp"));
+        assert!(!page("docs/functions.js"));
+    }
+
+    #[test]
+    fn urls_from_windows_paths() {
+        let url = |p: &str| UrlPath::try_from(&PathBuf::from(p)).unwrap();
+        // Backslashes separate parts like slashes do
+        assert_eq!(
+            url("include\\geode\\Mod.hpp").to_string(),
+            "/include/geode/Mod.hpp"
+        );
+        assert_eq!(url("include\\geode/Mod.hpp"), url("include/geode/Mod.hpp"));
+        // Drive letters and roots aren't part of the URL
+        assert_eq!(
+            url("C:\\proj\\include\\Mod.hpp").to_string(),
+            "/proj/include/Mod.hpp"
+        );
+        assert_eq!(url("c:/proj/Mod.hpp"), url("\\\\?\\C:\\proj\\Mod.hpp"));
+        // Neither are UNC servers and shares
+        assert_eq!(
+            url("\\\\server\\share\\proj\\Mod.hpp").to_string(),
+            "/proj/Mod.hpp"
+        );
+        assert_eq!(
+            url("\\\\?\\UNC\\server\\share\\proj\\Mod.hpp"),
+            url("//server/share/proj/Mod.hpp")
+        );
+        // Names with reserved characters are still encoded
+        assert_eq!(
+            url("include\\My Header#1.hpp").to_string(),
+            "/include/My%20Header%231.hpp"
+        );
+    }
+
+    #[test]
+    fn strip_prefix() {
+        assert_eq!(
+            strip("/home/me/proj/include/a.hpp", "/home/me/proj").as_deref(),
+            Some("include/a.hpp")
+        );
+        assert_eq!(
+            strip("C:\\proj\\include\\a.hpp", "c:/proj").as_deref(),
+            Some("include/a.hpp")
+        );
+        assert_eq!(
+            strip("\\\\?\\C:\\proj\\a.hpp", "C:\\proj\\").as_deref(),
+            Some("a.hpp")
+        );
+        assert_eq!(
+            strip("\\\\srv\\share\\proj\\a.hpp", "//SRV/share/proj").as_deref(),
+            Some("a.hpp")
+        );
+        assert_eq!(strip("D:\\proj\\a.hpp", "C:\\proj"), None);
+        assert_eq!(strip("/proj/a.hpp", "proj"), None);
+        assert_eq!(strip("/project/a.hpp", "/proj"), None);
     }
 }
@@ -2,17 +2,30 @@
 
 // This reminds me of 8th grade
 
-const nav = document.querySelector('nav');
-const navModeBtns = nav.querySelector('.mode');
+const nav = document.querySelector('body > nav');
+// The single page export has no navbar, so everything using it is skipped
+const navModeBtns = nav?.querySelector('.mode');
 const mainBody = document.querySelector('body > main');
 const searchInput = document.getElementById('nav-search');
 const searchGlass = document.getElementById('nav-clear-glass');
 const searchX = document.getElementById('nav-clear-x');
 
+// Settings are passed as data attributes on the script tag, since strict
+// content security policies don't allow inline scripts to set them
+const SETTINGS = document.querySelector('script[data-output-url]')?.dataset ?? {};
+
+// The output URL may be relative to the current page, so resolve it once
+const OUTPUT_URL = new URL(`${SETTINGS.outputUrl ?? ''}/`, window.location.href)
+    .pathname.replace(/\/$/, '');
+
 let searchNav = undefined;
 let searchQuery = '';
 
-let memberFunctionsList = null;
+// Ranking settings and entries from search.json. Members are searched along
+// with the navbar, while entities only have their links' scores adjusted
+let searchIndex = null;
+let entityBoosts = new Map();
+const MEMBER_ICONS = { method: 'code', field: 'box', enumerator: 'hash' };
 
 function createCopyButton(icon, text, callback = undefined) {
     const button = document.createElement('button');
@@ -46,7 +59,7 @@ function createCopyButton(icon, text, callback = undefined) {
 }
 
 // Add copy button to code blocks
-Prism.hooks.add('complete', env => {
+window.Prism?.hooks.add('complete', env => {
     // Check if inline or actual code block (credit to line-numbers plugin)
     const pre = env.element.parentNode;
     if (!pre || !/pre/i.test(pre.nodeName)) {
@@ -76,7 +89,7 @@ Prism.hooks.add('complete', env => {
     ));
 });
 
-searchInput.addEventListener('input', e => {
+searchInput?.addEventListener('input', e => {
     search(e.target.value);
 });
 
@@ -126,9 +139,27 @@ function highlight() {
             }
         });
 
+    // Show the code tab that was last picked
+    const codeTab = localStorage.getItem('code-tab');
+    if (codeTab) {
+        selectCodeTab(codeTab);
+    }
+
     Prism.highlightAll();
     feather.replace();
-    twemoji.parse(document.body);
+    // Custom head templates may not set this
+    if (SETTINGS.twemoji !== 'false') {
+        twemoji.parse(document.body);
+    }
+}
+
+function selectCodeTab(name) {
+    document.querySelectorAll('.code-tabs').forEach(tabs => {
+        // Only switch groups that have a tab with this name
+        if (!tabs.querySelector(`:scope > pre[data-tab="${CSS.escape(name)}"]`)) return;
+        tabs.querySelectorAll(':scope > .code-tab-buttons > button, :scope > pre')
+            .forEach(el => el.classList.toggle('selected', el.dataset.tab === name));
+    });
 }
 
 function clearSearch() {
@@ -152,25 +183,56 @@ function debounce(func, delay) {
 
 function searchActually(query) {
     searchQuery = query;
-    if (!memberFunctionsList && selectedNavTab() == 'entities') {
-        fetch(`${FLASH_OUTPUT_URL}/functions.json`)
+    if (!searchIndex) {
+        // Don't fetch it again while it's loading or if it's missing
+        searchIndex = { ranking: null, entries: [] };
+        fetch(`${OUTPUT_URL}/search.json`)
         .then(res => res.json())
         .then(res => {
-            memberFunctionsList = res;
+            searchIndex = res;
+            entityBoosts = new Map(
+                res.entries
+                    .filter(entry => !entry.member)
+                    .map(entry => [normalizePath(`${OUTPUT_URL}${entry.url}`), entry.boost])
+            );
             searchActually(searchQuery);
-        });
+        })
+        .catch(_ => {});
     }
     updateNav();
 }
 
 const search = debounce(searchActually, 50);
 
+function normalizePath(path) {
+    return path.replace(/\/$/, '');
+}
+
+// Adjust the score of a match with the ranking settings, boosting names that
+// are exactly the query or start with it
+function rankMatch(score, name, boost) {
+    const ranking = searchIndex?.ranking;
+    if (!ranking) {
+        return score;
+    }
+    const query = searchQuery.replace(/\s/g, '').split(/::|\//).at(-1).toLowerCase();
+    name = name.toLowerCase();
+    if (name === query) {
+        score += ranking.exact;
+    }
+    else if (name.startsWith(query)) {
+        score += ranking.prefix;
+    }
+    return score + boost;
+}
+
 function getFullName(node) {
     let parent = node;
     const result = [node.textContent.trim()];
     while (parent.parentElement) {
         parent = parent.parentElement;
-        if (parent.tagName === 'DETAILS') {
+        // Sections group entities by source, they aren't part of the name
+        if (parent.tagName === 'DETAILS' && !parent.classList.contains('section')) {
             result.splice(0, 0, parent.querySelector('summary').textContent.trim());
         }
     }
@@ -186,6 +248,11 @@ function furryMatch(str, query) {
         return undefined;
     }
 
+    // without fuzzy matching the query has to be in the string as is
+    if (searchIndex?.ranking?.fuzzy === false && !str.toLowerCase().includes(query.toLowerCase())) {
+        return undefined;
+    }
+
     let score = 0;
     let matchedString = '';
     let toMatch = 0;
@@ -296,13 +363,13 @@ function selectedNavTab() {
 function updateNav() {
     if (searchQuery.length) {
         // hide current navigation
-        currentNav().style.display = 'none';
+        currentNav().hidden = true;
         if (searchNav) {
             searchNav.remove();
         }
 
-        searchGlass.style.display = 'none';
-        searchX.style.display = null;
+        searchGlass.hidden = true;
+        searchX.hidden = false;
 
         const searchResults = document.createElement('div');
         searchResults.classList.add('content');
@@ -312,8 +379,9 @@ function updateNav() {
 
         const results = [];
         currentNav().querySelectorAll('a').forEach(a => {
+            const name = getFullName(a);
             const match = furryMatchMany(
-                getFullName(a), searchQuery,
+                name, searchQuery,
                 selectedNavTab() == 'entities' ? '::' : '/'
             );
             if (match) {
@@ -324,25 +392,23 @@ function updateNav() {
                 if (svg) {
                     clone.insertBefore(svg.cloneNode(true), clone.firstChild);
                 }
-                results.push([match.score, clone]);
+                const boost = entityBoosts.get(normalizePath(a.pathname)) ?? 0;
+                results.push([rankMatch(match.score, name.at(-1), boost), clone]);
             }
         });
         if (selectedNavTab() == 'entities') {
-            memberFunctionsList?.forEach(fun => {
-                let funParts = fun.split('::');
-                const name = funParts.at(-1);
-                const match = furryMatchMany(funParts, searchQuery, '::');
+            searchIndex?.entries.filter(entry => entry.member).forEach(member => {
+                const match = furryMatchMany(member.parts, searchQuery, '::');
                 if (match) {
-                    funParts.pop();
                     const node = document.createElement('a');
-                    const url = `${FLASH_OUTPUT_URL}/classes/${funParts.join('/')}#${name.replace(/\s+\([0-9]+\)/, '')}`;
-                    node.setAttribute('href', url);
-                    node.addEventListener('click', e => {
-                        navigate(url);
-                        e.preventDefault();
-                    });
-                    node.innerHTML = feather.icons.code.toSvg({ 'class': 'icon class' }) + match.matched;
-                    results.push([match.score, node]);
+                    node.setAttribute('href', `${OUTPUT_URL}${member.url}`);
+                    node.dataset.navigate = '';
+                    node.innerHTML = feather.icons[MEMBER_ICONS[member.kind] ?? 'code']
+                        .toSvg({ 'class': `icon ${member.kind}` }) + match.matched;
+                    results.push([
+                        rankMatch(match.score, member.parts.at(-1), member.boost),
+                        node
+                    ]);
                 }
             });
         }
@@ -369,17 +435,12 @@ function updateNav() {
             searchNav = undefined;
         }
 
-        searchGlass.style.display = null;
-        searchX.style.display = 'none';
+        searchGlass.hidden = false;
+        searchX.hidden = true;
 
         // hide all navs but show the currently selected one
         nav.querySelectorAll('.content').forEach(content => {
-            if (content.getAttribute('id').replace('nav-content-', '') === selectedNavTab()) {
-                content.style.display = null;
-            }
-            else {
-                content.style.display = 'none'
-            }
+            content.hidden = content.getAttribute('id').replace('nav-content-', '') !== selectedNavTab();
         });
     }
 }
@@ -403,13 +464,18 @@ function scrollAndOpenElement(id) {
 }
 
 function showNav(id) {
-    [...navModeBtns.children].forEach(node => node.classList.remove('selected'));
-    navModeBtns.querySelector(`#nav-tab-${id}`).classList.add('selected');
+    [...navModeBtns.children].forEach(node => {
+        node.classList.remove('selected');
+        node.setAttribute('aria-selected', 'false');
+    });
+    const tab = navModeBtns.querySelector(`#nav-tab-${id}`);
+    tab.classList.add('selected');
+    tab.setAttribute('aria-selected', 'true');
     updateNav();
 }
 
 async function buildNav() {
-    const res = await fetch(`${FLASH_OUTPUT_URL}/nav.json`);
+    const res = await fetch(`${OUTPUT_URL}/nav.json`);
     const data = await res.json();
 
     function buildIconInto(parent, icon) {
@@ -446,6 +512,30 @@ async function buildNav() {
             } else {
                 return data.items.map(buildNavFor);
             }
+        } else if (data.type === "section") {
+            let elem = document.createElement("details");
+            elem.open = true;
+            elem.classList.add("root", "section");
+
+            let summary = document.createElement("summary");
+            let icon = document.createElement("i");
+            icon.setAttribute("data-feather", "chevron-right");
+            summary.appendChild(icon);
+            buildIconInto(summary, data.icon);
+            summary.insertAdjacentText('beforeend', data.name);
+            elem.appendChild(summary);
+
+            let div = document.createElement("div");
+            if (data.description) {
+                let description = document.createElement("p");
+                description.classList.add("section-description");
+                description.innerText = data.description;
+                div.appendChild(description);
+            }
+            data.items.map(buildNavFor).forEach(x => div.appendChild(x));
+            elem.appendChild(div);
+
+            return elem;
         } else if (data.type === "dir") {
             let elem = document.createElement("details");
             elem.open = data.open;
@@ -465,8 +555,10 @@ async function buildNav() {
             return elem;
         } else if (data.type === "link") {
             let elem = document.createElement("a");
-            elem.onclick = () => { return navigate(data.url); };
-            elem.href = data.url;
+            // Relative links are relative to the output root
+            const url = data.url.startsWith('/') ? data.url : `${OUTPUT_URL}/${data.url}`;
+            elem.href = url;
+            elem.dataset.navigate = '';
             buildIconInto(elem, data.icon);
             elem.insertAdjacentText('beforeend', data.name);
             return elem;
@@ -481,10 +573,16 @@ async function buildNav() {
     }
     appendChildren(document.querySelector('#nav-content-entities'), buildNavFor(data.entities));
 	appendChildren(document.querySelector('#nav-content-tutorials'), buildNavFor(data.tutorials));
+    appendChildren(document.querySelector('#nav-content-groups'), buildNavFor(data.groups));
+    appendChildren(document.querySelector('#nav-content-files'), buildNavFor(data.files));
+    // Only show the groups tab if the project uses them
+    if (!data.groups.items.length) {
+        document.querySelector('#nav-tab-groups').hidden = true;
+    }
 }
 
 function navigate(url) {
-    const trueURL = url.split('#').shift();
+    const trueURL = url.split('#').shift().replace(/\/index\.html$/, '');
     const head = url.split('#').pop();
     Promise.all([
         fetch(`${trueURL}/content.html`).then(res => res.text()),
@@ -497,18 +595,29 @@ function navigate(url) {
             document.title = metadata.title;
             mainBody.innerHTML = content;
             mainBody.scrollTo({ left: 0, top: 0 });
-            nav.querySelectorAll('a.selected').forEach(a => a.classList.remove('selected'));
-            nav.querySelector(`[href="${url}"]`)?.classList.add('selected');
+            nav.querySelectorAll('a.selected').forEach(a => {
+                a.classList.remove('selected');
+                a.removeAttribute('aria-current');
+            });
+            const selected = [...nav.querySelectorAll('a')].find(a => a.href === url);
+            selected?.classList.add('selected');
+            selected?.setAttribute('aria-current', 'page');
             highlight();
             // hide navbar
-            nav.classList.add('collapsed');
+            toggleMenu(false);
+            // Let screen readers know the content changed
+            mainBody.focus({ preventScroll: true });
             scrollAndOpenElement(head);
         })
         .catch(err => {
             console.error(err);
+            // Fall back to a regular page load (pages can't be fetched when
+            // browsing from disk)
+            window.location.href = window.location.protocol === 'file:' && !url.includes('.html') ?
+                `${trueURL}/index.html` : url;
         });
 
-    // Prevent calling default onclick handler
+    // Prevent the default link behaviour when used as a handler
     return false;
 }
 
@@ -524,10 +633,13 @@ document.querySelectorAll('[data-pick-theme]').forEach(btn => {
     btn.addEventListener('click', e => {
         pickTheme(btn.getAttribute('data-pick-theme'));
         // deselect other buttons
-        btn.parentElement.querySelectorAll('.selected')
-            .forEach(b => b.classList.remove('selected'));
+        btn.parentElement.querySelectorAll('.selected').forEach(b => {
+            b.classList.remove('selected');
+            b.setAttribute('aria-pressed', 'false');
+        });
         // select this one
         btn.classList.add('selected');
+        btn.setAttribute('aria-pressed', 'true');
     });
 });
 
@@ -542,11 +654,14 @@ function pickTheme(name) {
     localStorage.setItem('theme', name);
 }
 
-function toggleMenu() {
-    nav.classList.toggle('collapsed');
+function toggleMenu(open = undefined) {
+    const collapsed = nav.classList.toggle('collapsed', open === undefined ? undefined : !open);
+    document.getElementById('menu-toggle')?.setAttribute('aria-expanded', `${!collapsed}`);
 }
 
-await buildNav();
+if (nav) {
+    await buildNav();
+}
 
 // Highlight everything
 try {
@@ -562,7 +677,8 @@ try {
     while (currentUrl.endsWith('/')) {
         currentUrl = currentUrl.slice(0, -1)
     }
-    const a = nav.querySelector(`[href="${currentUrl}"]`);
+    // Links may end with a slash depending on the trailing slash policy
+    const a = nav?.querySelector(`[href="${currentUrl}"], [href="${currentUrl}/"]`);
     if (a) {
         // Find the parent nav section of the selected item
         let parentNav = a.closest('.content');
@@ -577,12 +693,70 @@ try {
 
         // Scroll the selected item into view
         a.classList.add('selected');
+        a.setAttribute('aria-current', 'page');
         a.scrollIntoView(false);
 
         scrollAndOpenElement(window.location.hash);
     }
 }
 
+// Switch every tab group on the page to the picked tab, since they are
+// usually all about the same platform or language
+document.addEventListener('click', e => {
+    const btn = e.target.closest('.code-tab-button');
+    if (btn) {
+        selectCodeTab(btn.dataset.tab);
+        localStorage.setItem('code-tab', btn.dataset.tab);
+    }
+});
+
+// Hide the banner if it has been dismissed before
+{
+    const dismiss = document.querySelector('.banner-dismiss');
+    if (dismiss && localStorage.getItem('banner-dismissed') === dismiss.dataset.bannerId) {
+        dismiss.closest('.banner').remove();
+    }
+    dismiss?.addEventListener('click', () => {
+        localStorage.setItem('banner-dismissed', dismiss.dataset.bannerId);
+        dismiss.closest('.banner').remove();
+    });
+}
+
+// YouTube videos only show their thumbnail until they're clicked, so nothing
+// else is loaded from YouTube before then
+document.addEventListener('click', e => {
+    const embed = e.target.closest('.video-play')?.closest('[data-youtube]');
+    if (!embed) {
+        return;
+    }
+    const frame = document.createElement('iframe');
+    frame.src = `https://www.youtube-nocookie.com/embed/${encodeURIComponent(embed.dataset.youtube)}?autoplay=1`;
+    frame.title = embed.querySelector('.video-play').title;
+    frame.allow = 'autoplay; encrypted-media; picture-in-picture';
+    frame.allowFullscreen = true;
+    embed.replaceChildren(frame);
+});
+
+// Load linked pages without reloading the whole page, unless the link is
+// being opened in a new tab or window
+document.addEventListener('click', e => {
+    const a = e.target.closest('a[data-navigate]');
+    if (
+        !a || e.defaultPrevented || e.button !== 0 ||
+        e.ctrlKey || e.metaKey || e.shiftKey || e.altKey
+    ) {
+        return;
+    }
+    e.preventDefault();
+    navigate(a.href);
+});
+
+navModeBtns?.querySelectorAll('[data-nav-tab]').forEach(btn => {
+    btn.addEventListener('click', () => showNav(btn.dataset.navTab));
+});
+searchX?.addEventListener('click', () => clearSearch());
+document.getElementById('menu-toggle')?.addEventListener('click', () => toggleMenu());
+
 // Detect header link change
 window.addEventListener('hashchange', () => {
     scrollAndOpenElement(window.location.hash);